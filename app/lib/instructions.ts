@@ -14,13 +14,25 @@ function u64ToLeBytes(num: number): Buffer {
   return bn.toArrayLike(Buffer, 'le', 8);
 }
 
+// Helper to encode an optional 8-byte client ID as a Borsh Option
+function encodeClientId(clientId: Uint8Array | null): Buffer {
+  if (clientId === null) {
+    return Buffer.from([0]);
+  }
+  if (clientId.length !== 8) {
+    throw new Error('Client ID must be 8 bytes');
+  }
+  return Buffer.concat([Buffer.from([1]), Buffer.from(clientId)]);
+}
+
 export function createInitializeSplitInstruction(
   sender: PublicKey,
   recipient1: PublicKey,
   recipient2: PublicKey,
   recipient1Percentage: number,
   recipient2Percentage: number,
-  nonce: number
+  nonce: number,
+  clientId: Uint8Array | null = null
 ): TransactionInstruction {
   const programId = new PublicKey(PROGRAM_ID_STRING);
   
@@ -36,13 +48,25 @@ export function createInitializeSplitInstruction(
     programId
   );
 
+  // Global program config (creation fee) and the treasury that collects it
+  const [programConfigPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from('program_config')],
+    programId
+  );
+  const [treasuryPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from('treasury')],
+    programId
+  );
+
   // Serialize instruction data according to Anchor's format:
   // discriminator (8 bytes) + recipient1_percentage (u8) + recipient2_percentage (u8) + nonce (u64 LE)
+  // + client_id (Option<[u8; 8]>)
   const data = Buffer.concat([
     DISCRIMINATORS.INITIALIZE_SPLIT,
     Buffer.from([recipient1Percentage]),
     Buffer.from([recipient2Percentage]),
     nonceBuffer,
+    encodeClientId(clientId),
   ]);
 
   return new TransactionInstruction({
@@ -52,6 +76,8 @@ export function createInitializeSplitInstruction(
       { pubkey: recipient1, isSigner: false, isWritable: false },
       { pubkey: recipient2, isSigner: false, isWritable: false },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: programConfigPDA, isSigner: false, isWritable: false },
+      { pubkey: treasuryPDA, isSigner: false, isWritable: true },
     ],
     programId,
     data,
@@ -63,7 +89,9 @@ export function createExecuteSplitInstruction(
   recipient1: PublicKey,
   recipient2: PublicKey,
   amount: BN,
-  nonce: number
+  nonce: number,
+  validUntilSlot = 0,
+  clientId: Uint8Array | null = null
 ): TransactionInstruction {
   const programId = new PublicKey(PROGRAM_ID_STRING);
   
@@ -79,11 +107,21 @@ export function createExecuteSplitInstruction(
     programId
   );
 
+  // Global program config holding the per-epoch volume circuit breaker
+  const [programConfigPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from('program_config')],
+    programId
+  );
+
   // Serialize instruction data according to Anchor's format:
-  // discriminator (8 bytes) + amount (u64 LE)
+  // discriminator (8 bytes) + amount (u64 LE) + valid_until_slot (u64 LE, 0 = no bound)
+  // + client_id (Option<[u8; 8]>) + preconditions (Option, always none here)
   const data = Buffer.concat([
     DISCRIMINATORS.EXECUTE_SPLIT,
     amount.toArrayLike(Buffer, 'le', 8),
+    u64ToLeBytes(validUntilSlot),
+    encodeClientId(clientId),
+    Buffer.from([0]),
   ]);
 
   return new TransactionInstruction({
@@ -93,6 +131,7 @@ export function createExecuteSplitInstruction(
       { pubkey: recipient1, isSigner: false, isWritable: true },
       { pubkey: recipient2, isSigner: false, isWritable: true },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: programConfigPDA, isSigner: false, isWritable: true },
     ],
     programId,
     data,
@@ -0,0 +1,229 @@
+//! Client-side interface for programs and tools that call solsplit, compiled
+//! with the `interface` feature. The argument structs are the ones Anchor
+//! generates for the on-chain dispatcher, so builders can't drift from what the
+//! program deserializes.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData};
+
+pub use crate::instruction::{
+    ExecuteFromDeposit as ExecuteFromDepositArgs, ExecuteSplit as ExecuteSplitArgs,
+    InitializeSplit as InitializeSplitArgs,
+    InitializeSplitWithOptions as InitializeSplitWithOptionsArgs,
+};
+pub use crate::{
+    private_recipient_key, BatchEntryReport, BatchReport, BatchStatus, DistributionPreview,
+    EffectiveTerms, SplitOptions, TokenSplitQuote,
+};
+
+/// Address of a sender's split config for `nonce`
+pub fn split_config_address(sender: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"split_config", sender.as_ref(), &nonce.to_le_bytes()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Address of a sender's split config for `nonce` on a deployment whose
+/// program config records `genesis_guard`; all zeroes for unguarded ones
+pub fn guarded_split_config_address(sender: &Pubkey, nonce: u64, genesis_guard: [u8; 8]) -> Pubkey {
+    split_config_address(sender, crate::guarded_nonce(nonce, genesis_guard))
+}
+
+/// Terms a committed config's address is claimed to commit to
+pub struct CommittedTerms {
+    pub recipient1: Pubkey,
+    pub recipient2: Pubkey,
+    pub recipient1_percentage: u8,
+    pub recipient2_percentage: u8,
+    pub options: SplitOptions,
+}
+
+impl CommittedTerms {
+    /// Canonical hash of these terms, as `initialize_split_committed` derives it
+    pub fn hash(&self) -> [u8; 32] {
+        crate::terms_hash(
+            &self.recipient1,
+            &self.recipient2,
+            self.recipient1_percentage,
+            self.recipient2_percentage,
+            &self.options,
+        )
+    }
+}
+
+/// Address of a sender's config for `nonce` committed to `terms`
+pub fn committed_split_config_address(
+    sender: &Pubkey,
+    nonce: u64,
+    genesis_guard: [u8; 8],
+    terms: &CommittedTerms,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"split_config",
+            sender.as_ref(),
+            &crate::guarded_nonce(nonce, genesis_guard).to_le_bytes(),
+            &terms.hash(),
+        ],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Whether `address` is the config committed to the `claimed` terms, checked
+/// by re-deriving it rather than reading the account
+pub fn verify_committed_terms(
+    address: &Pubkey,
+    sender: &Pubkey,
+    nonce: u64,
+    genesis_guard: [u8; 8],
+    claimed: &CommittedTerms,
+) -> bool {
+    committed_split_config_address(sender, nonce, genesis_guard, claimed) == *address
+}
+
+/// Address of the global program config
+pub fn program_config_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"program_config"], &crate::ID).0
+}
+
+/// Address of the protocol treasury
+pub fn treasury_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"treasury"], &crate::ID).0
+}
+
+/// Accounts shared by the builders below. Optional accounts are always
+/// passed as absent.
+pub struct SplitAccounts {
+    pub sender: Pubkey,
+    pub recipient1: Pubkey,
+    pub recipient2: Pubkey,
+    pub nonce: u64,
+    pub genesis_guard: [u8; 8],
+}
+
+impl SplitAccounts {
+    fn split_config(&self) -> Pubkey {
+        guarded_split_config_address(&self.sender, self.nonce, self.genesis_guard)
+    }
+}
+
+/// Build `initialize_split` for the given percentages
+pub fn initialize_split_ix(
+    accounts: &SplitAccounts,
+    recipient1_percentage: u8,
+    recipient2_percentage: u8,
+) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new(accounts.split_config(), false),
+        AccountMeta::new(accounts.sender, true),
+        AccountMeta::new_readonly(accounts.recipient1, false),
+        AccountMeta::new_readonly(accounts.recipient2, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(program_config_address(), false),
+        AccountMeta::new(treasury_address(), false),
+    ];
+    // outbox, sender_settings, fee_exemption, client_registry
+    metas.extend(absent(4));
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: metas,
+        data: InitializeSplitArgs {
+            recipient1_percentage,
+            recipient2_percentage,
+            nonce: accounts.nonce,
+            client_id: None,
+        }
+        .data(),
+    }
+}
+
+/// Build `execute_split` for `amount`, with `valid_until_slot` 0 for no bound
+pub fn execute_split_ix(accounts: &SplitAccounts, amount: u64, valid_until_slot: u64) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new(accounts.split_config(), false),
+        AccountMeta::new(accounts.sender, true),
+        AccountMeta::new(accounts.recipient1, false),
+        AccountMeta::new(accounts.recipient2, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(program_config_address(), false),
+    ];
+    // outbox, history, reclaim_queue, recipient1_ledger, recipient2_ledger,
+    // client_registry
+    metas.extend(absent(6));
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: metas,
+        data: ExecuteSplitArgs {
+            amount,
+            valid_until_slot,
+            client_id: None,
+            preconditions: None,
+        }
+        .data(),
+    }
+}
+
+/// Build `execute_from_deposit` for `amount`, signed by `executor`
+pub fn execute_from_deposit_ix(accounts: &SplitAccounts, executor: &Pubkey, amount: u64) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new(accounts.split_config(), false),
+        AccountMeta::new_readonly(*executor, true),
+        AccountMeta::new(accounts.recipient1, false),
+        AccountMeta::new(accounts.recipient2, false),
+        AccountMeta::new(program_config_address(), false),
+    ];
+    // outbox, executor_bond, recipient1_ledger, recipient2_ledger
+    metas.extend(absent(4));
+
+    Instruction {
+        program_id: crate::ID,
+        accounts: metas,
+        data: ExecuteFromDepositArgs { amount }.data(),
+    }
+}
+
+/// Anchor reads an optional account passed as the program ID as absent
+fn absent(count: usize) -> impl Iterator<Item = AccountMeta> {
+    std::iter::repeat_n(AccountMeta::new_readonly(crate::ID, false), count)
+}
+
+/// Off-chain planner for asserting expected amounts without a runtime. It
+/// runs the same planning code as the program.
+pub mod mock {
+    use super::*;
+    use crate::SplitConfig;
+
+    /// A fresh, unexecuted config with the given terms and default options
+    pub fn config(
+        sender: Pubkey,
+        recipient1: Pubkey,
+        recipient2: Pubkey,
+        recipient1_percentage: u8,
+    ) -> SplitConfig {
+        SplitConfig {
+            sender,
+            recipient1,
+            recipient2,
+            recipient1_percentage,
+            recipient2_percentage: 100 - recipient1_percentage,
+            denomination: 1,
+            ..SplitConfig::default()
+        }
+    }
+
+    /// Split `amount` by recipient 1's percentage, recipient 2 taking the remainder
+    pub fn shares(amount: u64, recipient1_percentage: u8) -> Result<(u64, u64)> {
+        crate::compute_shares(amount, recipient1_percentage as u16 * 100)
+    }
+
+    /// What `execute_split` would do with `amount` at `now`
+    pub fn plan(config: &SplitConfig, amount: u64, now: i64) -> Result<EffectiveTerms> {
+        config.plan_execution(amount, now)
+    }
+}
@@ -1,11 +1,133 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::sysvar;
 use anchor_lang::system_program;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::{self, Burn, Mint, SyncNative, Token, TokenAccount, TransferChecked};
+use solana_sha256_hasher::hashv;
+use solana_stake_interface::instruction as stake_instruction;
+use solana_stake_interface::state::{Authorized, Lockup, StakeAuthorize, StakeStateV2};
 
 declare_id!("7dChiG6VDtneaVXxd2gdtg6MxsPXTvYUnEPEgP4sFKts");
 
+#[cfg(feature = "interface")]
+pub mod interface;
+
 // Minimum split amount to prevent dust attacks
 const MINIMUM_SPLIT_AMOUNT: u64 = 1000; // 0.000001 SOL
 
+// Maximum number of routes a single sender's route table can hold
+const MAX_ROUTES: usize = 16;
+
+// Maximum number of depositors an escrow config can allow besides its sender
+const MAX_ALLOWED_DEPOSITORS: usize = 8;
+
+// Version of the event layouts, bumped whenever any event's fields change
+pub const EVENT_SCHEMA_VERSION: u8 = 10;
+
+// Basis point denominator for fractional settings
+const BPS_DENOMINATOR: u64 = 10_000;
+
+// Number of lifecycle records kept in a sender's outbox ring buffer
+const OUTBOX_CAPACITY: usize = 64;
+
+// Length of the window a delegate's execution limit applies to
+const DELEGATE_LIMIT_WINDOW_SECONDS: i64 = 86_400;
+
+// Maximum number of vaults a single sweep_vaults call may process
+const MAX_SWEEP_BATCH: usize = 32;
+
+// Maximum number of accounts a single teardown_sender call may close
+const MAX_TEARDOWN_BATCH: usize = 24;
+
+// Maximum number of configs a single execute_splits_batch call may execute
+const MAX_EXECUTE_BATCH: usize = 8;
+
+// Maximum number of configs a single batch_upgrade_to_bps call may migrate
+const MAX_UPGRADE_BATCH: usize = 24;
+
+// How long an executor bond stays locked after it was last posted or used
+const BOND_WITHDRAWAL_COOLDOWN_SECONDS: i64 = 7 * 86_400;
+
+// Maximum number of executed configs a reclaim queue holds between sweeps
+const RECLAIM_QUEUE_CAPACITY: usize = 24;
+
+// Maximum number of swap programs the program config can whitelist
+const MAX_SWAP_PROGRAMS: usize = 8;
+
+// How long after creation a cancelled config gets its creation fee back
+const CREATION_FEE_REFUND_WINDOW_SECONDS: i64 = 86_400;
+
+// Maximum number of linked configs one execution may cascade through
+const MAX_CASCADE_DEPTH: usize = 3;
+
+// Maximum length of the memo attached to a single execution
+const MAX_EXECUTION_MEMO_LEN: usize = 128;
+
+// Maximum number of dated entries in each recipient's claim queue
+const CLAIM_QUEUE_CAPACITY: usize = 16;
+
+// Maximum number of blackout windows a config can carry
+const MAX_BLACKOUT_WINDOWS: usize = 4;
+
+// Upper bound on back-to-back windows walked when finding the next allowed time
+const MAX_BLACKOUT_STEPS: usize = 64;
+
+// Maximum number of amount brackets in a config's ratio table
+const MAX_RATIO_TIERS: usize = 8;
+
+// Split config layouts: the fixed recipient1/recipient2 pair, or a recipient
+// set account listing up to MAX_STAGED_RECIPIENTS recipients, of which only
+// sets of at most MAX_MULTI_RECIPIENTS execute in one transaction. A config
+// whose staged split is being committed is held in LAYOUT_STAGED until it
+// completes.
+const LAYOUT_TWO_RECIPIENTS: u8 = 0;
+const LAYOUT_MULTI_RECIPIENT: u8 = 1;
+const LAYOUT_STAGED: u8 = 2;
+const MAX_MULTI_RECIPIENTS: usize = 8;
+
+// Maximum number of recipients a staged split may pay
+const MAX_STAGED_RECIPIENTS: usize = 32;
+
+// Largest part of a recipient's share that may be withheld
+const MAX_WITHHOLDING_BPS: u16 = 5_000;
+
+// Maximum number of approvers a config can list, one bit each in its approvals
+const MAX_APPROVERS: usize = 8;
+
+// Largest part of an escrow deposit a sender's refund may owe the recipients
+const MAX_CANCEL_PENALTY_BPS: u16 = 2_500;
+
+// SPL Token and Associated Token Account program IDs
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const ATA_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Emit an event about `split_config`, folding the exact bytes logged into
+/// its history digest when the config tracks one
+macro_rules! emit_recorded {
+    ($split_config:expr, $event:expr) => {{
+        let data = anchor_lang::Event::data(&$event);
+        $split_config.record_history(&data);
+        anchor_lang::solana_program::log::sol_log_data(&[&data]);
+    }};
+}
+
+/// Log a lamport execution's `SplitExecuted`, as the byte-packed record from
+/// `SplitExecuted::compact` for configs with `compact_events`
+macro_rules! emit_split_executed {
+    ($split_config:expr, $event:expr) => {{
+        let event: SplitExecuted = $event;
+        if $split_config.compact_events {
+            let data = event.compact($split_config.nonce);
+            $split_config.record_history(&data);
+            anchor_lang::solana_program::log::sol_log_data(&[&data]);
+        } else {
+            emit_recorded!($split_config, event);
+        }
+    }};
+}
+
 #[program]
 pub mod solsplit {
     use super::*;
@@ -16,338 +138,9764 @@ pub mod solsplit {
         recipient1_percentage: u8,
         recipient2_percentage: u8,
         nonce: u64,
+        client_id: Option<[u8; 8]>,
     ) -> Result<()> {
-        // Validate percentages sum to exactly 100
-        require!(
-            recipient1_percentage + recipient2_percentage == 100,
-            SplitError::InvalidPercentages
-        );
+        process_initialize_split(
+            ctx,
+            recipient1_percentage,
+            recipient2_percentage,
+            nonce,
+            SplitOptions {
+                client_id,
+                ..SplitOptions::default()
+            },
+            0,
+            0,
+        )
+    }
 
-        // Validate percentages are non-zero
-        require!(
-            recipient1_percentage > 0 && recipient2_percentage > 0,
-            SplitError::ZeroPercentage
-        );
+    /// Initialize an escrow split and fund it with its initial deposit in the
+    /// same instruction
+    pub fn initialize_and_deposit(
+        ctx: Context<InitializeSplit>,
+        recipient1_percentage: u8,
+        recipient2_percentage: u8,
+        nonce: u64,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, SplitError::AmountTooSmall);
 
-        // Validate recipients are not the same
-        require!(
-            ctx.accounts.recipient1.key() != ctx.accounts.recipient2.key(),
-            SplitError::DuplicateRecipient
-        );
+        process_initialize_split(
+            ctx,
+            recipient1_percentage,
+            recipient2_percentage,
+            nonce,
+            SplitOptions {
+                escrow: true,
+                ..SplitOptions::default()
+            },
+            0,
+            amount,
+        )
+    }
 
-        // Validate recipients are not system program
-        require!(
-            ctx.accounts.recipient1.key() != ctx.accounts.system_program.key(),
-            SplitError::InvalidRecipient
-        );
+    /// Initialize a new split configuration with optional settings
+    pub fn initialize_split_with_options(
+        ctx: Context<InitializeSplit>,
+        recipient1_percentage: u8,
+        recipient2_percentage: u8,
+        nonce: u64,
+        options: SplitOptions,
+    ) -> Result<()> {
+        process_initialize_split(
+            ctx,
+            recipient1_percentage,
+            recipient2_percentage,
+            nonce,
+            options,
+            0,
+            0,
+        )
+    }
+
+    /// Initialize a new split configuration, filling every override left unset
+    /// from the sender's settings account
+    pub fn initialize_split_with_defaults(
+        ctx: Context<InitializeSplit>,
+        recipient1_percentage: u8,
+        recipient2_percentage: u8,
+        nonce: u64,
+        overrides: SplitOverrides,
+    ) -> Result<()> {
+        let settings = ctx
+            .accounts
+            .sender_settings
+            .as_ref()
+            .ok_or(SplitError::SenderSettingsMissing)?;
+        let (options, defaults_applied) = settings.defaults.resolve(overrides);
+
+        process_initialize_split(
+            ctx,
+            recipient1_percentage,
+            recipient2_percentage,
+            nonce,
+            options,
+            defaults_applied,
+            0,
+        )
+    }
+
+    /// Initialize a split whose recipients both sign the transaction, proving
+    /// control of their keys, and mark it as having verified recipients
+    pub fn initialize_split_verified(
+        ctx: Context<InitializeSplit>,
+        recipient1_percentage: u8,
+        recipient2_percentage: u8,
+        nonce: u64,
+        options: SplitOptions,
+    ) -> Result<()> {
         require!(
-            ctx.accounts.recipient2.key() != ctx.accounts.system_program.key(),
-            SplitError::InvalidRecipient
+            ctx.accounts.recipient1.is_signer && ctx.accounts.recipient2.is_signer,
+            SplitError::RecipientSignatureRequired
         );
 
-        let split_config = &mut ctx.accounts.split_config;
-        let clock = Clock::get()?;
-        
-        split_config.sender = ctx.accounts.sender.key();
-        split_config.recipient1 = ctx.accounts.recipient1.key();
-        split_config.recipient2 = ctx.accounts.recipient2.key();
-        split_config.recipient1_percentage = recipient1_percentage;
-        split_config.recipient2_percentage = recipient2_percentage;
-        split_config.executed = false;
-        split_config.nonce = nonce;
-        split_config.created_at = clock.unix_timestamp;
-        split_config.executed_at = 0;
-        split_config.bump = ctx.bumps.split_config;
-
-        emit!(SplitInitialized {
-            sender: split_config.sender,
-            recipient1: split_config.recipient1,
-            recipient2: split_config.recipient2,
+        process_initialize_split(
+            ctx,
             recipient1_percentage,
             recipient2_percentage,
             nonce,
-            timestamp: clock.unix_timestamp,
-        });
+            options,
+            0,
+            0,
+        )
+    }
 
-        msg!(
-            "Split initialized: {}% / {}%, nonce: {}", 
-            recipient1_percentage, 
+    /// Initialize a split whose address also commits to the `terms_hash` of its
+    /// recipients, percentages and flags, so a funder given the address and
+    /// the claimed terms can check them by re-deriving it
+    pub fn initialize_split_committed<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeSplitCommitted<'info>>,
+        recipient1_percentage: u8,
+        recipient2_percentage: u8,
+        nonce: u64,
+        options: SplitOptions,
+    ) -> Result<()> {
+        let committed = terms_hash(
+            &ctx.accounts.recipient1.key(),
+            &ctx.accounts.recipient2.key(),
+            recipient1_percentage,
             recipient2_percentage,
-            nonce
+            &options,
         );
-        
+        let accounts = ctx.accounts;
+
+        // Initialization runs on copies of the accounts, written back below
+        let mut base = InitializeSplit {
+            split_config: accounts.split_config.clone(),
+            sender: accounts.sender.clone(),
+            recipient1: accounts.recipient1.clone(),
+            recipient2: accounts.recipient2.clone(),
+            system_program: accounts.system_program.clone(),
+            program_config: accounts.program_config.clone(),
+            treasury: accounts.treasury.clone(),
+            outbox: accounts.outbox.clone(),
+            sender_settings: None,
+            fee_exemption: accounts.fee_exemption.clone(),
+            client_registry: accounts.client_registry.clone(),
+        };
+        process_initialize_split(
+            Context::new(
+                ctx.program_id,
+                &mut base,
+                ctx.remaining_accounts,
+                InitializeSplitBumps {
+                    split_config: ctx.bumps.split_config,
+                    program_config: ctx.bumps.program_config,
+                    treasury: ctx.bumps.treasury,
+                },
+            ),
+            recipient1_percentage,
+            recipient2_percentage,
+            nonce,
+            options,
+            0,
+            0,
+        )?;
+
+        let mut split_config = base.split_config.into_inner();
+        split_config.terms_hash = committed;
+        accounts.split_config.set_inner(split_config);
+        if let (Some(outbox), Some(updated)) = (accounts.outbox.as_mut(), base.outbox) {
+            outbox.set_inner(updated.into_inner());
+        }
+
+        Ok(())
+    }
+
+    /// Create the sender's settings account holding defaults for new configs
+    pub fn initialize_sender_settings(
+        ctx: Context<InitializeSenderSettings>,
+        defaults: SenderDefaults,
+    ) -> Result<()> {
+        let sender_settings = &mut ctx.accounts.sender_settings;
+
+        sender_settings.sender = ctx.accounts.sender.key();
+        sender_settings.defaults = defaults;
+        sender_settings.bump = ctx.bumps.sender_settings;
+
+        msg!("Sender settings created for {}", sender_settings.sender);
+
+        Ok(())
+    }
+
+    /// Replace the sender's defaults. Existing configs keep the values they
+    /// were created with.
+    pub fn update_sender_settings(
+        ctx: Context<UpdateSenderSettings>,
+        defaults: SenderDefaults,
+    ) -> Result<()> {
+        ctx.accounts.sender_settings.defaults = defaults;
+
+        msg!("Sender settings updated for {}", ctx.accounts.sender.key());
+
+        Ok(())
+    }
+
+    /// Close the sender's settings account, reclaiming its rent
+    pub fn close_sender_settings(ctx: Context<CloseSenderSettings>) -> Result<()> {
+        msg!("Sender settings closed for {}", ctx.accounts.sender.key());
+
+        Ok(())
+    }
+
+    /// Execute the split by transferring SOL to recipients, optionally no later
+    /// than `valid_until_slot` (zero disables the bound). `preconditions` pin
+    /// the state the transaction was signed against.
+    pub fn execute_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteSplit<'info>>,
+        amount: u64,
+        valid_until_slot: u64,
+        client_id: Option<[u8; 8]>,
+        preconditions: Option<ExecutionPreconditions>,
+    ) -> Result<()> {
+        if let Some(preconditions) = preconditions {
+            preconditions.check(&ctx.accounts.split_config, Clock::get()?.unix_timestamp)?;
+        }
+
+        process_execute_split(
+            ctx.accounts,
+            ctx.remaining_accounts,
+            ctx.bumps.history,
+            amount,
+            [0u8; 16],
+            valid_until_slot,
+            ExecutionNote {
+                client_id,
+                ..ExecutionNote::default()
+            },
+        )
+    }
+
+    /// Execute the split like `execute_split` and close it in the same
+    /// instruction, reclaiming its rent as `close_split` would. Either both
+    /// happen or neither does.
+    pub fn execute_and_close_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteAndCloseSplit<'info>>,
+        amount: u64,
+        valid_until_slot: u64,
+    ) -> Result<()> {
+        let accounts = ctx.accounts;
+
+        process_execute_split(
+            &mut accounts.execute,
+            ctx.remaining_accounts,
+            ctx.bumps.execute.history,
+            amount,
+            [0u8; 16],
+            valid_until_slot,
+            ExecutionNote::default(),
+        )?;
+
+        let execute = &mut accounts.execute;
+        let split_config = &execute.split_config;
+
+        // Credited shares belong to the recipients until claimed
+        require!(!split_config.has_outstanding_claims(), SplitError::ClaimsOutstanding);
+
+        // A queued config must stay open for sweep_reclaim
+        require!(!split_config.defer_reclaim, SplitError::ReclaimDeferred);
+
+        emit!(SplitClosed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            topic: split_config.topic,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_outbox(
+            &mut execute.outbox,
+            split_config.key(),
+            LifecycleEvent::Closed,
+            0,
+        )?;
+
+        if split_config.close_residual_to_recipients {
+            distribute_residual(split_config, Some(&execute.recipient1), Some(&execute.recipient2))?;
+        }
+
+        distribute_rent(
+            split_config,
+            &execute.sender,
+            accounts.rent_split_destination.as_ref(),
+        )?;
+
+        split_config.close(execute.sender.to_account_info())?;
+
+        msg!("Split executed and closed, nonce: {}", split_config.nonce);
+
         Ok(())
     }
 
-    /// Execute the split by transferring SOL to recipients
-    pub fn execute_split(ctx: Context<ExecuteSplit>, amount: u64) -> Result<()> {
+    /// Execute the split like `execute_split`, recording `memo` with the
+    /// execution for audit. Configs requiring a memo only execute this way.
+    pub fn execute_split_with_memo<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteSplit<'info>>,
+        amount: u64,
+        valid_until_slot: u64,
+        memo: String,
+    ) -> Result<()> {
+        process_execute_split(
+            ctx.accounts,
+            ctx.remaining_accounts,
+            ctx.bumps.history,
+            amount,
+            [0u8; 16],
+            valid_until_slot,
+            ExecutionNote {
+                memo: Some(memo),
+                ..ExecutionNote::default()
+            },
+        )
+    }
+
+    /// Execute the split across the config's recipients, passed as remaining
+    /// accounts in order. Multi-recipient configs divide `amount` by each
+    /// recipient's basis points with the last taking the rounding remainder;
+    /// two-recipient configs split exactly as `execute_split`, which remains
+    /// the cheaper path for them. Sets too large for one transaction must be
+    /// staged instead.
+    pub fn execute_split_multi<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteSplitMulti<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         let split_config = &mut ctx.accounts.split_config;
+        let sender = &ctx.accounts.sender;
+        let clock = Clock::get()?;
 
-        // Ensure split hasn't been executed yet
-        require!(!split_config.executed, SplitError::AlreadyExecuted);
+        split_config.verify()?;
 
-        // Validate sender matches the original configuration
-        require!(
-            split_config.sender == ctx.accounts.sender.key(),
-            SplitError::UnauthorizedSender
-        );
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+        require!(!split_config.escrow, SplitError::EscrowConfig);
+        require!(split_config.deposited_amount == 0, SplitError::EscrowNotEmpty);
+        require!(split_config.supports_multi_recipients(), SplitError::MultiRecipientUnsupported);
+        require!(split_config.layout != LAYOUT_STAGED, SplitError::SplitStaged);
+        split_config.check_execution_window(clock.unix_timestamp)?;
+        split_config.consume_approvals()?;
 
-        // Validate recipients match configuration
+        let terms = split_config.plan_execution(amount, clock.unix_timestamp)?;
+        let (recipients, shares) = match split_config.layout {
+            LAYOUT_MULTI_RECIPIENT => {
+                let set = ctx
+                    .accounts
+                    .recipient_set
+                    .as_ref()
+                    .ok_or(SplitError::RecipientSetRequired)?;
+                require!(
+                    set.shares.len() <= MAX_MULTI_RECIPIENTS,
+                    SplitError::RecipientSetTooLarge
+                );
+                (set.recipients(), set.payouts(amount)?)
+            }
+            _ => (
+                vec![split_config.recipient1, split_config.recipient2],
+                vec![terms.amount1, terms.amount2],
+            ),
+        };
+
+        // Capped overflow never leaves the sender
+        let delivered = amount - terms.overflow;
+
+        let sender_balance = sender.get_lamports();
+        require!(sender_balance >= delivered, SplitError::InsufficientBalance);
         require!(
-            split_config.recipient1 == ctx.accounts.recipient1.key(),
-            SplitError::InvalidRecipient
+            sender_balance - delivered >= split_config.sender_min_balance,
+            SplitError::SenderBalanceBelowMinimum
         );
+
+        apply_program_limits(&ctx.accounts.program_config, split_config, delivered)?;
+
         require!(
-            split_config.recipient2 == ctx.accounts.recipient2.key(),
+            ctx.remaining_accounts.len() == recipients.len(),
             SplitError::InvalidRecipient
         );
+        for ((info, recipient), share) in ctx.remaining_accounts.iter().zip(&recipients).zip(&shares) {
+            require_keys_eq!(*info.key, *recipient, SplitError::InvalidRecipient);
+            if *share == 0 {
+                continue;
+            }
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: sender.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                *share,
+            )?;
+        }
 
-        // Validate minimum amount to prevent dust
-        require!(amount >= MINIMUM_SPLIT_AMOUNT, SplitError::AmountTooSmall);
+        if split_config.layout == LAYOUT_TWO_RECIPIENTS {
+            split_config.record_received(terms.amount1, terms.amount2)?;
+        }
+        split_config.record_distribution(delivered)?;
+        split_config.mark_executed(clock.unix_timestamp);
 
-        // Calculate amount for recipient 1 with safe math
-        let amount1 = (amount as u128)
-            .checked_mul(split_config.recipient1_percentage as u128)
-            .ok_or(SplitError::MathOverflow)?
-            .checked_div(100)
-            .ok_or(SplitError::MathOverflow)? as u64;
+        emit_recorded!(split_config, MultiSplitExecuted {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            layout: split_config.layout,
+            recipients: recipients
+                .iter()
+                .map(|recipient| event_recipient(split_config, recipient))
+                .collect(),
+            amounts: shares,
+            total_amount: amount,
+            timestamp: clock.unix_timestamp,
+        });
 
-        // Calculate amount2 as remainder to avoid rounding errors
-        let amount2 = amount
-            .checked_sub(amount1)
-            .ok_or(SplitError::MathOverflow)?;
+        msg!("Split executed across {} recipients", ctx.remaining_accounts.len());
+
+        Ok(())
+    }
+
+    /// First phase of a split too large for one transaction: compute each
+    /// recipient's share of `amount` from the config's recipient set, or its
+    /// pair at the ratio an execution would use now, and lock the total into
+    /// the staged split account. `shares` must repeat those recipients and
+    /// basis points in order. The amounts are fixed here; `commit_split_page`
+    /// pays them.
+    pub fn stage_split(
+        ctx: Context<StageSplit>,
+        amount: u64,
+        shares: Vec<RecipientShare>,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let sender = &ctx.accounts.sender;
+        let clock = Clock::get()?;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+        require!(!split_config.escrow, SplitError::EscrowConfig);
+        require!(split_config.deposited_amount == 0, SplitError::EscrowNotEmpty);
+        require!(split_config.supports_multi_recipients(), SplitError::MultiRecipientUnsupported);
+        split_config.check_execution_window(clock.unix_timestamp)?;
+        split_config.consume_approvals()?;
+        require!(amount >= MINIMUM_SPLIT_AMOUNT, SplitError::AmountTooSmall);
 
-        // Verify sender has sufficient balance
-        let sender_balance = ctx.accounts.sender.get_lamports();
+        let config_shares = match split_config.layout {
+            LAYOUT_MULTI_RECIPIENT => ctx
+                .accounts
+                .recipient_set
+                .as_ref()
+                .ok_or(SplitError::RecipientSetRequired)?
+                .shares
+                .clone(),
+            _ => {
+                let recipient1_bps = split_config.recipient1_bps_for(amount, clock.unix_timestamp);
+                vec![
+                    RecipientShare { recipient: split_config.recipient1, bps: recipient1_bps },
+                    RecipientShare {
+                        recipient: split_config.recipient2,
+                        bps: BPS_DENOMINATOR as u16 - recipient1_bps,
+                    },
+                ]
+            }
+        };
         require!(
-            sender_balance >= amount,
-            SplitError::InsufficientBalance
+            shares.len() == config_shares.len()
+                && shares
+                    .iter()
+                    .zip(&config_shares)
+                    .all(|(share, expected)| {
+                        share.recipient == expected.recipient && share.bps == expected.bps
+                    }),
+            SplitError::StagedSharesMismatch
         );
 
-        // Transfer to recipient 1
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.sender.to_account_info(),
-                    to: ctx.accounts.recipient1.to_account_info(),
-                },
-            ),
-            amount1,
-        )?;
+        let sender_balance = sender.get_lamports();
+        require!(sender_balance >= amount, SplitError::InsufficientBalance);
+        require!(
+            sender_balance - amount >= split_config.sender_min_balance,
+            SplitError::SenderBalanceBelowMinimum
+        );
+
+        apply_program_limits(&ctx.accounts.program_config, split_config, amount)?;
 
-        // Transfer to recipient 2
         system_program::transfer(
             CpiContext::new(
                 ctx.accounts.system_program.to_account_info(),
                 system_program::Transfer {
-                    from: ctx.accounts.sender.to_account_info(),
-                    to: ctx.accounts.recipient2.to_account_info(),
+                    from: sender.to_account_info(),
+                    to: ctx.accounts.staged_split.to_account_info(),
                 },
             ),
-            amount2,
+            amount,
         )?;
 
-        // Mark as executed to prevent replay
-        let clock = Clock::get()?;
-        split_config.executed = true;
-        split_config.executed_at = clock.unix_timestamp;
+        let amounts = bps_payouts(&config_shares, amount)?;
+        let staged_split = &mut ctx.accounts.staged_split;
+        staged_split.split_config = split_config.key();
+        staged_split.total = amount;
+        staged_split.next_index = 0;
+        staged_split.prior_layout = split_config.layout;
+        staged_split.bump = ctx.bumps.staged_split;
+        staged_split.payouts = config_shares
+            .iter()
+            .zip(amounts)
+            .map(|(share, amount)| StagedPayout { recipient: share.recipient, amount })
+            .collect();
+        split_config.layout = LAYOUT_STAGED;
 
-        emit!(SplitExecuted {
+        emit_recorded!(split_config, SplitStaged {
+            schema_version: EVENT_SCHEMA_VERSION,
             sender: split_config.sender,
-            recipient1: split_config.recipient1,
-            recipient2: split_config.recipient2,
-            amount1,
-            amount2,
+            nonce: split_config.nonce,
+            recipients: staged_split.payouts.len() as u8,
             total_amount: amount,
             timestamp: clock.unix_timestamp,
         });
 
-        msg!(
-            "Split executed: {} lamports to recipient1, {} lamports to recipient2", 
-            amount1, 
-            amount2
-        );
+        msg!("Split staged across {} recipients", staged_split.payouts.len());
 
         Ok(())
     }
 
-    /// Cancel a split configuration before execution
-    pub fn cancel_split(ctx: Context<CancelSplit>) -> Result<()> {
-        let split_config = &ctx.accounts.split_config;
-
-        // Ensure split hasn't been executed yet
-        require!(!split_config.executed, SplitError::AlreadyExecuted);
+    /// Pay the next window of a staged split, its recipients passed as
+    /// remaining accounts in staged order. Anyone may commit a page. The page
+    /// paying the last recipient marks the config executed and returns the
+    /// staged account's rent to the sender.
+    pub fn commit_split_page(ctx: Context<CommitSplitPage>) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let staged_split = ctx
+            .accounts
+            .staged_split
+            .as_mut()
+            .ok_or(SplitError::SplitNotStaged)?;
+        let now = Clock::get()?.unix_timestamp;
 
-        // Validate sender matches
+        let start = staged_split.next_index as usize;
+        let end = start + ctx.remaining_accounts.len();
         require!(
-            split_config.sender == ctx.accounts.sender.key(),
-            SplitError::UnauthorizedSender
+            !ctx.remaining_accounts.is_empty() && end <= staged_split.payouts.len(),
+            SplitError::StagedPageMismatch
         );
 
-        emit!(SplitCancelled {
+        for (info, payout) in ctx.remaining_accounts.iter().zip(&staged_split.payouts[start..end]) {
+            require_keys_eq!(*info.key, payout.recipient, SplitError::StagedPageMismatch);
+            if payout.amount == 0 {
+                continue;
+            }
+            staged_split.sub_lamports(payout.amount)?;
+            info.add_lamports(payout.amount)?;
+        }
+        staged_split.next_index = end as u16;
+
+        let complete = end == staged_split.payouts.len();
+        emit_recorded!(split_config, StagedPageCommitted {
+            schema_version: EVENT_SCHEMA_VERSION,
             sender: split_config.sender,
             nonce: split_config.nonce,
-            timestamp: Clock::get()?.unix_timestamp,
+            first_index: start as u16,
+            paid: (end - start) as u16,
+            complete,
+            timestamp: now,
         });
 
-        msg!("Split cancelled, nonce: {}", split_config.nonce);
+        if complete {
+            split_config.layout = staged_split.prior_layout;
+            split_config.record_distribution(staged_split.total)?;
+            split_config.mark_executed(now);
+            staged_split.close(ctx.accounts.sender.to_account_info())?;
+
+            msg!("Staged split complete");
+        } else {
+            msg!("Staged split paid {} of {} recipients", end, staged_split.payouts.len());
+        }
 
-        // Account will be closed automatically via close constraint
         Ok(())
     }
 
-    /// Close a split configuration after execution to reclaim rent
-    pub fn close_split(ctx: Context<CloseSplit>) -> Result<()> {
-        let split_config = &ctx.accounts.split_config;
-
-        // Ensure split has been executed
-        require!(split_config.executed, SplitError::NotExecuted);
+    /// Execute several of the signing sender's configs, one per entry of
+    /// `amounts`, each passed as its config and two recipients in the remaining
+    /// accounts. Only configs paying their pair directly in lamports qualify.
+    /// Without `allow_partial` any failing entry fails the batch; with it,
+    /// executed configs are skipped and failing entries reported instead. The
+    /// outcome of every entry is returned via return data.
+    pub fn execute_splits_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteSplitsBatch<'info>>,
+        amounts: Vec<u64>,
+        allow_partial: bool,
+    ) -> Result<BatchReport> {
+        let sender = &ctx.accounts.sender;
+        let program_config = ctx.accounts.program_config.to_account_info();
+        let clock = Clock::get()?;
 
-        // Validate sender matches
         require!(
-            split_config.sender == ctx.accounts.sender.key(),
-            SplitError::UnauthorizedSender
+            !amounts.is_empty() && amounts.len() <= MAX_EXECUTE_BATCH,
+            SplitError::BatchTooLarge
+        );
+        require!(
+            ctx.remaining_accounts.len() == amounts.len() * 3,
+            SplitError::BatchAccountsMismatch
         );
 
-        emit!(SplitClosed {
-            sender: split_config.sender,
-            nonce: split_config.nonce,
-            timestamp: Clock::get()?.unix_timestamp,
+        let mut report = BatchReport { entries: Vec::with_capacity(amounts.len()) };
+        for (accounts, &amount) in ctx.remaining_accounts.chunks(3).zip(&amounts) {
+            let [config_info, recipient1, recipient2] = accounts else {
+                return err!(SplitError::BatchAccountsMismatch);
+            };
+
+            let outcome = prepare_batch_entry(sender.key, config_info, recipient1, recipient2, amount, &clock)
+                .map_err(|err| (BatchStatus::FailedValidation(error_code(&err)), err))
+                .and_then(|prepared| match prepared {
+                    Some((split_config, terms)) => {
+                        let delivered = amount - terms.overflow;
+                        check_sender_funds(sender, &split_config, delivered)
+                            .map_err(|err| (BatchStatus::FailedTransfer(error_code(&err)), err))?;
+                        apply_program_limits(&program_config, &split_config, delivered)
+                            .map_err(|err| (BatchStatus::FailedValidation(error_code(&err)), err))?;
+                        Ok(Some((split_config, terms)))
+                    }
+                    None => Ok(None),
+                });
+
+            let entry = match outcome {
+                Ok(Some((mut split_config, terms))) => {
+                    for (recipient, share) in [(recipient1, terms.amount1), (recipient2, terms.amount2)] {
+                        if share == 0 {
+                            continue;
+                        }
+                        system_program::transfer(
+                            CpiContext::new(
+                                ctx.accounts.system_program.to_account_info(),
+                                system_program::Transfer {
+                                    from: sender.to_account_info(),
+                                    to: recipient.clone(),
+                                },
+                            ),
+                            share,
+                        )?;
+                    }
+
+                    split_config.record_received(terms.amount1, terms.amount2)?;
+                    split_config.record_distribution(amount - terms.overflow)?;
+                    split_config.mark_executed(clock.unix_timestamp);
+
+                    emit_split_executed!(split_config, SplitExecuted {
+                        schema_version: EVENT_SCHEMA_VERSION,
+                        sender: split_config.sender,
+                        recipient1: event_recipient(&split_config, &split_config.recipient1),
+                        recipient2: event_recipient(&split_config, &split_config.recipient2),
+                        amount1: terms.amount1,
+                        amount2: terms.amount2,
+                        total_amount: amount,
+                        topic: split_config.topic,
+                        agreement_hash: split_config.agreement_hash,
+                        route_id: [0u8; 16],
+                        valid_until_slot: 0,
+                        slot: clock.slot,
+                        external_ref: split_config.external_ref,
+                        client: None,
+                        timestamp: clock.unix_timestamp,
+                    });
+                    split_config.exit(&crate::ID)?;
+
+                    BatchEntryReport {
+                        status: BatchStatus::Executed,
+                        amount1: terms.amount1,
+                        amount2: terms.amount2,
+                    }
+                }
+                Ok(None) if allow_partial => BatchEntryReport::unpaid(BatchStatus::SkippedAlreadyExecuted),
+                Ok(None) => return err!(SplitError::AlreadyExecuted),
+                Err((status, _)) if allow_partial => BatchEntryReport::unpaid(status),
+                Err((_, err)) => return Err(err),
+            };
+            report.entries.push(entry);
+        }
+
+        let count = |matches: fn(&BatchStatus) -> bool| {
+            report.entries.iter().filter(|entry| matches(&entry.status)).count() as u16
+        };
+        let executed = count(|status| *status == BatchStatus::Executed);
+        let skipped = count(|status| *status == BatchStatus::SkippedAlreadyExecuted);
+        let total_distributed = report
+            .entries
+            .iter()
+            .try_fold(0u64, |total, entry| {
+                total.checked_add(entry.amount1)?.checked_add(entry.amount2)
+            })
+            .ok_or(SplitError::MathOverflow)?;
+
+        emit!(BatchExecuted {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: sender.key(),
+            executed,
+            skipped,
+            failed: report.entries.len() as u16 - executed - skipped,
+            total_distributed,
+            timestamp: clock.unix_timestamp,
         });
 
-        msg!("Split closed, rent reclaimed, nonce: {}", split_config.nonce);
+        msg!("Batch executed {} of {} configs", executed, report.entries.len());
 
-        // Account will be closed automatically via close constraint
-        Ok(())
+        Ok(report)
     }
-}
 
-#[derive(Accounts)]
-#[instruction(recipient1_percentage: u8, recipient2_percentage: u8, nonce: u64)]
-pub struct InitializeSplit<'info> {
-    #[account(
-        init,
-        payer = sender,
-        space = 8 + SplitConfig::INIT_SPACE,
-        seeds = [b"split_config", sender.key().as_ref(), &nonce.to_le_bytes()],
-        bump
+    /// Migrate the sender's configs passed as remaining accounts from whole
+    /// percentage precision to basis points. Configs already in bps are skipped;
+    /// the number migrated is returned via return data.
+    pub fn batch_upgrade_to_bps<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchUpgradeToBps<'info>>,
+    ) -> Result<u32> {
+        let sender = ctx.accounts.sender.key();
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() <= MAX_UPGRADE_BATCH,
+            SplitError::UpgradeBatchTooLarge
+        );
+
+        let mut upgraded = 0u32;
+        for config_info in ctx.remaining_accounts {
+            let mut split_config = Account::<SplitConfig>::try_from(config_info)?;
+            require!(split_config.sender == sender, SplitError::UnauthorizedSender);
+            if split_config.recipient1_bps != 0 {
+                continue;
+            }
+
+            split_config.recipient1_bps = split_config.recipient1_percentage as u16 * 100;
+            emit_recorded!(split_config, SplitPrecisionUpgraded {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sender,
+                nonce: split_config.nonce,
+                recipient1_bps: split_config.recipient1_bps,
+                timestamp: now,
+            });
+            split_config.exit(&crate::ID)?;
+            upgraded += 1;
+        }
+
+        msg!("Upgraded {} of {} configs to bps", upgraded, ctx.remaining_accounts.len());
+
+        Ok(upgraded)
+    }
+
+    /// Split exactly what an earlier swap in the same transaction paid the
+    /// sender: the increase of the sender's balance over `pre_swap_balance`,
+    /// which must be at least `min_amount`. A whitelisted swap program must
+    /// have run before this instruction.
+    pub fn execute_split_after_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteSplitAfterSwap<'info>>,
+        pre_swap_balance: u64,
+        min_amount: u64,
+    ) -> Result<()> {
+        let accounts = ctx.accounts;
+
+        let amount = accounts
+            .execute
+            .sender
+            .get_lamports()
+            .checked_sub(pre_swap_balance)
+            .ok_or(SplitError::SwapOutputTooSmall)?;
+        require!(amount >= min_amount, SplitError::SwapOutputTooSmall);
+
+        let whitelist = swap_programs(&accounts.execute.program_config)?;
+        let instructions = accounts.instructions.to_account_info();
+        let current = sysvar::instructions::load_current_index_checked(&instructions)?;
+        let mut swapped = false;
+        for index in 0..current {
+            let instruction = sysvar::instructions::load_instruction_at_checked(index as usize, &instructions)?;
+            if whitelist.contains(&instruction.program_id) {
+                swapped = true;
+                break;
+            }
+        }
+        require!(swapped, SplitError::SwapNotFound);
+
+        msg!("Splitting swap output of {} lamports", amount);
+
+        process_execute_split(
+            &mut accounts.execute,
+            ctx.remaining_accounts,
+            ctx.bumps.execute.history,
+            amount,
+            [0u8; 16],
+            0,
+            ExecutionNote::default(),
+        )
+    }
+
+    /// Cancel a split configuration before execution, recording why
+    pub fn cancel_split(ctx: Context<CancelSplit>, reason: CancelReason) -> Result<()> {
+        let split_config = &ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        // Expiry and compliance cancellations come from their own paths only
+        require!(
+            !matches!(reason, CancelReason::Expired | CancelReason::Compliance),
+            SplitError::InvalidCancelReason
+        );
+
+        // Ensure split hasn't been executed yet
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        // Escrowed funds must go through the refund path first
+        require!(split_config.deposited_amount == 0, SplitError::EscrowNotEmpty);
+
+        // Credited shares belong to the recipients until claimed
+        require!(!split_config.has_outstanding_claims(), SplitError::ClaimsOutstanding);
+
+        // Staged amounts are already locked for the recipients
+        require!(split_config.layout != LAYOUT_STAGED, SplitError::SplitStaged);
+
+        // Validate sender matches
+        require!(
+            split_config.sender == ctx.accounts.sender.key(),
+            SplitError::UnauthorizedSender
+        );
+
+        // Executors get the cooldown to act before the sender can pull the config
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= split_config.cancel_allowed_at(),
+            SplitError::CancelCooldownActive
+        );
+
+        // An unexecuted config cancelled soon after creation gets its fee
+        // back, as far as the treasury still holds it; a shortfall never
+        // blocks the cancel
+        let fee = split_config.creation_fee_paid;
+        if fee > 0
+            && now < split_config.created_at.saturating_add(CREATION_FEE_REFUND_WINDOW_SECONDS)
+        {
+            let treasury = ctx
+                .accounts
+                .treasury
+                .as_mut()
+                .ok_or(SplitError::TreasuryRequired)?;
+            treasury.release_refund(fee, split_config.created_at, now);
+
+            let rent_exempt = Rent::get()?.minimum_balance(treasury.to_account_info().data_len());
+            let refunded = fee.min(treasury.get_lamports().saturating_sub(rent_exempt));
+            treasury.sub_lamports(refunded)?;
+            ctx.accounts.sender.add_lamports(refunded)?;
+
+            emit!(CreationFeeRefunded {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sender: split_config.sender,
+                split_config: split_config.key(),
+                fee: refunded,
+                timestamp: now,
+            });
+        }
+
+        emit!(SplitCancelled {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            topic: split_config.topic,
+            reason,
+            penalty1: split_config.recipient1_penalty_paid,
+            penalty2: split_config.recipient2_penalty_paid,
+            timestamp: now,
+        });
+
+        record_outbox(
+            &mut ctx.accounts.outbox,
+            split_config.key(),
+            LifecycleEvent::Cancelled,
+            0,
+        )?;
+
+        distribute_rent(
+            split_config,
+            &ctx.accounts.sender,
+            ctx.accounts.rent_split_destination.as_ref(),
+        )?;
+
+        msg!("Split cancelled, nonce: {}", split_config.nonce);
+
+        // Remaining rent goes to the sender via close constraint
+        Ok(())
+    }
+
+    /// Close a split configuration after execution to reclaim rent
+    pub fn close_split(ctx: Context<CloseSplit>) -> Result<()> {
+        let split_config = &ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        // Ensure split has been executed
+        require!(split_config.is_terminal(), SplitError::NotExecuted);
+
+        // Credited shares belong to the recipients until claimed
+        require!(!split_config.has_outstanding_claims(), SplitError::ClaimsOutstanding);
+
+        // A queued config must stay open for sweep_reclaim
+        require!(!split_config.defer_reclaim, SplitError::ReclaimDeferred);
+
+        // Validate sender matches
+        require!(
+            split_config.sender == ctx.accounts.sender.key(),
+            SplitError::UnauthorizedSender
+        );
+
+        // A close composed after an execution of the same config can land
+        // while the rest of the transaction's intent didn't; the combined
+        // instruction keeps the two atomic
+        if !composed_close_allowed(&ctx.accounts.program_config)? {
+            require!(
+                !executed_earlier_in_transaction(&ctx.accounts.instructions, &split_config.key())?,
+                SplitError::UseExecuteAndClose
+            );
+        }
+
+        emit!(SplitClosed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            topic: split_config.topic,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_outbox(
+            &mut ctx.accounts.outbox,
+            split_config.key(),
+            LifecycleEvent::Closed,
+            0,
+        )?;
+
+        if split_config.close_residual_to_recipients {
+            distribute_residual(
+                split_config,
+                ctx.accounts.recipient1.as_deref(),
+                ctx.accounts.recipient2.as_deref(),
+            )?;
+        }
+
+        distribute_rent(
+            split_config,
+            &ctx.accounts.sender,
+            ctx.accounts.rent_split_destination.as_ref(),
+        )?;
+
+        msg!("Split closed, rent reclaimed, nonce: {}", split_config.nonce);
+
+        // Remaining rent goes to the sender via close constraint
+        Ok(())
+    }
+
+    /// Return lamports stranded at the addresses of the sender's closed configs.
+    /// Each remaining account must derive from the sender, the nonce at the
+    /// same position in `nonces` and, for configs that were committed to their
+    /// terms, the terms hash at that position in `terms_hashes`; anything else
+    /// is skipped untouched. `terms_hashes` may be empty when no vault was
+    /// committed.
+    pub fn sweep_vaults<'info>(
+        ctx: Context<'_, '_, '_, 'info, SweepVaults<'info>>,
+        nonces: Vec<u64>,
+        terms_hashes: Vec<Option<[u8; 32]>>,
+    ) -> Result<()> {
+        let sender = &ctx.accounts.sender;
+        let vaults = ctx.remaining_accounts;
+
+        require!(nonces.len() <= MAX_SWEEP_BATCH, SplitError::SweepBatchTooLarge);
+        require!(nonces.len() == vaults.len(), SplitError::SweepAccountsMismatch);
+        require!(
+            terms_hashes.is_empty() || terms_hashes.len() == nonces.len(),
+            SplitError::SweepAccountsMismatch
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut total_swept: u64 = 0;
+
+        for (index, (vault, nonce)) in vaults.iter().zip(nonces).enumerate() {
+            let nonce_bytes = nonce.to_le_bytes();
+            // Uncommitted configs derive without a trailing terms seed
+            let terms_seed: &[u8] = match terms_hashes.get(index) {
+                Some(Some(terms_hash)) => terms_hash,
+                _ => &[],
+            };
+            let (expected, bump) = Pubkey::find_program_address(
+                &[b"split_config", sender.key().as_ref(), &nonce_bytes, terms_seed],
+                ctx.program_id,
+            );
+
+            let lamports = vault.lamports();
+            let skip_reason = if vault.key() != expected {
+                Some(SweepSkipReason::SeedMismatch)
+            } else if vault.owner != &system_program::ID {
+                // Still a live config, closed through close_split instead
+                Some(SweepSkipReason::LiveConfig)
+            } else if lamports == 0 {
+                Some(SweepSkipReason::Empty)
+            } else {
+                None
+            };
+
+            let amount = if skip_reason.is_none() {
+                system_program::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: vault.clone(),
+                            to: sender.to_account_info(),
+                        },
+                        &[&[b"split_config", sender.key().as_ref(), &nonce_bytes, terms_seed, &[bump]]],
+                    ),
+                    lamports,
+                )?;
+                total_swept = total_swept
+                    .checked_add(lamports)
+                    .ok_or(SplitError::MathOverflow)?;
+                lamports
+            } else {
+                0
+            };
+
+            emit!(VaultSwept {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sender: sender.key(),
+                vault: vault.key(),
+                nonce,
+                amount,
+                skip_reason,
+                timestamp,
+            });
+        }
+
+        msg!("Swept {} lamports from {} vaults", total_swept, vaults.len());
+
+        Ok(())
+    }
+
+    /// Close a config that has outlived the global maximum lifetime. Anyone may
+    /// call this; everything the config holds returns to the sender, and an
+    /// armed cancel penalty is not paid.
+    pub fn cleanup_expired_config(ctx: Context<CleanupExpiredConfig>) -> Result<()> {
+        let split_config = &ctx.accounts.split_config;
+        let clock = Clock::get()?;
+
+        require!(
+            ctx.accounts
+                .program_config
+                .config_expired(split_config.created_at, clock.unix_timestamp),
+            SplitError::ConfigNotExpired
+        );
+
+        // Credited shares belong to the recipients until claimed
+        require!(!split_config.has_outstanding_claims(), SplitError::ClaimsOutstanding);
+        require!(split_config.layout != LAYOUT_STAGED, SplitError::SplitStaged);
+
+        // An executed deferred config is queued and must stay open for sweep_reclaim
+        require!(
+            !(split_config.defer_reclaim && split_config.is_terminal()),
+            SplitError::ReclaimDeferred
+        );
+
+        emit!(ConfigExpired {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            cleaner: ctx.accounts.cleaner.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        // Cleaning up a never-executed config cancels it, always as expired
+        if !split_config.is_terminal() {
+            emit!(SplitCancelled {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sender: split_config.sender,
+                nonce: split_config.nonce,
+                topic: split_config.topic,
+                reason: CancelReason::Expired,
+                penalty1: 0,
+                penalty2: 0,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        record_outbox(
+            &mut ctx.accounts.outbox,
+            split_config.key(),
+            LifecycleEvent::Closed,
+            0,
+        )?;
+
+        distribute_rent(
+            split_config,
+            &ctx.accounts.sender,
+            ctx.accounts.rent_split_destination.as_ref(),
+        )?;
+
+        msg!("Expired split cleaned up, nonce: {}", split_config.nonce);
+
+        // Remaining lamports go to the sender via close constraint
+        Ok(())
+    }
+
+    /// Change the topic label of a split configuration for re-categorization
+    pub fn set_topic(ctx: Context<SetTopic>, new_topic: [u8; 8]) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        // Validate sender matches
+        require!(
+            split_config.sender == ctx.accounts.sender.key(),
+            SplitError::UnauthorizedSender
+        );
+
+        let old_topic = split_config.topic;
+        split_config.topic = new_topic;
+
+        emit_recorded!(split_config, TopicChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            old_topic,
+            new_topic,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Split topic changed, nonce: {}", split_config.nonce);
+
+        Ok(())
+    }
+
+    /// Map a route ID to a split configuration, re-pointing it if already registered
+    pub fn register_route(ctx: Context<RegisterRoute>, route_id: [u8; 16]) -> Result<()> {
+        let route_table = &mut ctx.accounts.route_table;
+        let split_config = &ctx.accounts.split_config;
+
+        // Validate sender matches the configuration being routed to
+        require!(
+            split_config.sender == ctx.accounts.sender.key(),
+            SplitError::UnauthorizedSender
+        );
+
+        // Freshly created tables start out owned by the signing sender
+        if route_table.sender == Pubkey::default() {
+            route_table.sender = ctx.accounts.sender.key();
+            route_table.bump = ctx.bumps.route_table;
+        }
+
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        match route_table.routes.iter_mut().find(|r| r.route_id == route_id) {
+            Some(route) => {
+                let old_split_config = route.split_config;
+                route.split_config = split_config.key();
+
+                emit!(RouteRepointed {
+                    schema_version: EVENT_SCHEMA_VERSION,
+                    sender: route_table.sender,
+                    route_id,
+                    old_split_config,
+                    new_split_config: split_config.key(),
+                    timestamp,
+                });
+
+                msg!("Route re-pointed to nonce: {}", split_config.nonce);
+            }
+            None => {
+                require!(
+                    route_table.routes.len() < MAX_ROUTES,
+                    SplitError::RouteTableFull
+                );
+
+                route_table.routes.push(Route {
+                    route_id,
+                    split_config: split_config.key(),
+                });
+
+                emit!(RouteRegistered {
+                    schema_version: EVENT_SCHEMA_VERSION,
+                    sender: route_table.sender,
+                    route_id,
+                    split_config: split_config.key(),
+                    timestamp,
+                });
+
+                msg!("Route registered for nonce: {}", split_config.nonce);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Execute the split configuration a route ID currently points to
+    pub fn execute_routed<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteRouted<'info>>,
+        route_id: [u8; 16],
+        amount: u64,
+    ) -> Result<()> {
+        let accounts = ctx.accounts;
+
+        let route = accounts
+            .route_table
+            .routes
+            .iter()
+            .find(|r| r.route_id == route_id)
+            .ok_or(SplitError::RouteNotFound)?;
+
+        // Validate the passed configuration is the one the route points to
+        require!(
+            route.split_config == accounts.execute.split_config.key(),
+            SplitError::RouteConfigMismatch
+        );
+
+        process_execute_split(
+            &mut accounts.execute,
+            ctx.remaining_accounts,
+            ctx.bumps.execute.history,
+            amount,
+            route_id,
+            0,
+            ExecutionNote::default(),
+        )
+    }
+
+    /// Execute the split in SPL tokens, burning `burn_bps` of the amount first
+    pub fn execute_split_spl(
+        ctx: Context<ExecuteSplitSpl>,
+        amount: u64,
+        burn_bps: u16,
+    ) -> Result<()> {
+        process_execute_split_spl(ctx.accounts, amount, burn_bps, None)
+    }
+
+    /// Execute the split in SPL tokens like `execute_split_spl`, swapping the
+    /// legs of auto-swap slots into their target mint through the venue routes
+    /// in `legs`. Legs without a route through a whitelisted venue fall back to
+    /// the original mint if the config allows it.
+    #[cfg(feature = "auto-swap")]
+    pub fn execute_split_spl_auto_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteSplitSplAutoSwap<'info>>,
+        amount: u64,
+        burn_bps: u16,
+        legs: Vec<SwapLeg>,
+    ) -> Result<()> {
+        let routes = SwapRoutes {
+            whitelist: swap_programs(&ctx.accounts.program_config)?,
+            legs,
+            accounts: ctx.remaining_accounts,
+        };
+
+        process_execute_split_spl(&mut ctx.accounts.execute, amount, burn_bps, Some(routes))
+    }
+
+    /// Execute the split in SPL tokens like `execute_split_spl`, charging the
+    /// token split fee in SOL from the sender to the treasury. The fee is read
+    /// from the program config and must not exceed `max_fee_lamports`.
+    pub fn execute_split_spl_sol_fee(
+        ctx: Context<ExecuteSplitSplSolFee>,
+        amount: u64,
+        burn_bps: u16,
+        max_fee_lamports: u64,
+    ) -> Result<()> {
+        let fee = ctx.accounts.program_config.token_split_fee_lamports;
+        require!(fee <= max_fee_lamports, SplitError::FeeAboveMaximum);
+
+        process_execute_split_spl(&mut ctx.accounts.execute, amount, burn_bps, None)?;
+
+        if fee > 0 {
+            let sender = &ctx.accounts.execute.sender;
+            require!(sender.get_lamports() >= fee, SplitError::InsufficientBalance);
+
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: sender.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+
+            emit_recorded!(ctx.accounts.execute.split_config, SolFeeCollected {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sender: sender.key(),
+                split_config: ctx.accounts.execute.split_config.key(),
+                mint: ctx.accounts.execute.mint.key(),
+                fee,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Split tokens from the sender's token account using a delegate approval
+    /// granted to the config PDA, so the sender doesn't sign each execution.
+    /// The approval must cover the amount; like `execute_split_spl`, the config
+    /// executes once.
+    pub fn execute_split_delegated_token(
+        ctx: Context<ExecuteSplitDelegatedToken>,
+        amount: u64,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let executor = ctx.accounts.executor.key();
+        let source = &ctx.accounts.sender_token_account;
+        let clock = Clock::get()?;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        // Escrow configs only release through recipient confirmation
+        require!(!split_config.escrow, SplitError::EscrowConfig);
+
+        // A prefunded balance must be spent or refunded before the final execution
+        require!(split_config.deposited_amount == 0, SplitError::EscrowNotEmpty);
+
+        // Claimable balances are tracked in lamports only
+        require!(!split_config.claim_mode, SplitError::ClaimModeConfig);
+
+        if executor != split_config.sender {
+            require!(
+                split_config.has_delegate() && executor == split_config.delegate,
+                SplitError::UnauthorizedExecutor
+            );
+        }
+
+        split_config.check_execution_window(clock.unix_timestamp)?;
+        check_config_lifetime(&ctx.accounts.program_config, split_config, clock.unix_timestamp)?;
+        split_config.consume_approvals()?;
+        require!(split_config.cascade_target1.is_none(), SplitError::CascadeUnsupported);
+        split_config.require_two_recipients()?;
+
+        // Token executions have no reclaim queue to register with
+        require!(!split_config.defer_reclaim, SplitError::ReclaimQueueRequired);
+
+        require!(amount > 0, SplitError::AmountTooSmall);
+
+        // The program may only move what the sender approved to the config
+        require!(
+            source.delegate == COption::Some(split_config.key()),
+            SplitError::TokenDelegationMissing
+        );
+        require!(
+            source.delegated_amount >= amount,
+            SplitError::TokenDelegationInsufficient
+        );
+        require!(source.amount >= amount, SplitError::InsufficientBalance);
+
+        // The venue can't be signed for by the config, so auto-swapped legs
+        // are only paid here when the config accepts the original mint
+        require!(
+            split_config.swap_fallback_to_original
+                || (split_config.recipient1_auto_swap.is_none()
+                    && split_config.recipient2_auto_swap.is_none()),
+            SplitError::AutoSwapUnavailable
+        );
+
+        let (_, amount1, amount2) = split_config.token_shares(amount, 0, clock.unix_timestamp)?;
+
+        let preferences_honored = [
+            honor_preference(&ctx.accounts.recipient1_preference, &ctx.accounts.recipient1_token_account.key())?,
+            honor_preference(&ctx.accounts.recipient2_preference, &ctx.accounts.recipient2_token_account.key())?,
+        ];
+
+        let nonce = split_config.nonce.to_le_bytes();
+        let seeds: &[&[u8]] = &[
+            b"split_config",
+            split_config.sender.as_ref(),
+            &nonce,
+            split_config.terms_seed(),
+            &[split_config.bump],
+        ];
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let decimals = ctx.accounts.mint.decimals;
+
+        for (destination, share) in [
+            (&ctx.accounts.recipient1_token_account, amount1),
+            (&ctx.accounts.recipient2_token_account, amount2),
+        ] {
+            token::transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program.clone(),
+                    TransferChecked {
+                        from: source.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: destination.to_account_info(),
+                        authority: split_config.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                share,
+                decimals,
+            )?;
+        }
+
+        // Mark as executed to prevent replay
+        split_config.mark_executed(clock.unix_timestamp);
+
+        emit_recorded!(split_config, TokenSplitExecuted {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            mint: ctx.accounts.mint.key(),
+            recipient1: event_recipient(split_config, &split_config.recipient1),
+            recipient2: event_recipient(split_config, &split_config.recipient2),
+            amount1,
+            amount2,
+            burned: 0,
+            total_amount: amount,
+            topic: split_config.topic,
+            preferences_honored,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_outbox(
+            &mut ctx.accounts.outbox,
+            split_config.key(),
+            LifecycleEvent::Executed,
+            amount,
+        )?;
+
+        msg!(
+            "Delegated token split executed: {} to recipient1, {} to recipient2",
+            amount1,
+            amount2
+        );
+
+        Ok(())
+    }
+
+    /// Replace both recipients and their percentages in one step on a config
+    /// that has not executed and has paid or owes its recipients nothing yet.
+    /// Acceptances and attestations are reset, since they were given for the
+    /// old terms.
+    pub fn reconfigure_split(
+        ctx: Context<ReconfigureSplit>,
+        new_recipient1: Pubkey,
+        new_recipient2: Pubkey,
+        new_recipient1_percentage: u8,
+        new_recipient2_percentage: u8,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        // Credited shares belong to the current recipients until claimed
+        require!(!split_config.has_outstanding_claims(), SplitError::ClaimsOutstanding);
+
+        // Received totals, shortfalls and penalties count per recipient slot,
+        // so they would carry over to the new pair
+        require!(!split_config.has_recipient_history(), SplitError::RecipientHistory);
+
+        // The address commits to the original terms
+        require!(split_config.terms_hash == [0u8; 32], SplitError::TermsCommitted);
+
+        validate_terms(
+            &new_recipient1,
+            &new_recipient2,
+            new_recipient1_percentage,
+            new_recipient2_percentage,
+        )?;
+        validate_deliveries(
+            [
+                (&split_config.recipient1_delivery, new_recipient1),
+                (&split_config.recipient2_delivery, new_recipient2),
+            ],
+            ctx.remaining_accounts,
+        )?;
+
+        // Settings tied to the recipients must still hold for the new pair
+        if let Some(withholding) = &split_config.withholding {
+            validate_withholding(withholding, &new_recipient1, &new_recipient2)?;
+        }
+        if let Some(target) = split_config.cascade_target1 {
+            require!(
+                target == new_recipient1 && target != split_config.key(),
+                SplitError::InvalidCascadeTarget
+            );
+        }
+
+        emit_recorded!(split_config, SplitReconfigured {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            old_recipient1: event_recipient(split_config, &split_config.recipient1),
+            old_recipient2: event_recipient(split_config, &split_config.recipient2),
+            old_recipient1_percentage: split_config.recipient1_percentage,
+            old_recipient2_percentage: split_config.recipient2_percentage,
+            new_recipient1: event_recipient(split_config, &new_recipient1),
+            new_recipient2: event_recipient(split_config, &new_recipient2),
+            new_recipient1_percentage,
+            new_recipient2_percentage,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        split_config.recipient1 = new_recipient1;
+        split_config.recipient2 = new_recipient2;
+        split_config.recipient1_percentage = new_recipient1_percentage;
+        split_config.recipient2_percentage = new_recipient2_percentage;
+        if split_config.recipient1_bps != 0 {
+            split_config.recipient1_bps = new_recipient1_percentage as u16 * 100;
+        }
+        split_config.recipient1_accepted = false;
+        split_config.recipient2_accepted = false;
+        split_config.recipient1_attested = false;
+        split_config.recipient2_attested = false;
+        // Freezes, recovery keys and signatures belonged to the replaced recipients
+        split_config.verified_recipients = false;
+        split_config.approvals = 0;
+        split_config.recipient1_claim_status = ClaimStatus::Open;
+        split_config.recipient2_claim_status = ClaimStatus::Open;
+        split_config.recipient1_recovery = Pubkey::default();
+        split_config.recipient2_recovery = Pubkey::default();
+
+        msg!(
+            "Split reconfigured: {}% to {}, {}% to {}",
+            new_recipient1_percentage,
+            event_recipient(split_config, &new_recipient1),
+            new_recipient2_percentage,
+            event_recipient(split_config, &new_recipient2)
+        );
+
+        Ok(())
+    }
+
+    /// Replace or remove the withholding on a config that has not executed.
+    /// Like `reconfigure_split`, acceptances and attestations are reset, since
+    /// they were given for the old terms.
+    pub fn set_withholding(
+        ctx: Context<ReconfigureSplit>,
+        withholding: Option<Withholding>,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        if let Some(withholding) = &withholding {
+            validate_withholding(withholding, &split_config.recipient1, &split_config.recipient2)?;
+            require!(
+                withholding_compatible(
+                    split_config.claim_mode,
+                    split_config.cascade_target1.is_some(),
+                    split_config.close_residual_to_recipients,
+                    split_config.update_recipient_ledger,
+                ),
+                SplitError::IncompatibleOptions
+            );
+        }
+
+        split_config.withholding = withholding;
+        split_config.recipient1_accepted = false;
+        split_config.recipient2_accepted = false;
+        split_config.recipient1_attested = false;
+        split_config.recipient2_attested = false;
+
+        emit_recorded!(split_config, WithholdingUpdated {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            withholding,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Withholding updated, nonce: {}", split_config.nonce);
+
+        Ok(())
+    }
+
+    /// Link an unexecuted config to an account in another program, or clear
+    /// the link. The reference is only surfaced in events.
+    pub fn update_external_ref(
+        ctx: Context<ReconfigureSplit>,
+        external_ref: Option<Pubkey>,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        split_config.external_ref = external_ref;
+
+        emit_recorded!(split_config, ExternalRefUpdated {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            external_ref,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("External reference updated, nonce: {}", split_config.nonce);
+
+        Ok(())
+    }
+
+    /// Record one approver's sign-off on the config's next execution
+    pub fn approve_execution(ctx: Context<ApproveExecution>) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let approver = ctx.accounts.approver.key();
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        let index = split_config
+            .approvers
+            .iter()
+            .position(|key| *key == approver)
+            .ok_or(SplitError::NotAnApprover)?;
+        split_config.approvals |= 1 << index;
+
+        emit_recorded!(split_config, ExecutionApproved {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            approver,
+            approvals: split_config.approvals.count_ones() as u8,
+            approvals_required: split_config.approvals_required,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Execution approved, {}/{}",
+            split_config.approvals.count_ones(),
+            split_config.approvals_required
+        );
+
+        Ok(())
+    }
+
+    /// Lock lamports in the executor's bond, allowing open execution of
+    /// configs that enable it
+    pub fn post_executor_bond(ctx: Context<PostExecutorBond>, lamports: u64) -> Result<()> {
+        require!(lamports > 0, SplitError::AmountTooSmall);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.executor.to_account_info(),
+                    to: ctx.accounts.executor_bond.to_account_info(),
+                },
+            ),
+            lamports,
+        )?;
+
+        let bond = &mut ctx.accounts.executor_bond;
+        bond.executor = ctx.accounts.executor.key();
+        bond.amount = bond
+            .amount
+            .checked_add(lamports)
+            .ok_or(SplitError::MathOverflow)?;
+        bond.last_active_at = Clock::get()?.unix_timestamp;
+        bond.bump = ctx.bumps.executor_bond;
+
+        msg!("Executor bond posted: {} lamports, total {}", lamports, bond.amount);
+
+        Ok(())
+    }
+
+    /// Withdraw the whole bond once it has been idle for the cooldown, closing
+    /// the bond account
+    pub fn withdraw_executor_bond(ctx: Context<WithdrawExecutorBond>) -> Result<()> {
+        let bond = &ctx.accounts.executor_bond;
+
+        require!(
+            Clock::get()?.unix_timestamp
+                >= bond.last_active_at.saturating_add(BOND_WITHDRAWAL_COOLDOWN_SECONDS),
+            SplitError::BondCooldownActive
+        );
+
+        msg!("Executor bond withdrawn: {} lamports", bond.amount);
+
+        // Bond and rent return to the executor via close constraint
+        Ok(())
+    }
+
+    /// Slash an executor whose last recorded execution of this config fell in
+    /// the config's quiet window, moving the bond into the config's balance
+    pub fn slash_executor_bond(ctx: Context<SlashExecutorBond>) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let bond = &mut ctx.accounts.executor_bond;
+
+        // A window reaching back to the epoch would otherwise contain the
+        // zero a never-executed bond stores
+        let executed_at = bond
+            .last_executed_at_checked()
+            .ok_or(SplitError::NotYetExecuted)?;
+        require!(
+            bond.last_config == split_config.key()
+                && split_config
+                    .executor_quiet_window
+                    .as_ref()
+                    .is_some_and(|window| window.contains(executed_at)),
+            SplitError::NoBondViolation
+        );
+
+        let slashed = bond.amount;
+        require!(slashed > 0, SplitError::NoBondViolation);
+
+        bond.amount = 0;
+        bond.clear_execution();
+        bond.sub_lamports(slashed)?;
+        split_config.add_lamports(slashed)?;
+        split_config.deposited_amount = split_config
+            .deposited_amount
+            .checked_add(slashed)
+            .ok_or(SplitError::MathOverflow)?;
+
+        emit!(ExecutorBondSlashed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            split_config: split_config.key(),
+            executor: bond.executor,
+            amount: slashed,
+            executed_at,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Executor bond slashed: {} lamports from {}", slashed, bond.executor);
+
+        Ok(())
+    }
+
+    /// Set or clear the delegate allowed to execute from the prefunded balance.
+    /// A `daily_limit` of zero leaves the delegate unlimited.
+    pub fn set_delegate(
+        ctx: Context<SetDelegate>,
+        delegate: Pubkey,
+        daily_limit: u64,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        // Validate sender matches
+        require!(
+            split_config.sender == ctx.accounts.sender.key(),
+            SplitError::UnauthorizedSender
+        );
+
+        // Changing the delegate or its limit starts a fresh window
+        split_config.delegate = delegate;
+        split_config.delegate_daily_limit = daily_limit;
+        split_config.delegate_window_start = 0;
+        split_config.delegate_window_spent = 0;
+
+        emit_recorded!(split_config, DelegateUpdated {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            delegate,
+            daily_limit,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Delegate updated, nonce: {}", split_config.nonce);
+
+        Ok(())
+    }
+
+    /// Propose a new delegate, which only becomes active once it signs
+    /// `accept_delegate`. The current delegate keeps acting until then.
+    pub fn propose_delegate(
+        ctx: Context<SetDelegate>,
+        delegate: Pubkey,
+        daily_limit: u64,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        // Validate sender matches
+        require!(
+            split_config.sender == ctx.accounts.sender.key(),
+            SplitError::UnauthorizedSender
+        );
+
+        split_config.pending_delegate = delegate;
+        split_config.pending_delegate_daily_limit = daily_limit;
+
+        emit_recorded!(split_config, DelegateProposed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            delegate,
+            daily_limit,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Delegate proposed, nonce: {}", split_config.nonce);
+
+        Ok(())
+    }
+
+    /// Accept a proposed delegate role, signed by the proposed key
+    pub fn accept_delegate(ctx: Context<AcceptDelegate>) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+        require!(
+            split_config.pending_delegate != Pubkey::default(),
+            SplitError::NoPendingDelegate
+        );
+        require_keys_eq!(
+            ctx.accounts.delegate.key(),
+            split_config.pending_delegate,
+            SplitError::UnauthorizedDelegate
+        );
+
+        // The new delegate starts a fresh limit window, like set_delegate
+        let delegate = std::mem::take(&mut split_config.pending_delegate);
+        let daily_limit = std::mem::take(&mut split_config.pending_delegate_daily_limit);
+        split_config.delegate = delegate;
+        split_config.delegate_daily_limit = daily_limit;
+        split_config.delegate_window_start = 0;
+        split_config.delegate_window_spent = 0;
+
+        emit_recorded!(split_config, DelegateUpdated {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            delegate,
+            daily_limit,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Delegate accepted, nonce: {}", split_config.nonce);
+
+        Ok(())
+    }
+
+    /// Deposit SOL into a split configuration, held as escrow or as a prefunded balance.
+    /// Escrow configs may also take deposits from allowed depositors; refunds of
+    /// those still go to the sender, who settles with them off-chain.
+    pub fn deposit_to_split(ctx: Context<DepositToSplit>, amount: u64) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+        require!(amount > 0, SplitError::AmountTooSmall);
+
+        // Deposits pay out through the recipient pair only
+        split_config.require_two_recipients()?;
+
+        // Only the sender funds a config, or on escrow configs a depositor it allows
+        require!(
+            split_config.depositor_allowed(&ctx.accounts.sender.key()),
+            SplitError::DepositorNotAllowed
+        );
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sender.to_account_info(),
+                    to: split_config.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        split_config.deposited_amount = split_config
+            .deposited_amount
+            .checked_add(amount)
+            .ok_or(SplitError::MathOverflow)?;
+
+        emit_recorded!(split_config, EscrowDeposited {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            amount,
+            deposited_amount: split_config.deposited_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_outbox(
+            &mut ctx.accounts.outbox,
+            split_config.key(),
+            LifecycleEvent::Deposited,
+            amount,
+        )?;
+
+        msg!(
+            "Escrow deposit: {} lamports, total {}",
+            amount,
+            split_config.deposited_amount
+        );
+
+        Ok(())
+    }
+
+    /// Allow `depositor` to fund an escrow config, before it has executed
+    pub fn add_allowed_depositor(ctx: Context<UpdateAllowedDepositors>, depositor: Pubkey) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        require!(split_config.escrow, SplitError::NotEscrowConfig);
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+        require!(
+            depositor != Pubkey::default()
+                && !split_config.allowed_depositors.contains(&depositor)
+                && split_config.allowed_depositors.len() < MAX_ALLOWED_DEPOSITORS,
+            SplitError::InvalidDepositors
+        );
+
+        split_config.allowed_depositors.push(depositor);
+
+        emit_recorded!(split_config, DepositorAllowed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            depositor,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Depositor allowed: {}", depositor);
+
+        Ok(())
+    }
+
+    /// Stop `depositor` funding the config. What it already deposited stays.
+    pub fn remove_allowed_depositor(ctx: Context<UpdateAllowedDepositors>, depositor: Pubkey) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        let index = split_config
+            .allowed_depositors
+            .iter()
+            .position(|key| *key == depositor)
+            .ok_or(SplitError::DepositorNotAllowed)?;
+        split_config.allowed_depositors.remove(index);
+
+        emit_recorded!(split_config, DepositorRemoved {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            depositor,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Depositor removed: {}", depositor);
+
+        Ok(())
+    }
+
+    /// Record a recipient's acknowledgment of the config's agreement hash
+    pub fn attest_agreement(ctx: Context<AttestAgreement>, hash: [u8; 32]) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let recipient = ctx.accounts.recipient.key();
+
+        split_config.verify()?;
+
+        require!(split_config.agreement_hash != [0u8; 32], SplitError::NoAgreement);
+
+        // Recipients attest to the exact document the config commits to
+        require!(
+            hash == split_config.agreement_hash,
+            SplitError::AgreementHashMismatch
+        );
+
+        if recipient == split_config.recipient1 {
+            split_config.recipient1_attested = true;
+        } else if recipient == split_config.recipient2 {
+            split_config.recipient2_attested = true;
+        } else {
+            return err!(SplitError::InvalidRecipient);
+        }
+
+        emit_recorded!(split_config, AgreementAttested {
+            schema_version: EVENT_SCHEMA_VERSION,
+            recipient: event_recipient(split_config, &recipient),
+            nonce: split_config.nonce,
+            hash,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Agreement attested by recipient, nonce: {}", split_config.nonce);
+
+        Ok(())
+    }
+
+    /// Split part of a non-escrow config's prefunded balance, signed by the sender or
+    /// its delegate. Only delegate executions count against the delegate's daily limit,
+    /// and the config stays open for further executions.
+    pub fn execute_from_deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteFromDeposit<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let executor = ctx.accounts.executor.key();
+        let clock = Clock::get()?;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        // Escrow configs only release through recipient confirmation
+        require!(!split_config.escrow, SplitError::EscrowConfig);
+
+        // Validate recipients match configuration
+        require!(
+            split_config.recipient1 == ctx.accounts.recipient1.key(),
+            SplitError::InvalidRecipient
+        );
+        require!(
+            split_config.recipient2 == ctx.accounts.recipient2.key(),
+            SplitError::InvalidRecipient
+        );
+
+        split_config.check_recipient_data_len(&ctx.accounts.recipient1, &ctx.accounts.recipient2)?;
+        split_config.check_execution_window(clock.unix_timestamp)?;
+        split_config.consume_approvals()?;
+
+        // Cascades run only from execute_split, which the sender signs
+        require!(split_config.cascade_target1.is_none(), SplitError::CascadeUnsupported);
+
+        // A zero amount only tops up shortfalls left by partial executions
+        require!(
+            amount >= MINIMUM_SPLIT_AMOUNT || (amount == 0 && split_config.has_shortfall()),
+            SplitError::AmountTooSmall
+        );
+
+        if executor != split_config.sender {
+            if split_config.has_delegate() && executor == split_config.delegate {
+                split_config.consume_delegate_allowance(clock.unix_timestamp, amount)?;
+            } else {
+                // Anyone else needs open execution and a large enough bond at stake
+                require!(split_config.anyone_can_execute, SplitError::UnauthorizedExecutor);
+                let bond = ctx
+                    .accounts
+                    .executor_bond
+                    .as_mut()
+                    .ok_or(SplitError::ExecutorBondRequired)?;
+                require!(
+                    bond.amount >= min_executor_bond(&ctx.accounts.program_config)?,
+                    SplitError::ExecutorBondRequired
+                );
+                bond.record_execution(split_config.key(), clock.unix_timestamp);
+            }
+        }
+
+        apply_program_limits(&ctx.accounts.program_config, split_config, amount)?;
+
+        let (share1, share2) = if amount == 0 {
+            (0, 0)
+        } else {
+            split_config.next_reusable_shares(amount, clock.unix_timestamp)?
+        };
+
+        // Earlier shortfalls are owed alongside the new shares
+        let owed1 = share1
+            .checked_add(split_config.recipient1_shortfall)
+            .ok_or(SplitError::MathOverflow)?;
+        let owed2 = share2
+            .checked_add(split_config.recipient2_shortfall)
+            .ok_or(SplitError::MathOverflow)?;
+
+        // Capped overflow stays in the sender's prefunded balance
+        let (owed1, owed2, _) = split_config.apply_caps(owed1, owed2);
+        let (amount1, amount2) = split_config.fundable_shares(owed1, owed2)?;
+        split_config.recipient1_shortfall = owed1 - amount1;
+        split_config.recipient2_shortfall = owed2 - amount2;
+        split_config.record_received(amount1, amount2)?;
+        split_config.record_distribution(amount1 + amount2)?;
+        update_recipient_ledgers(
+            split_config,
+            [
+                &mut ctx.accounts.recipient1_ledger,
+                &mut ctx.accounts.recipient2_ledger,
+            ],
+            amount1,
+            amount2,
+        )?;
+
+        pay_from_deposit(
+            split_config,
+            &ctx.accounts.recipient1,
+            &ctx.accounts.recipient2,
+            ctx.remaining_accounts,
+            amount1,
+            amount2,
+        )?;
+
+        emit_split_executed!(split_config, SplitExecuted {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            recipient1: event_recipient(split_config, &split_config.recipient1),
+            recipient2: event_recipient(split_config, &split_config.recipient2),
+            amount1,
+            amount2,
+            total_amount: amount,
+            topic: split_config.topic,
+            agreement_hash: split_config.agreement_hash,
+            route_id: [0u8; 16],
+            valid_until_slot: 0,
+            slot: clock.slot,
+            external_ref: split_config.external_ref,
+            client: None,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if split_config.has_shortfall() {
+            emit_recorded!(split_config, PartialExecution {
+                schema_version: EVENT_SCHEMA_VERSION,
+                split_config: split_config.key(),
+                policy: split_config.shortfall_policy,
+                amount1,
+                amount2,
+                unpaid1: split_config.recipient1_shortfall,
+                unpaid2: split_config.recipient2_shortfall,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        record_outbox(
+            &mut ctx.accounts.outbox,
+            split_config.key(),
+            LifecycleEvent::Executed,
+            amount,
+        )?;
+
+        msg!(
+            "Prefunded split executed: {} lamports to recipient1, {} lamports to recipient2, {} remaining",
+            amount1,
+            amount2,
+            split_config.deposited_amount
+        );
+
+        Ok(())
+    }
+
+    /// Split whatever has been sent straight to a forwarding config's address
+    /// since the last crank. Anyone may crank. Lamports above the config's rent,
+    /// deposit and claimable balances are split like a prefunded execution and
+    /// the config stays open for the next receipts.
+    pub fn forward_and_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, ForwardAndSplit<'info>>,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let clock = Clock::get()?;
+
+        split_config.verify()?;
+
+        require!(split_config.forward_receipts, SplitError::ForwardingDisabled);
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+        split_config.require_two_recipients()?;
+
+        require!(
+            split_config.recipient1 == ctx.accounts.recipient1.key(),
+            SplitError::InvalidRecipient
+        );
+        require!(
+            split_config.recipient2 == ctx.accounts.recipient2.key(),
+            SplitError::InvalidRecipient
+        );
+
+        split_config.check_recipient_data_len(&ctx.accounts.recipient1, &ctx.accounts.recipient2)?;
+        split_config.check_execution_window(clock.unix_timestamp)?;
+        split_config.consume_approvals()?;
+
+        let amount = unaccounted_lamports(split_config)?;
+        require!(amount >= MINIMUM_SPLIT_AMOUNT, SplitError::AmountTooSmall);
+
+        apply_program_limits(&ctx.accounts.program_config, split_config, amount)?;
+
+        // Receipts join the prefunded balance, where capped overflow stays
+        split_config.deposited_amount = split_config
+            .deposited_amount
+            .checked_add(amount)
+            .ok_or(SplitError::MathOverflow)?;
+
+        let (share1, share2) = split_config.next_reusable_shares(amount, clock.unix_timestamp)?;
+        let (amount1, amount2, _) = split_config.apply_caps(share1, share2);
+        split_config.record_received(amount1, amount2)?;
+        split_config.record_distribution(amount1 + amount2)?;
+
+        pay_from_deposit(
+            split_config,
+            &ctx.accounts.recipient1,
+            &ctx.accounts.recipient2,
+            ctx.remaining_accounts,
+            amount1,
+            amount2,
+        )?;
+
+        emit_recorded!(split_config, ReceiptsForwarded {
+            schema_version: EVENT_SCHEMA_VERSION,
+            split_config: split_config.key(),
+            cranker: ctx.accounts.cranker.key(),
+            amount,
+            amount1,
+            amount2,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Forwarded {} lamports: {} to recipient1, {} to recipient2",
+            amount,
+            amount1,
+            amount2
+        );
+
+        Ok(())
+    }
+
+    /// Record a recipient's acceptance, releasing the escrow once both have accepted
+    pub fn confirm_and_release<'info>(
+        ctx: Context<'_, '_, '_, 'info, ConfirmAndRelease<'info>>,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let recipient = ctx.accounts.recipient.key();
+        let clock = Clock::get()?;
+
+        split_config.verify()?;
+
+        require!(split_config.escrow, SplitError::NotEscrowConfig);
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        // Validate recipients match configuration
+        require!(
+            split_config.recipient1 == ctx.accounts.recipient1.key(),
+            SplitError::InvalidRecipient
+        );
+        require!(
+            split_config.recipient2 == ctx.accounts.recipient2.key(),
+            SplitError::InvalidRecipient
+        );
+
+        // Acceptance is only possible before the deadline
+        require!(
+            split_config.can_execute(clock.unix_timestamp),
+            SplitError::AcceptanceDeadlinePassed
+        );
+
+        if recipient == split_config.recipient1 {
+            split_config.recipient1_accepted = true;
+        } else if recipient == split_config.recipient2 {
+            split_config.recipient2_accepted = true;
+        } else {
+            return err!(SplitError::InvalidRecipient);
+        }
+        split_config.cancel_penalty_armed = split_config.cancel_penalty_bps > 0;
+
+        emit_recorded!(split_config, ReleaseConfirmed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            recipient: event_recipient(split_config, &recipient),
+            nonce: split_config.nonce,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Release confirmed by recipient, nonce: {}", split_config.nonce);
+
+        if !(split_config.recipient1_accepted && split_config.recipient2_accepted) {
+            return Ok(());
+        }
+
+        let (amount, amount1, amount2) = release_escrow(
+            split_config,
+            &ctx.accounts.recipient1,
+            &ctx.accounts.recipient2,
+            ctx.remaining_accounts,
+            &clock,
+        )?;
+
+        record_outbox(
+            &mut ctx.accounts.outbox,
+            split_config.key(),
+            LifecycleEvent::Executed,
+            amount,
+        )?;
+
+        msg!(
+            "Escrow released: {} lamports to recipient1, {} lamports to recipient2",
+            amount1,
+            amount2
+        );
+
+        Ok(())
+    }
+
+    /// Release an escrow once its oracle reports a value at or above the
+    /// configured condition. Anyone may call this; the oracle decides.
+    pub fn release_on_condition<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReleaseOnCondition<'info>>,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let oracle = &ctx.accounts.oracle;
+        let clock = Clock::get()?;
+
+        split_config.verify()?;
+
+        require!(split_config.escrow, SplitError::NotEscrowConfig);
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        let condition = split_config
+            .oracle_condition
+            .clone()
+            .ok_or(SplitError::NoOracleCondition)?;
+
+        // Validate recipients match configuration
+        require!(
+            split_config.recipient1 == ctx.accounts.recipient1.key(),
+            SplitError::InvalidRecipient
+        );
+        require!(
+            split_config.recipient2 == ctx.accounts.recipient2.key(),
+            SplitError::InvalidRecipient
+        );
+
+        // Past the deadline the deposit belongs to the sender's refund
+        require!(
+            split_config.can_execute(clock.unix_timestamp),
+            SplitError::AcceptanceDeadlinePassed
+        );
+
+        require_keys_eq!(oracle.key(), condition.oracle, SplitError::InvalidOracle);
+        require_keys_eq!(*oracle.owner, condition.owner, SplitError::InvalidOracle);
+
+        let value = condition.read_value(&oracle.try_borrow_data()?)?;
+        require!(
+            value >= condition.condition_value,
+            SplitError::OracleConditionNotMet
+        );
+
+        let (amount, amount1, amount2) = release_escrow(
+            split_config,
+            &ctx.accounts.recipient1,
+            &ctx.accounts.recipient2,
+            ctx.remaining_accounts,
+            &clock,
+        )?;
+
+        record_outbox(
+            &mut ctx.accounts.outbox,
+            split_config.key(),
+            LifecycleEvent::Executed,
+            amount,
+        )?;
+
+        msg!(
+            "Escrow released on oracle value {}: {} lamports to recipient1, {} lamports to recipient2",
+            value,
+            amount1,
+            amount2
+        );
+
+        Ok(())
+    }
+
+    /// Refund deposited SOL to the sender. Escrow deposits are refundable once the
+    /// recipients can no longer both accept; prefunded balances at any time.
+    /// Once a recipient has accepted, the config's cancel penalty is paid to
+    /// the recipients out of the deposit first.
+    pub fn refund_escrow(ctx: Context<RefundEscrow>) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let clock = Clock::get()?;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+        // Validate sender matches
+        require!(
+            split_config.sender == ctx.accounts.sender.key(),
+            SplitError::UnauthorizedSender
+        );
+
+        // Refunds open up once the acceptance deadline has passed
+        require!(
+            !split_config.escrow
+                || split_config.acceptance_deadline == 0
+                || !split_config.can_execute(clock.unix_timestamp),
+            SplitError::RefundNotAvailable
+        );
+
+        // Pulling a deposit a recipient already accepted owes them the penalty
+        let deposit = split_config.deposited_amount;
+        let (penalty1, penalty2) = pay_cancel_penalty(
+            split_config,
+            ctx.accounts.recipient1.as_ref(),
+            ctx.accounts.recipient2.as_ref(),
+        )?;
+
+        let amount = deposit - penalty1 - penalty2;
+        split_config.sub_lamports(amount)?;
+        ctx.accounts.sender.add_lamports(amount)?;
+
+        split_config.deposited_amount = 0;
+        split_config.recipient1_accepted = false;
+        split_config.recipient2_accepted = false;
+
+        emit_recorded!(split_config, EscrowRefunded {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            amount,
+            penalty1,
+            penalty2,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_outbox(
+            &mut ctx.accounts.outbox,
+            split_config.key(),
+            LifecycleEvent::Refunded,
+            amount,
+        )?;
+
+        msg!("Escrow refunded: {} lamports", amount);
+
+        Ok(())
+    }
+
+    /// Compute the gross amount that nets the given recipient amounts at the
+    /// ratio an execution of that gross would use now, returned via return data
+    pub fn compute_gross_for_net(
+        ctx: Context<ComputeGrossForNet>,
+        net1: u64,
+        net2: u64,
+    ) -> Result<u64> {
+        let split_config = &ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        let gross = net1.checked_add(net2).ok_or(SplitError::MathOverflow)?;
+
+        // Nets must be exactly what executing the gross would produce
+        let recipient1_bps = split_config.recipient1_bps_for(gross, Clock::get()?.unix_timestamp);
+        let (amount1, amount2) = compute_shares(gross, recipient1_bps)?;
+        require!(
+            amount1 == net1 && amount2 == net2,
+            SplitError::NetAmountsInconsistent
+        );
+
+        Ok(gross)
+    }
+
+    /// Compute the rent-exempt minimum a split config with the given layout
+    /// costs at initialization, returned via return data. Configs don't store
+    /// a memo, so any nonzero `memo_len` is rejected as a layout they can't have.
+    pub fn estimate_rent(
+        _ctx: Context<EstimateRent>,
+        num_recipients: u8,
+        memo_len: u16,
+    ) -> Result<u64> {
+        // More than two recipients need a recipient set
+        require!(
+            (2..=MAX_STAGED_RECIPIENTS as u8).contains(&num_recipients) && memo_len == 0,
+            SplitError::UnsupportedConfigLayout
+        );
+
+        let rent = Rent::get()?;
+        let mut total = rent.minimum_balance(8 + SplitConfig::INIT_SPACE);
+        if num_recipients > 2 {
+            total = total
+                .checked_add(rent.minimum_balance(8 + RecipientSet::INIT_SPACE))
+                .ok_or(SplitError::MathOverflow)?;
+        }
+
+        Ok(total)
+    }
+
+    /// Quote a token split, returned via return data. Amounts that are not a
+    /// multiple of the config's denomination quote zero shares along with the
+    /// nearest valid amounts on either side.
+    pub fn quote_token_split(
+        ctx: Context<QuoteTokenSplit>,
+        amount: u64,
+        burn_bps: u16,
+    ) -> Result<TokenSplitQuote> {
+        let split_config = &ctx.accounts.split_config;
+
+        split_config.verify()?;
+        require!(burn_bps as u64 <= BPS_DENOMINATOR, SplitError::InvalidBurnBps);
+
+        let denomination = split_config.denomination;
+        let lower_valid_amount = amount - amount % denomination;
+        let upper_valid_amount = if lower_valid_amount == amount {
+            amount
+        } else {
+            lower_valid_amount
+                .checked_add(denomination)
+                .ok_or(SplitError::MathOverflow)?
+        };
+
+        let denominated = lower_valid_amount == amount;
+        let (burned, amount1, amount2) = if denominated {
+            split_config.token_shares(amount, burn_bps, Clock::get()?.unix_timestamp)?
+        } else {
+            (0, 0, 0)
+        };
+
+        Ok(TokenSplitQuote {
+            denominated,
+            burned,
+            amount1,
+            amount2,
+            lower_valid_amount,
+            upper_valid_amount,
+        })
+    }
+
+    /// Resolve what `execute_split` would do with `amount` right now, returned
+    /// via return data. Uses the same planner as execution.
+    pub fn resolve_effective_terms(
+        ctx: Context<ResolveEffectiveTerms>,
+        amount: u64,
+    ) -> Result<EffectiveTerms> {
+        let split_config = &ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        split_config.plan_execution(amount, Clock::get()?.unix_timestamp)
+    }
+
+    /// Check the passed token accounts are the canonical associated token
+    /// accounts of the sender and both recipients for `mint`, returning the
+    /// slots that aren't via return data. An empty result means
+    /// `execute_split_spl` can be sent with these accounts.
+    pub fn validate_token_accounts(ctx: Context<ValidateTokenAccounts>) -> Result<Vec<TokenAccountSlot>> {
+        let split_config = &ctx.accounts.split_config;
+        let mint = ctx.accounts.mint.key();
+
+        let mismatched: Vec<TokenAccountSlot> = [
+            (TokenAccountSlot::Sender, split_config.sender, &ctx.accounts.sender_token_account),
+            (TokenAccountSlot::Recipient1, split_config.recipient1, &ctx.accounts.recipient1_token_account),
+            (TokenAccountSlot::Recipient2, split_config.recipient2, &ctx.accounts.recipient2_token_account),
+        ]
+        .into_iter()
+        .filter(|(_, owner, passed)| passed.key() != associated_token_address(owner, &mint))
+        .map(|(slot, _, passed)| {
+            msg!("{:?} token account {} is not the associated account", slot, passed.key());
+            slot
+        })
+        .collect();
+
+        Ok(mismatched)
+    }
+
+    /// Preview every lamport destination of executing `amount` right now,
+    /// returned via return data. Entries always sum to `amount`; this program
+    /// charges no fee on lamport executions and recipient 2 takes the rounding
+    /// remainder, so there is never a fee or dust entry.
+    pub fn preview_distribution(
+        ctx: Context<PreviewDistribution>,
+        amount: u64,
+    ) -> Result<DistributionPreview> {
+        let split_config = &ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        let terms = split_config.plan_execution(amount, Clock::get()?.unix_timestamp)?;
+
+        // Claim mode parks both shares in the config until they're claimed
+        let holder = |recipient: Pubkey| {
+            if terms.claim_mode {
+                split_config.key()
+            } else {
+                recipient
+            }
+        };
+        let mut entries = vec![
+            DistributionEntry {
+                kind: DistributionKind::Recipient1,
+                destination: holder(split_config.recipient1),
+                amount: terms.amount1,
+            },
+            DistributionEntry {
+                kind: DistributionKind::Recipient2,
+                destination: holder(split_config.recipient2),
+                amount: terms.amount2,
+            },
+        ];
+
+        // Withholding comes out of a delivered slot's gross share; claims are
+        // parked whole
+        if let Some(withholding) = split_config.withholding.filter(|_| !terms.claim_mode) {
+            let share = &mut entries[withholding.applies_to_slot as usize - 1].amount;
+            let withheld = safe_mul_div(*share, withholding.bps as u64, BPS_DENOMINATOR)?;
+            if withheld > 0 {
+                *share -= withheld;
+                entries.push(DistributionEntry {
+                    kind: DistributionKind::Withholding,
+                    destination: withholding.destination,
+                    amount: withheld,
+                });
+            }
+        }
+        if terms.overflow > 0 {
+            entries.push(DistributionEntry {
+                kind: DistributionKind::CapOverflow,
+                destination: split_config.sender,
+                amount: terms.overflow,
+            });
+        }
+
+        Ok(DistributionPreview {
+            amount,
+            blocked_until: terms.blocked_until,
+            entries,
+        })
+    }
+
+    /// Seconds until the config can next execute and until it stops being
+    /// executable, returned via return data for countdowns. Zero until
+    /// executable means it can execute now; no expiry means neither an escrow
+    /// acceptance deadline nor a global lifetime applies.
+    pub fn time_until_executable(ctx: Context<TimeUntilExecutable>) -> Result<ExecutionCountdown> {
+        let split_config = &ctx.accounts.split_config;
+        let now = Clock::get()?.unix_timestamp;
+
+        split_config.verify()?;
+
+        let executable_at = split_config.next_allowed_at(now)?;
+
+        let deadline = (split_config.escrow && split_config.acceptance_deadline != 0)
+            .then_some(split_config.acceptance_deadline);
+        let lifetime_end = match max_config_lifetime(&ctx.accounts.program_config)? {
+            0 => None,
+            lifetime => Some(split_config.created_at.saturating_add(lifetime)),
+        };
+        let expires_at = deadline.into_iter().chain(lifetime_end).min();
+
+        Ok(ExecutionCountdown {
+            executable_at,
+            seconds_until_executable: executable_at.saturating_sub(now).max(0) as u64,
+            expires_at,
+            seconds_until_expiry: expires_at.map(|at| at.saturating_sub(now).max(0) as u64),
+        })
+    }
+
+    /// The config's rolling history digest, returned via return data. Replaying
+    /// the config's events as logged, each `sha256(digest || event data)`
+    /// starting from zero, must reproduce it.
+    pub fn read_history_digest(ctx: Context<ReadHistoryDigest>) -> Result<[u8; 32]> {
+        let split_config = &ctx.accounts.split_config;
+
+        require!(split_config.track_history_digest, SplitError::HistoryDigestDisabled);
+
+        Ok(split_config.history_digest)
+    }
+
+    /// Assert that `recipient` has received exactly `expected` lamports from
+    /// this config over its lifetime, for reconciliation
+    pub fn assert_recipient_total(
+        ctx: Context<AssertRecipientTotal>,
+        recipient: Pubkey,
+        expected: u64,
+    ) -> Result<()> {
+        let split_config = &ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        let received = split_config.received_by(&recipient)?;
+        require!(received == expected, SplitError::TotalMismatch);
+
+        Ok(())
+    }
+
+    /// Recompute the canonical bump of a split configuration and store it if the
+    /// stored value drifted
+    pub fn repair_bump(ctx: Context<RepairBump>) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let canonical_bump = ctx.bumps.split_config;
+
+        if split_config.bump == canonical_bump {
+            msg!("Bump already canonical, nonce: {}", split_config.nonce);
+            return Ok(());
+        }
+
+        let old_bump = split_config.bump;
+        split_config.bump = canonical_bump;
+
+        emit_recorded!(split_config, BumpRepaired {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            old_bump,
+            new_bump: canonical_bump,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Bump repaired: {} -> {}", old_bump, canonical_bump);
+
+        Ok(())
+    }
+
+    /// Overwrite a config's stored bump so tests can exercise `repair_bump`
+    #[cfg(feature = "test-helpers")]
+    pub fn corrupt_bump_for_testing(ctx: Context<RepairBump>, bump: u8) -> Result<()> {
+        ctx.accounts.split_config.bump = bump;
+        Ok(())
+    }
+
+    /// Check a split configuration's state invariants without modifying it
+    pub fn verify_config(ctx: Context<VerifyConfig>) -> Result<()> {
+        ctx.accounts.split_config.verify()
+    }
+
+    /// Create the global program configuration, callable by the upgrade authority
+    pub fn initialize_program_config(ctx: Context<InitializeProgramConfig>) -> Result<()> {
+        let program_config = &mut ctx.accounts.program_config;
+
+        program_config.admin = ctx.accounts.admin.key();
+        program_config.epoch_volume_ceiling = 0;
+        program_config.epoch = Clock::get()?.epoch;
+        program_config.epoch_volume = 0;
+        program_config.charity = Pubkey::default();
+        program_config.open_donations = false;
+        program_config.soft_paused = false;
+        program_config.max_execution_during_pause = 0;
+        program_config.migration_window_end = 0;
+        program_config.max_config_lifetime_seconds = 0;
+        program_config.min_executor_bond = 0;
+        program_config.swap_programs = Vec::new();
+        program_config.token_split_fee_lamports = 0;
+        program_config.creation_fee_lamports = 0;
+        program_config.genesis_guard = [0u8; 8];
+        program_config.allow_composed_close = false;
+        program_config.bump = ctx.bumps.program_config;
+
+        msg!("Program config initialized, admin: {}", program_config.admin);
+
+        Ok(())
+    }
+
+    /// Set the per-epoch execution volume ceiling; zero disables the circuit breaker
+    pub fn set_epoch_ceiling(ctx: Context<SetEpochCeiling>, ceiling: u64) -> Result<()> {
+        let program_config = &mut ctx.accounts.program_config;
+        let old_ceiling = program_config.epoch_volume_ceiling;
+
+        program_config.epoch_volume_ceiling = ceiling;
+
+        emit!(EpochCeilingChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            admin: program_config.admin,
+            old_ceiling,
+            new_ceiling: ceiling,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Epoch volume ceiling set to {}", ceiling);
+
+        Ok(())
+    }
+
+    /// Enter or leave the soft pause, during which only executions up to
+    /// `max_execution_during_pause` lamports are allowed
+    pub fn set_soft_pause(
+        ctx: Context<SetSoftPause>,
+        soft_paused: bool,
+        max_execution_during_pause: u64,
+    ) -> Result<()> {
+        let program_config = &mut ctx.accounts.program_config;
+
+        program_config.soft_paused = soft_paused;
+        program_config.max_execution_during_pause = max_execution_during_pause;
+
+        emit!(SoftPauseChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            admin: program_config.admin,
+            soft_paused,
+            max_execution_during_pause,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Soft pause: {}, max execution: {}",
+            soft_paused,
+            max_execution_during_pause
+        );
+
+        Ok(())
+    }
+
+    /// Close the global program config, refunding its rent to the admin.
+    /// Executions then run without global limits, as before it existed.
+    pub fn close_config(ctx: Context<CloseConfig>) -> Result<()> {
+        let admin = ctx.accounts.admin.key();
+
+        emit!(ProgramConfigClosed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Program config closed by admin: {}", admin);
+
+        // Account will be closed automatically via close constraint
+        Ok(())
+    }
+
+    /// Replace the swap programs `execute_split_after_swap` accepts
+    pub fn set_swap_programs(ctx: Context<SetSwapPrograms>, swap_programs: Vec<Pubkey>) -> Result<()> {
+        require!(
+            swap_programs.len() <= MAX_SWAP_PROGRAMS,
+            SplitError::TooManySwapPrograms
+        );
+
+        msg!("Swap program whitelist set to {} programs", swap_programs.len());
+
+        ctx.accounts.program_config.swap_programs = swap_programs;
+
+        Ok(())
+    }
+
+    /// Set the SOL fee `execute_split_spl_sol_fee` charges per token split
+    pub fn set_token_split_fee(ctx: Context<SetTokenSplitFee>, fee_lamports: u64) -> Result<()> {
+        ctx.accounts.program_config.token_split_fee_lamports = fee_lamports;
+
+        msg!("Token split fee set to {} lamports", fee_lamports);
+
+        Ok(())
+    }
+
+    /// Set the fee `initialize_split` collects into the treasury per config
+    pub fn set_creation_fee(ctx: Context<SetCreationFee>, fee_lamports: u64) -> Result<()> {
+        ctx.accounts.program_config.creation_fee_lamports = fee_lamports;
+
+        msg!("Creation fee set to {} lamports", fee_lamports);
+
+        Ok(())
+    }
+
+    /// Record the cluster guard, e.g. the first 8 bytes of the cluster's
+    /// genesis hash, mixed into the addresses of configs created from now on.
+    /// Configs created before keep their addresses. The guard can be set only
+    /// once: changing it would silently move where new configs are derived.
+    pub fn set_genesis_guard(ctx: Context<SetGenesisGuard>, genesis_guard: [u8; 8]) -> Result<()> {
+        let program_config = &mut ctx.accounts.program_config;
+        require!(program_config.genesis_guard == [0u8; 8], SplitError::GenesisGuardAlreadySet);
+        program_config.genesis_guard = genesis_guard;
+
+        msg!("Genesis guard set to {:?}", genesis_guard);
+
+        Ok(())
+    }
+
+    /// Let `close_split` run after an `execute_split` of the same config in one
+    /// transaction again, for integrations still migrating to
+    /// `execute_and_close_split`
+    pub fn set_allow_composed_close(ctx: Context<SetAllowComposedClose>, allowed: bool) -> Result<()> {
+        ctx.accounts.program_config.allow_composed_close = allowed;
+
+        msg!("Composed execute and close allowed: {}", allowed);
+
+        Ok(())
+    }
+
+    /// Exempt `sender` from the creation fee
+    pub fn grant_fee_exemption(ctx: Context<GrantFeeExemption>, sender: Pubkey) -> Result<()> {
+        let fee_exemption = &mut ctx.accounts.fee_exemption;
+
+        fee_exemption.sender = sender;
+        fee_exemption.bump = ctx.bumps.fee_exemption;
+
+        msg!("Creation fee exemption granted to {}", sender);
+
+        Ok(())
+    }
+
+    /// Approve `client_id` for attribution, recording the hash of its name
+    pub fn register_client(
+        ctx: Context<RegisterClient>,
+        client_id: [u8; 8],
+        name_hash: [u8; 32],
+    ) -> Result<()> {
+        let client_registry = &mut ctx.accounts.client_registry;
+
+        client_registry.client_id = client_id;
+        client_registry.name_hash = name_hash;
+        client_registry.bump = ctx.bumps.client_registry;
+
+        msg!("Client registered: {:?}", client_id);
+
+        Ok(())
+    }
+
+    /// Withdraw a sender's creation fee exemption, returning its rent to the admin
+    pub fn revoke_fee_exemption(ctx: Context<RevokeFeeExemption>) -> Result<()> {
+        msg!(
+            "Creation fee exemption revoked from {}",
+            ctx.accounts.fee_exemption.sender
+        );
+
+        Ok(())
+    }
+
+    /// Set the bond open executors must hold
+    pub fn set_min_executor_bond(ctx: Context<SetMinExecutorBond>, min_executor_bond: u64) -> Result<()> {
+        ctx.accounts.program_config.min_executor_bond = min_executor_bond;
+
+        msg!("Minimum executor bond set to {} lamports", min_executor_bond);
+
+        Ok(())
+    }
+
+    /// Set how long configs stay executable after creation. Zero disables
+    /// the limit.
+    pub fn set_max_config_lifetime(
+        ctx: Context<SetMaxConfigLifetime>,
+        max_config_lifetime_seconds: i64,
+    ) -> Result<()> {
+        require!(max_config_lifetime_seconds >= 0, SplitError::InvalidConfigLifetime);
+
+        ctx.accounts.program_config.max_config_lifetime_seconds = max_config_lifetime_seconds;
+
+        msg!("Max config lifetime set to {} seconds", max_config_lifetime_seconds);
+
+        Ok(())
+    }
+
+    /// Set when the counter backfill window closes. Once it has closed it can no
+    /// longer be moved, so `backfill_counters` stays disabled for good.
+    pub fn set_migration_window(ctx: Context<SetMigrationWindow>, window_end: i64) -> Result<()> {
+        let program_config = &mut ctx.accounts.program_config;
+
+        require!(
+            !program_config.migration_window_closed(Clock::get()?.unix_timestamp),
+            SplitError::MigrationWindowClosed
+        );
+
+        program_config.migration_window_end = window_end;
+
+        msg!("Migration window ends at {}", window_end);
+
+        Ok(())
+    }
+
+    /// Set counters computed off-chain from historical events on a config that
+    /// predates them. Allowed once per config while the migration window is open.
+    pub fn backfill_counters(
+        ctx: Context<BackfillCounters>,
+        execution_count: u64,
+        total_distributed: u64,
+        proof_note: [u8; 32],
+    ) -> Result<()> {
+        let program_config = &ctx.accounts.program_config;
+        let split_config = &mut ctx.accounts.split_config;
+        let clock = Clock::get()?;
+
+        require!(
+            program_config.migration_window_end != 0
+                && !program_config.migration_window_closed(clock.unix_timestamp),
+            SplitError::MigrationWindowClosed
+        );
+        require!(!split_config.backfilled, SplitError::AlreadyBackfilled);
+
+        split_config.execution_count = execution_count;
+        split_config.total_distributed = total_distributed;
+        split_config.backfilled = true;
+
+        emit_recorded!(split_config, CountersBackfilled {
+            schema_version: EVENT_SCHEMA_VERSION,
+            split_config: split_config.key(),
+            admin: program_config.admin,
+            execution_count,
+            total_distributed,
+            proof_note,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Counters backfilled: {} executions, {} lamports",
+            execution_count,
+            total_distributed
+        );
+
+        Ok(())
+    }
+
+    /// Set the charity donations go to, or allow donations to any destination
+    pub fn set_donation_policy(
+        ctx: Context<SetDonationPolicy>,
+        charity: Pubkey,
+        open_donations: bool,
+    ) -> Result<()> {
+        let program_config = &mut ctx.accounts.program_config;
+
+        program_config.charity = charity;
+        program_config.open_donations = open_donations;
+
+        msg!("Donation policy set, charity: {}, open: {}", charity, open_donations);
+
+        Ok(())
+    }
+
+    /// Set the stakeholders that treasury distributions are split between
+    pub fn set_treasury_stakeholders(
+        ctx: Context<SetTreasuryStakeholders>,
+        stakeholder1: Pubkey,
+        stakeholder2: Pubkey,
+        stakeholder1_percentage: u8,
+        stakeholder2_percentage: u8,
+    ) -> Result<()> {
+        require!(
+            stakeholder1_percentage as u16 + stakeholder2_percentage as u16 == 100,
+            SplitError::InvalidPercentages
+        );
+        require!(
+            stakeholder1_percentage > 0 && stakeholder2_percentage > 0,
+            SplitError::ZeroPercentage
+        );
+        require_keys_neq!(stakeholder1, stakeholder2, SplitError::DuplicateRecipient);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.stakeholder1 = stakeholder1;
+        treasury.stakeholder2 = stakeholder2;
+        treasury.stakeholder1_percentage = stakeholder1_percentage;
+        treasury.stakeholder2_percentage = stakeholder2_percentage;
+        treasury.bump = ctx.bumps.treasury;
+
+        msg!(
+            "Treasury stakeholders set: {}% to {}, {}% to {}",
+            stakeholder1_percentage,
+            stakeholder1,
+            stakeholder2_percentage,
+            stakeholder2
+        );
+
+        Ok(())
+    }
+
+    /// Split the fees accumulated in the treasury, above its rent-exempt
+    /// minimum, between the configured stakeholders
+    pub fn distribute_treasury(ctx: Context<DistributeTreasury>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+
+        // Fees still refundable to their senders stay behind
+        let rent_exempt = Rent::get()?.minimum_balance(treasury.to_account_info().data_len());
+        let pending_refunds = treasury.pending_refunds(Clock::get()?.unix_timestamp);
+        let amount = treasury
+            .get_lamports()
+            .saturating_sub(rent_exempt)
+            .saturating_sub(pending_refunds);
+        require!(amount > 0, SplitError::TreasuryEmpty);
+
+        let (amount1, amount2) = compute_shares(amount, treasury.stakeholder1_percentage as u16 * 100)?;
+
+        treasury.sub_lamports(amount)?;
+        ctx.accounts.stakeholder1.add_lamports(amount1)?;
+        ctx.accounts.stakeholder2.add_lamports(amount2)?;
+
+        treasury.total_distributed = treasury
+            .total_distributed
+            .checked_add(amount)
+            .ok_or(SplitError::MathOverflow)?;
+
+        emit!(TreasuryDistributed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            stakeholder1: treasury.stakeholder1,
+            stakeholder2: treasury.stakeholder2,
+            amount1,
+            amount2,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Treasury distributed {} lamports: {} to {}, {} to {}",
+            amount,
+            amount1,
+            treasury.stakeholder1,
+            amount2,
+            treasury.stakeholder2
+        );
+
+        Ok(())
+    }
+
+    /// Claim a recipient's credited share of a claim-mode split, optionally
+    /// donating `donate_bps` of it to the donation destination
+    pub fn claim_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimSplit<'info>>,
+        donate_bps: u16,
+    ) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let recipient = ctx.accounts.recipient.to_account_info();
+
+        split_config.verify()?;
+
+        require!(split_config.claim_mode, SplitError::NotClaimMode);
+        require!(donate_bps as u64 <= BPS_DENOMINATOR, SplitError::InvalidDonationBps);
+        split_config.require_claims_open(&recipient.key())?;
+
+        let claimed = std::mem::take(split_config.claimable_mut(&recipient.key())?);
+        require!(claimed > 0, SplitError::NothingToClaim);
+        record_ledger_claim(split_config, &mut ctx.accounts.recipient_ledger, claimed)?;
+
+        // The whole balance is claimed, so every queued entry goes with it
+        split_config.claim_queue_mut(&recipient.key())?.clear();
+
+        let donated = safe_mul_div(claimed, donate_bps as u64, BPS_DENOMINATOR)?;
+        let kept = claimed
+            .checked_sub(donated)
+            .ok_or(SplitError::MathOverflow)?;
+
+        // The config PDA is program-owned, so claimed lamports move directly
+        split_config.sub_lamports(claimed)?;
+
+        let mut donation_destination = Pubkey::default();
+        if donated > 0 {
+            let destination = ctx
+                .accounts
+                .donation_destination
+                .as_ref()
+                .ok_or(SplitError::DonationDestinationNotAllowed)?;
+            let program_config = ctx
+                .accounts
+                .program_config
+                .as_ref()
+                .ok_or(SplitError::DonationDestinationNotAllowed)?;
+            require!(
+                program_config.open_donations
+                    || (program_config.charity != Pubkey::default()
+                        && destination.key() == program_config.charity),
+                SplitError::DonationDestinationNotAllowed
+            );
+            destination.add_lamports(donated)?;
+            donation_destination = destination.key();
+        }
+
+        if kept > 0 {
+            let delivery = if recipient.key() == split_config.recipient1 {
+                &split_config.recipient1_delivery
+            } else {
+                &split_config.recipient2_delivery
+            };
+            deliver_share(split_config, delivery, &recipient, ctx.remaining_accounts, kept, |to| {
+                to.add_lamports(kept)?;
+                Ok(())
+            })?;
+        }
+
+        emit_recorded!(split_config, SplitClaimed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            recipient: event_recipient(split_config, &recipient.key()),
+            nonce: split_config.nonce,
+            claimed_amount: claimed,
+            recipient_amount: kept,
+            donated_amount: donated,
+            donation_destination,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        record_outbox(
+            &mut ctx.accounts.outbox,
+            split_config.key(),
+            LifecycleEvent::Claimed,
+            claimed,
+        )?;
+
+        msg!(
+            "Claimed {} lamports: {} to recipient, {} donated",
+            claimed,
+            kept,
+            donated
+        );
+
+        Ok(())
+    }
+
+    /// Claim exactly the oldest entry in the recipient's claim queue
+    pub fn claim_next<'info>(ctx: Context<'_, '_, '_, 'info, ClaimSplit<'info>>) -> Result<()> {
+        process_claim_queue(ctx, 1)
+    }
+
+    /// Claim every entry in the recipient's claim queue, oldest first
+    pub fn claim_all<'info>(ctx: Context<'_, '_, '_, 'info, ClaimSplit<'info>>) -> Result<()> {
+        process_claim_queue(ctx, CLAIM_QUEUE_CAPACITY)
+    }
+
+    /// Freeze the signing recipient's unclaimed balance, for a recipient who
+    /// suspects their key is compromised
+    pub fn freeze_my_claims(ctx: Context<FreezeMyClaims>) -> Result<()> {
+        let recipient = ctx.accounts.recipient.key();
+        let slot = ctx.accounts.split_config.recipient_slot(&recipient)?;
+
+        freeze_claims(&mut ctx.accounts.split_config, slot, recipient)
+    }
+
+    /// Freeze a recipient slot's unclaimed balance on the sender's authority
+    pub fn freeze_recipient_claims(ctx: Context<ReconfigureSplit>, slot: u8) -> Result<()> {
+        let sender = ctx.accounts.sender.key();
+
+        freeze_claims(&mut ctx.accounts.split_config, slot, sender)
+    }
+
+    /// Register, with the recipient's current key, the key that may take over
+    /// their claims if the slot is frozen
+    pub fn register_claims_recovery(ctx: Context<AttestAgreement>, new_address: Pubkey) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+        let recipient = ctx.accounts.recipient.key();
+
+        split_config.verify()?;
+
+        require!(split_config.claim_mode, SplitError::NotClaimMode);
+        require!(
+            new_address != Pubkey::default()
+                && new_address != split_config.recipient1
+                && new_address != split_config.recipient2,
+            SplitError::InvalidRecipient
+        );
+
+        let slot = split_config.recipient_slot(&recipient)?;
+        *split_config.claim_status_mut(slot)?.1 = new_address;
+
+        emit_recorded!(split_config, ClaimsRecoveryRegistered {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            slot,
+            recipient: event_recipient(split_config, &recipient),
+            recovery: event_recipient(split_config, &new_address),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Claims recovery registered for slot {}", slot);
+
+        Ok(())
+    }
+
+    /// Move a frozen slot to the recovery key its recipient registered, signed
+    /// by both the sender and that key. The slot's balance and claim queue go
+    /// with it and its claims reopen.
+    pub fn redirect_frozen_claims(ctx: Context<RedirectFrozenClaims>, new_address: Pubkey) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        require_keys_eq!(ctx.accounts.new_recipient.key(), new_address, SplitError::RecoveryKeyMismatch);
+
+        // The address commits to the original recipients
+        require!(split_config.terms_hash == [0u8; 32], SplitError::TermsCommitted);
+
+        let slot = if new_address == split_config.recipient1_recovery {
+            1
+        } else if new_address == split_config.recipient2_recovery {
+            2
+        } else {
+            return err!(SplitError::RecoveryKeyMismatch);
+        };
+        let (status, recovery) = split_config.claim_status_mut(slot)?;
+        require!(*status == ClaimStatus::Frozen, SplitError::ClaimsNotFrozen);
+        *status = ClaimStatus::Open;
+        *recovery = Pubkey::default();
+
+        let (old_recipient, claimable) = if slot == 1 {
+            let old = std::mem::replace(&mut split_config.recipient1, new_address);
+            (old, split_config.recipient1_claimable)
+        } else {
+            let old = std::mem::replace(&mut split_config.recipient2, new_address);
+            (old, split_config.recipient2_claimable)
+        };
+
+        emit_recorded!(split_config, ClaimsRedirected {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            slot,
+            old_recipient: event_recipient(split_config, &old_recipient),
+            new_recipient: event_recipient(split_config, &new_address),
+            claimable,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Frozen claims of slot {} redirected to {}",
+            slot,
+            event_recipient(split_config, &new_address)
+        );
+
+        Ok(())
+    }
+
+    /// Create the sender's outbox for polling-based lifecycle tracking
+    pub fn init_outbox(ctx: Context<InitOutbox>) -> Result<()> {
+        let outbox = &mut ctx.accounts.outbox;
+
+        outbox.sender = ctx.accounts.sender.key();
+        outbox.next_seq = 1;
+        outbox.acked_seq = 0;
+        outbox.lost_events = 0;
+        outbox.records = Vec::new();
+        outbox.bump = ctx.bumps.outbox;
+
+        msg!("Outbox initialized");
+
+        Ok(())
+    }
+
+    /// Create the sender's queue of executed configs awaiting rent reclamation
+    pub fn init_reclaim_queue(ctx: Context<InitReclaimQueue>) -> Result<()> {
+        let reclaim_queue = &mut ctx.accounts.reclaim_queue;
+
+        reclaim_queue.sender = ctx.accounts.sender.key();
+        reclaim_queue.configs = Vec::new();
+        reclaim_queue.bump = ctx.bumps.reclaim_queue;
+
+        msg!("Reclaim queue initialized");
+
+        Ok(())
+    }
+
+    /// Switch an unexecuted config to the multi-recipient layout, paying
+    /// `shares` instead of its recipient pair. Basis points must total 10_000
+    /// and the config must not use options tied to the pair, such as claim
+    /// mode, caps or delivery overrides. Executes only through
+    /// `execute_split_multi` afterwards, or `stage_split` for sets of more
+    /// than MAX_MULTI_RECIPIENTS recipients.
+    pub fn init_recipient_set(ctx: Context<InitRecipientSet>, shares: Vec<RecipientShare>) -> Result<()> {
+        let split_config = &mut ctx.accounts.split_config;
+
+        split_config.verify()?;
+
+        require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+        require!(split_config.execution_count == 0, SplitError::AlreadyExecuted);
+        require!(split_config.deposited_amount == 0, SplitError::EscrowNotEmpty);
+        require!(split_config.supports_multi_recipients(), SplitError::MultiRecipientUnsupported);
+        require!(split_config.layout != LAYOUT_STAGED, SplitError::SplitStaged);
+        require!(split_config.terms_hash == [0u8; 32], SplitError::TermsCommitted);
+        validate_recipient_shares(&shares, MAX_STAGED_RECIPIENTS)?;
+
+        let recipient_set = &mut ctx.accounts.recipient_set;
+        recipient_set.split_config = split_config.key();
+        recipient_set.shares = shares;
+        split_config.layout = LAYOUT_MULTI_RECIPIENT;
+
+        emit_recorded!(split_config, RecipientSetConfigured {
+            schema_version: EVENT_SCHEMA_VERSION,
+            split_config: split_config.key(),
+            recipients: recipient_set.shares.len() as u8,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Split now pays {} recipients", recipient_set.shares.len());
+
+        Ok(())
+    }
+
+    /// Create `recipient`'s ledger ahead of an execution that can't create it,
+    /// such as `execute_from_deposit`. Anyone may pay for it.
+    pub fn init_recipient_ledger(ctx: Context<InitRecipientLedger>, recipient: Pubkey) -> Result<()> {
+        ctx.accounts.recipient_ledger.recipient = recipient;
+
+        msg!("Recipient ledger initialized for {}", recipient);
+
+        Ok(())
+    }
+
+    /// Register or replace the signing recipient's preferred token account for
+    /// `mint`. Token splits passing the preference must pay that account.
+    pub fn set_recipient_preference(ctx: Context<SetRecipientPreference>) -> Result<()> {
+        let preference = &mut ctx.accounts.preference;
+        preference.recipient = ctx.accounts.recipient.key();
+        preference.mint = ctx.accounts.mint.key();
+        preference.token_account = ctx.accounts.token_account.key();
+        preference.bump = ctx.bumps.preference;
+
+        msg!("Preferred token account set to {}", preference.token_account);
+
+        Ok(())
+    }
+
+    /// Close the signing recipient's preference for a mint, refunding its rent
+    pub fn close_recipient_preference(_ctx: Context<CloseRecipientPreference>) -> Result<()> {
+        msg!("Recipient preference closed");
+
+        Ok(())
+    }
+
+    /// Close the signing recipient's ledger, refunding its rent to them. Only
+    /// allowed once nothing is pending, so no unclaimed credit is forgotten.
+    pub fn close_recipient_ledger(ctx: Context<CloseRecipientLedger>) -> Result<()> {
+        require!(
+            ctx.accounts.recipient_ledger.pending == 0,
+            SplitError::LedgerPending
+        );
+
+        msg!("Recipient ledger closed");
+
+        Ok(())
+    }
+
+    /// Close every config in the sender's reclaim queue in one pass, refunding
+    /// their rent to the sender. The queued configs are passed as remaining
+    /// accounts in queue order.
+    pub fn sweep_reclaim<'info>(ctx: Context<'_, '_, '_, 'info, SweepReclaim<'info>>) -> Result<()> {
+        let reclaim_queue = &mut ctx.accounts.reclaim_queue;
+        let sender = ctx.accounts.sender.to_account_info();
+        let configs = ctx.remaining_accounts;
+
+        require!(
+            configs.len() == reclaim_queue.configs.len()
+                && configs.iter().zip(&reclaim_queue.configs).all(|(info, key)| info.key == key),
+            SplitError::ReclaimAccountsMismatch
+        );
+
+        let mut reclaimed: u64 = 0;
+        for info in configs {
+            require_keys_eq!(*info.owner, crate::ID, SplitError::InvalidConfigState);
+            let split_config = SplitConfig::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            split_config.verify()?;
+            require!(split_config.is_terminal(), SplitError::NotExecuted);
+
+            reclaimed = reclaimed
+                .checked_add(close_account_safely(info, &sender)?)
+                .ok_or(SplitError::MathOverflow)?;
+        }
+        let count = reclaim_queue.configs.len() as u32;
+        reclaim_queue.configs.clear();
+
+        emit!(RentReclaimed {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: sender.key(),
+            configs: count,
+            lamports: reclaimed,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Reclaimed {} lamports from {} configs", reclaimed, count);
+
+        Ok(())
+    }
+
+    /// Close the signing sender's program accounts passed as remaining
+    /// accounts: configs with their histories, route table, reclaim queue,
+    /// settings and outbox, in that order, with all rent to the sender. Refuses
+    /// if any config still holds a deposit, unclaimed shares, a rent split or
+    /// receipts not yet forwarded, or is queued for reclaim without its queue
+    /// in the batch, listing each in the logs. With `dry_run` nothing is closed and the logs
+    /// report what would be. Large footprints take several calls.
+    pub fn teardown_sender<'info>(
+        ctx: Context<'_, '_, '_, 'info, TeardownSender<'info>>,
+        dry_run: bool,
+    ) -> Result<()> {
+        let sender = ctx.accounts.sender.to_account_info();
+        let batch = ctx.remaining_accounts;
+
+        require!(
+            !batch.is_empty() && batch.len() <= MAX_TEARDOWN_BATCH,
+            SplitError::TeardownBatchTooLarge
+        );
+
+        let mut plan = Vec::with_capacity(batch.len());
+        let mut blocked: u32 = 0;
+        for info in batch {
+            let (kind, offence) = teardown_kind(info, sender.key, batch)?;
+            if let Some(offence) = offence {
+                msg!("Config {} {}", info.key, offence);
+                blocked += 1;
+            }
+            plan.push((kind, info));
+        }
+        require!(dry_run || blocked == 0, SplitError::TeardownBlocked);
+
+        // Histories go before their configs and the outbox goes last
+        plan.sort_by_key(|(kind, _)| *kind);
+
+        let mut reclaimed: u64 = 0;
+        for (kind, info) in &plan {
+            let lamports = if dry_run {
+                msg!("Would close {:?} {} ({} lamports)", kind, info.key, info.lamports());
+                info.lamports()
+            } else {
+                close_account_safely(info, &sender)?
+            };
+            reclaimed = reclaimed
+                .checked_add(lamports)
+                .ok_or(SplitError::MathOverflow)?;
+        }
+
+        emit!(SenderTornDown {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: sender.key(),
+            dry_run,
+            accounts: plan.len() as u32,
+            blocked,
+            lamports: reclaimed,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Teardown {}: {} accounts, {} lamports, {} blocked",
+            if dry_run { "dry run" } else { "complete" },
+            plan.len(),
+            reclaimed,
+            blocked
+        );
+
+        Ok(())
+    }
+
+    /// Mark outbox records up to and including a sequence number as consumed
+    pub fn ack_outbox(ctx: Context<AckOutbox>, up_to_seq: u64) -> Result<()> {
+        let outbox = &mut ctx.accounts.outbox;
+
+        // Only sequence numbers that have been written can be acknowledged
+        require!(up_to_seq < outbox.next_seq, SplitError::InvalidAckSequence);
+
+        outbox.acked_seq = outbox.acked_seq.max(up_to_seq);
+
+        msg!("Outbox acknowledged up to seq: {}", outbox.acked_seq);
+
+        Ok(())
+    }
+
+    /// Close the sender's outbox to reclaim rent
+    pub fn close_outbox(_ctx: Context<CloseOutbox>) -> Result<()> {
+        msg!("Outbox closed");
+
+        // Account will be closed automatically via close constraint
+        Ok(())
+    }
+}
+
+/// Shared body of the SPL token execution variants
+fn process_execute_split_spl<'info>(
+    accounts: &mut ExecuteSplitSpl<'info>,
+    amount: u64,
+    burn_bps: u16,
+    routes: Option<SwapRoutes<'_, 'info>>,
+) -> Result<()> {
+    let split_config = &mut accounts.split_config;
+
+    split_config.verify()?;
+
+    // Ensure split hasn't been executed yet
+    require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+    // Escrow configs only release through recipient confirmation
+    require!(!split_config.escrow, SplitError::EscrowConfig);
+
+    // A prefunded balance must be spent or refunded before the final execution
+    require!(split_config.deposited_amount == 0, SplitError::EscrowNotEmpty);
+
+    // Multi-recipient configs execute through execute_split_multi
+    split_config.require_two_recipients()?;
+
+    // Claimable balances are tracked in lamports only
+    require!(!split_config.claim_mode, SplitError::ClaimModeConfig);
+
+    // Validate sender matches the original configuration
+    require!(
+        split_config.sender == accounts.sender.key(),
+        SplitError::UnauthorizedSender
+    );
+
+    split_config.check_execution_window(Clock::get()?.unix_timestamp)?;
+    split_config.consume_approvals()?;
+    require!(split_config.cascade_target1.is_none(), SplitError::CascadeUnsupported);
+
+    // Token executions have no reclaim queue to register with
+    require!(!split_config.defer_reclaim, SplitError::ReclaimQueueRequired);
+
+    require!(amount > 0, SplitError::AmountTooSmall);
+    require!(burn_bps as u64 <= BPS_DENOMINATOR, SplitError::InvalidBurnBps);
+
+    let (burned, amount1, amount2) =
+        split_config.token_shares(amount, burn_bps, Clock::get()?.unix_timestamp)?;
+
+    require!(
+        accounts.sender_token_account.amount >= amount,
+        SplitError::InsufficientBalance
+    );
+
+    let preferences_honored = [
+        honor_preference(&accounts.recipient1_preference, &accounts.recipient1_token_account.key())?,
+        honor_preference(&accounts.recipient2_preference, &accounts.recipient2_token_account.key())?,
+    ];
+
+    let token_program = accounts.token_program.to_account_info();
+    let decimals = accounts.mint.decimals;
+
+    // Burn authority is the source account owner, which must be the signer
+    if burned > 0 {
+        token::burn(
+            CpiContext::new(
+                token_program.clone(),
+                Burn {
+                    mint: accounts.mint.to_account_info(),
+                    from: accounts.sender_token_account.to_account_info(),
+                    authority: accounts.sender.to_account_info(),
+                },
+            ),
+            burned,
+        )?;
+    }
+
+    let clock = Clock::get()?;
+    let config_key = split_config.key();
+
+    for (slot, recipient, spec, destination, share) in [
+        (
+            1,
+            split_config.recipient1,
+            split_config.recipient1_auto_swap,
+            &accounts.recipient1_token_account,
+            amount1,
+        ),
+        (
+            2,
+            split_config.recipient2,
+            split_config.recipient2_auto_swap,
+            &accounts.recipient2_token_account,
+            amount2,
+        ),
+    ] {
+        if let (Some(spec), true) = (spec, share > 0) {
+            let route = match &routes {
+                Some(routes) => routes.leg(slot, &spec.venue_program)?,
+                None => None,
+            };
+
+            if let Some((leg, leg_accounts)) = route {
+                let amount_out = swap_token_share(
+                    &mut accounts.sender_token_account,
+                    &recipient,
+                    &spec,
+                    leg,
+                    leg_accounts,
+                    share,
+                )?;
+
+                emit_recorded!(split_config, TokenLegSwapped {
+                    schema_version: EVENT_SCHEMA_VERSION,
+                    sender: split_config.sender,
+                    split_config: config_key,
+                    slot,
+                    recipient: event_recipient(split_config, &recipient),
+                    venue_program: spec.venue_program,
+                    target_mint: spec.target_mint,
+                    amount_in: share,
+                    amount_out,
+                    timestamp: clock.unix_timestamp,
+                });
+                continue;
+            }
+
+            // Without a usable route the leg is paid in the original mint only
+            // if the config opted into that
+            require!(
+                split_config.swap_fallback_to_original,
+                SplitError::AutoSwapUnavailable
+            );
+
+            emit_recorded!(split_config, AutoSwapFallback {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sender: split_config.sender,
+                split_config: config_key,
+                slot,
+                recipient: event_recipient(split_config, &recipient),
+                amount: share,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        token::transfer_checked(
+            CpiContext::new(
+                token_program.clone(),
+                TransferChecked {
+                    from: accounts.sender_token_account.to_account_info(),
+                    mint: accounts.mint.to_account_info(),
+                    to: destination.to_account_info(),
+                    authority: accounts.sender.to_account_info(),
+                },
+            ),
+            share,
+            decimals,
+        )?;
+    }
+
+    // Mark as executed to prevent replay
+    split_config.mark_executed(clock.unix_timestamp);
+
+    emit_recorded!(split_config, TokenSplitExecuted {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sender: split_config.sender,
+        mint: accounts.mint.key(),
+        recipient1: event_recipient(split_config, &split_config.recipient1),
+        recipient2: event_recipient(split_config, &split_config.recipient2),
+        amount1,
+        amount2,
+        burned,
+        total_amount: amount,
+        topic: split_config.topic,
+        preferences_honored,
+        timestamp: clock.unix_timestamp,
+    });
+
+    record_outbox(
+        &mut accounts.outbox,
+        split_config.key(),
+        LifecycleEvent::Executed,
+        amount,
+    )?;
+
+    msg!(
+        "Token split executed: {} to recipient1, {} to recipient2, {} burned",
+        amount1,
+        amount2,
+        burned
+    );
+
+    Ok(())
+}
+
+/// Validate a pair of recipients and their percentages
+fn validate_terms(
+    recipient1: &Pubkey,
+    recipient2: &Pubkey,
+    recipient1_percentage: u8,
+    recipient2_percentage: u8,
+) -> Result<()> {
+    // Validate percentages sum to exactly 100
+    require!(
+        recipient1_percentage as u16 + recipient2_percentage as u16 == 100,
+        SplitError::InvalidPercentages
+    );
+
+    // Validate percentages are non-zero
+    require!(
+        recipient1_percentage > 0 && recipient2_percentage > 0,
+        SplitError::ZeroPercentage
+    );
+
+    // Validate recipients are not the same
+    require!(recipient1 != recipient2, SplitError::DuplicateRecipient);
+
+    // Validate recipients are not system program
+    require!(
+        *recipient1 != system_program::ID && *recipient2 != system_program::ID,
+        SplitError::InvalidRecipient
+    );
+
+    Ok(())
+}
+
+/// Wrapped SOL destinations must already be valid wSOL accounts of the recipient
+fn validate_deliveries(
+    deliveries: [(&DeliverAs, Pubkey); 2],
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    for (delivery, recipient) in deliveries {
+        if let DeliverAs::WrappedSol { token_account } = delivery {
+            let info = find_account(remaining_accounts, token_account)
+                .ok_or(SplitError::WrappedAccountMissing)?;
+            validate_wrapped_sol_account(info, &recipient)?;
+        }
+    }
+    Ok(())
+}
+
+/// Shared initialization path for all initialize variants
+fn process_initialize_split(
+    ctx: Context<InitializeSplit>,
+    recipient1_percentage: u8,
+    recipient2_percentage: u8,
+    nonce: u64,
+    options: SplitOptions,
+    defaults_applied: u16,
+    initial_deposit: u64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let remaining_accounts = ctx.remaining_accounts;
+    let bump = ctx.bumps.split_config;
+    // Callers deriving without the guard were already rejected by the seeds
+    let nonce = guarded_nonce(nonce, genesis_guard(&accounts.program_config)?);
+
+    validate_terms(
+        &accounts.recipient1.key(),
+        &accounts.recipient2.key(),
+        recipient1_percentage,
+        recipient2_percentage,
+    )?;
+
+    require!(
+        !(options.claim_mode && options.escrow),
+        SplitError::IncompatibleOptions
+    );
+
+    // Only escrow deposits wait in the config, so only they take outside funders
+    require!(
+        options.escrow || (!options.open_deposits && options.allowed_depositors.is_empty()),
+        SplitError::IncompatibleOptions
+    );
+    validate_allowed_depositors(&options.allowed_depositors)?;
+
+    validate_blackout_windows(&options.blackout_windows)?;
+    validate_ratio_tiers(&options.ratio_tiers)?;
+
+    // Remainder rotation depends on how many executions came before and a ramp
+    // on the clock, so neither can give the same shares whenever execution lands
+    require!(
+        !options.deterministic_only || !(options.rotate_remainder || options.ratio_ramp.is_some()),
+        SplitError::NonDeterministicOptionForbidden
+    );
+
+    // A ramp replaces the config's ratio over time; escrow releases and ratio
+    // tables don't follow it
+    if let Some(ramp) = &options.ratio_ramp {
+        ramp.validate()?;
+        require!(
+            !options.escrow && options.ratio_tiers.is_empty(),
+            SplitError::IncompatibleOptions
+        );
+    }
+
+    // A cascading recipient 1 is the linked child config itself, executed
+    // directly rather than credited or escrowed
+    if let Some(target) = options.cascade_target1 {
+        require!(
+            target == accounts.recipient1.key() && target != accounts.split_config.key(),
+            SplitError::InvalidCascadeTarget
+        );
+        require!(
+            !options.claim_mode && !options.escrow,
+            SplitError::IncompatibleOptions
+        );
+    }
+
+    // A sweep returns everything to the sender, so deferred configs can't hold
+    // escrow, claims, or rent and residual destined for anyone else
+    require!(
+        !options.defer_reclaim
+            || !(options.escrow
+                || options.claim_mode
+                || options.rent_split.is_some()
+                || options.close_residual_to_recipients),
+        SplitError::IncompatibleOptions
+    );
+
+    for spec in [&options.recipient1_auto_swap, &options.recipient2_auto_swap]
+        .into_iter()
+        .flatten()
+    {
+        // Venue CPIs are only compiled into builds with the `auto-swap` feature
+        require!(cfg!(feature = "auto-swap"), SplitError::AutoSwapDisabled);
+        require!(spec.min_out_bps > 0, SplitError::InvalidSwapSpec);
+    }
+
+    // Escrow releases and cascades aren't recorded on recipient ledgers
+    require!(
+        !options.update_recipient_ledger || !(options.escrow || options.cascade_target1.is_some()),
+        SplitError::IncompatibleOptions
+    );
+
+    if let ShortfallPolicy::PrioritizeSlot(slot) = options.shortfall_policy {
+        require!(slot == 1 || slot == 2, SplitError::InvalidShortfallPolicy);
+    }
+
+    // Escrows release their whole deposit at once, leaving no room for overflow
+    require!(
+        !options.escrow || (options.recipient1_cap == 0 && options.recipient2_cap == 0),
+        SplitError::IncompatibleOptions
+    );
+
+    // Forwarded receipts are paid out like a prefunded execution, which
+    // escrows, cascades and ledger updates don't take part in
+    require!(
+        !options.forward_receipts
+            || !(options.escrow
+                || options.cascade_target1.is_some()
+                || options.update_recipient_ledger),
+        SplitError::IncompatibleOptions
+    );
+
+    // Oracle conditions gate the release of an escrowed deposit
+    require!(
+        options.oracle_condition.is_none() || options.escrow,
+        SplitError::IncompatibleOptions
+    );
+
+    if let Some(withholding) = &options.withholding {
+        validate_withholding(withholding, &accounts.recipient1.key(), &accounts.recipient2.key())?;
+        require!(
+            withholding_compatible(
+                options.claim_mode,
+                options.cascade_target1.is_some(),
+                options.close_residual_to_recipients,
+                options.update_recipient_ledger,
+            ),
+            SplitError::IncompatibleOptions
+        );
+    }
+
+    validate_approvers(&options.approvers, options.approvals_required)?;
+
+    // Only escrow deposits wait on acceptance, so only they can owe a penalty
+    require!(
+        options.cancel_penalty_bps <= MAX_CANCEL_PENALTY_BPS
+            && (options.cancel_penalty_bps == 0 || options.escrow),
+        SplitError::InvalidCancelPenalty
+    );
+
+    if let Some(window) = &options.executor_quiet_window {
+        require!(window.start < window.end, SplitError::InvalidQuietWindow);
+    }
+
+    if let Some(rent_split) = &options.rent_split {
+        require!(
+            rent_split.percentage <= 100
+                && rent_split.destination != Pubkey::default()
+                && rent_split.destination != system_program::ID,
+            SplitError::InvalidRentSplit
+        );
+    }
+
+    validate_deliveries(
+        [
+            (&options.recipient1_delivery, accounts.recipient1.key()),
+            (&options.recipient2_delivery, accounts.recipient2.key()),
+        ],
+        remaining_accounts,
+    )?;
+
+    let split_config = &mut accounts.split_config;
+    let clock = Clock::get()?;
+    
+    split_config.sender = accounts.sender.key();
+    split_config.recipient1 = accounts.recipient1.key();
+    split_config.recipient2 = accounts.recipient2.key();
+    split_config.recipient1_percentage = recipient1_percentage;
+    split_config.recipient2_percentage = recipient2_percentage;
+    split_config.executed = false;
+    split_config.nonce = nonce;
+    split_config.created_at = clock.unix_timestamp;
+    split_config.executed_at = 0;
+    split_config.bump = bump;
+    split_config.topic = [0u8; 8];
+    split_config.sender_min_balance = options.sender_min_balance;
+    split_config.escrow = options.escrow;
+    split_config.acceptance_deadline = options.acceptance_deadline;
+    split_config.deposited_amount = 0;
+    split_config.recipient1_accepted = false;
+    split_config.recipient2_accepted = false;
+    split_config.open_deposits = options.open_deposits;
+    split_config.allowed_depositors = options.allowed_depositors;
+    split_config.recipient1_delivery = options.recipient1_delivery;
+    split_config.recipient2_delivery = options.recipient2_delivery;
+    split_config.delegate = Pubkey::default();
+    split_config.delegate_daily_limit = 0;
+    split_config.delegate_window_start = 0;
+    split_config.delegate_window_spent = 0;
+    split_config.rotate_remainder = options.rotate_remainder;
+    split_config.remainder_rotation = 0;
+    split_config.claim_mode = options.claim_mode;
+    split_config.recipient1_claimable = 0;
+    split_config.recipient2_claimable = 0;
+    split_config.agreement_hash = options.agreement_hash;
+    split_config.recipient1_attested = false;
+    split_config.recipient2_attested = false;
+    split_config.rent_split = options.rent_split;
+    split_config.min_recipient_data_len = options.min_recipient_data_len;
+    split_config.execution_count = 0;
+    split_config.total_distributed = 0;
+    split_config.backfilled = false;
+    // Whole-unit granularity unless the config declares a coarser one
+    split_config.denomination = options.denomination.max(1);
+    split_config.close_residual_to_recipients = options.close_residual_to_recipients;
+    split_config.oracle_condition = options.oracle_condition;
+    split_config.anyone_can_execute = options.anyone_can_execute;
+    split_config.executor_quiet_window = options.executor_quiet_window;
+    split_config.recipient1_cap = options.recipient1_cap;
+    split_config.recipient2_cap = options.recipient2_cap;
+    split_config.recipient1_received = 0;
+    split_config.recipient2_received = 0;
+    split_config.blackout_windows = options.blackout_windows;
+    split_config.shortfall_policy = options.shortfall_policy;
+    split_config.allow_partial = options.allow_partial;
+    split_config.recipient1_shortfall = 0;
+    split_config.recipient2_shortfall = 0;
+    split_config.recipient1_claim_queue = Vec::new();
+    split_config.recipient2_claim_queue = Vec::new();
+    split_config.require_execution_memo = options.require_execution_memo;
+    split_config.cascade_target1 = options.cascade_target1;
+    split_config.defer_reclaim = options.defer_reclaim;
+    split_config.review_period_seconds = options.review_period_seconds;
+    split_config.recipient1_auto_swap = options.recipient1_auto_swap;
+    split_config.recipient2_auto_swap = options.recipient2_auto_swap;
+    split_config.swap_fallback_to_original = options.swap_fallback_to_original;
+    split_config.update_recipient_ledger = options.update_recipient_ledger;
+    split_config.cancel_cooldown_seconds = options.cancel_cooldown_seconds;
+    split_config.creation_fee_paid = 0;
+    split_config.pending_delegate = Pubkey::default();
+    split_config.pending_delegate_daily_limit = 0;
+    split_config.ratio_tiers = options.ratio_tiers;
+    split_config.layout = LAYOUT_TWO_RECIPIENTS;
+    split_config.track_history_digest = options.track_history_digest;
+    split_config.forward_receipts = options.forward_receipts;
+    split_config.history_digest = [0u8; 32];
+    split_config.recipient1_bps = 0;
+    split_config.withholding = options.withholding;
+    split_config.compact_events = options.compact_events;
+    split_config.terms_hash = [0u8; 32];
+    split_config.ratio_ramp = options.ratio_ramp;
+    split_config.recipient1_claim_status = ClaimStatus::Open;
+    split_config.recipient2_claim_status = ClaimStatus::Open;
+    split_config.recipient1_recovery = Pubkey::default();
+    split_config.recipient2_recovery = Pubkey::default();
+    split_config.external_ref = options.external_ref;
+    split_config.private_recipients = options.private_recipients;
+    split_config.verified_recipients = accounts.recipient1.is_signer && accounts.recipient2.is_signer;
+    split_config.deterministic_only = options.deterministic_only;
+    split_config.approvers = options.approvers;
+    split_config.approvals_required = options.approvals_required;
+    split_config.approvals = 0;
+    split_config.client = attribute_client(
+        options.client_id,
+        accounts.client_registry.as_deref(),
+    );
+    split_config.cancel_penalty_bps = options.cancel_penalty_bps;
+    split_config.cancel_penalty_armed = false;
+    split_config.recipient1_penalty_paid = 0;
+    split_config.recipient2_penalty_paid = 0;
+
+    let (rent_costs, total_rent) = initialization_rent_costs(&[(
+        split_config.key(),
+        8 + SplitConfig::INIT_SPACE,
+        true,
+    )])?;
+
+    // Exempt senders skip the anti-spam fee entirely
+    let fee = if accounts.fee_exemption.is_some() {
+        0
+    } else {
+        creation_fee(&accounts.program_config)?
+    };
+    if fee > 0 {
+        require_keys_eq!(*accounts.treasury.owner, crate::ID, SplitError::TreasuryRequired);
+
+        system_program::transfer(
+            CpiContext::new(
+                accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: accounts.sender.to_account_info(),
+                    to: accounts.treasury.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+        split_config.creation_fee_paid = fee;
+
+        // The fee stays refundable for a day, so distributions hold it back
+        let treasury_info = accounts.treasury.to_account_info();
+        let mut treasury = Treasury::try_deserialize(&mut &treasury_info.data.borrow()[..])?;
+        treasury.reserve_refund(fee, clock.unix_timestamp);
+        treasury.try_serialize(&mut &mut treasury_info.data.borrow_mut()[..])?;
+
+        emit_recorded!(split_config, CreationFeeCollected {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            split_config: split_config.key(),
+            fee,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    emit_recorded!(split_config, SplitInitialized {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sender: split_config.sender,
+        recipient1: event_recipient(split_config, &split_config.recipient1),
+        recipient2: event_recipient(split_config, &split_config.recipient2),
+        recipient1_percentage,
+        recipient2_percentage,
+        nonce,
+        topic: split_config.topic,
+        agreement_hash: split_config.agreement_hash,
+        defaults_applied,
+        review_period_seconds: split_config.review_period_seconds,
+        rent_costs,
+        total_rent,
+        external_ref: split_config.external_ref,
+        client: split_config.client,
+        timestamp: clock.unix_timestamp,
+    });
+
+    record_outbox(
+        &mut accounts.outbox,
+        split_config.key(),
+        LifecycleEvent::Initialized,
+        0,
+    )?;
+
+    if initial_deposit > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: accounts.sender.to_account_info(),
+                    to: split_config.to_account_info(),
+                },
+            ),
+            initial_deposit,
+        )?;
+        split_config.deposited_amount = initial_deposit;
+
+        emit_recorded!(split_config, EscrowDeposited {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            amount: initial_deposit,
+            deposited_amount: initial_deposit,
+            timestamp: clock.unix_timestamp,
+        });
+
+        record_outbox(
+            &mut accounts.outbox,
+            split_config.key(),
+            LifecycleEvent::Deposited,
+            initial_deposit,
+        )?;
+    }
+
+    msg!(
+        "Split initialized: {}% / {}%, nonce: {}", 
+        recipient1_percentage, 
+        recipient2_percentage,
+        nonce
+    );
+    
+    Ok(())
+}
+
+/// Validate a raw account is the real System Program
+pub fn assert_system_program(info: &AccountInfo) -> Result<()> {
+    require_keys_eq!(info.key(), system_program::ID, SplitError::InvalidSystemProgram);
+    require!(info.executable, SplitError::InvalidSystemProgram);
+    Ok(())
+}
+
+/// Require a token split destination to be the recipient's preferred account
+/// when their preference was passed, returning whether one was
+fn honor_preference(
+    preference: &Option<Box<Account<RecipientPreference>>>,
+    destination: &Pubkey,
+) -> Result<bool> {
+    let Some(preference) = preference else {
+        return Ok(false);
+    };
+    require_keys_eq!(preference.token_account, *destination, SplitError::PreferenceViolation);
+    Ok(true)
+}
+
+/// Load and validate one `execute_splits_batch` entry without changing
+/// anything, returning the config and its planned shares, or `None` when it
+/// has already executed
+fn prepare_batch_entry<'info>(
+    sender: &Pubkey,
+    config_info: &'info AccountInfo<'info>,
+    recipient1: &AccountInfo<'info>,
+    recipient2: &AccountInfo<'info>,
+    amount: u64,
+    clock: &Clock,
+) -> Result<Option<(Account<'info, SplitConfig>, EffectiveTerms)>> {
+    require!(config_info.is_writable, SplitError::BatchAccountsMismatch);
+    let split_config = Account::<SplitConfig>::try_from(config_info)?;
+
+    split_config.verify()?;
+
+    require_keys_eq!(split_config.sender, *sender, SplitError::UnauthorizedSender);
+    if split_config.is_terminal() {
+        return Ok(None);
+    }
+
+    require!(!split_config.escrow, SplitError::EscrowConfig);
+    require!(split_config.deposited_amount == 0, SplitError::EscrowNotEmpty);
+    split_config.require_two_recipients()?;
+
+    // Batches only pay the pair directly, with nothing else to record
+    require!(
+        !split_config.claim_mode
+            && split_config.cascade_target1.is_none()
+            && split_config.recipient1_delivery == DeliverAs::Native
+            && split_config.recipient2_delivery == DeliverAs::Native
+            && !split_config.require_execution_memo
+            && !split_config.defer_reclaim
+            && !split_config.update_recipient_ledger
+            && split_config.withholding.is_none()
+            && split_config.approvals_required == 0,
+        SplitError::BatchUnsupportedConfig
+    );
+
+    require_keys_eq!(split_config.recipient1, recipient1.key(), SplitError::InvalidRecipient);
+    require_keys_eq!(split_config.recipient2, recipient2.key(), SplitError::InvalidRecipient);
+    split_config.check_recipient_data_len(recipient1, recipient2)?;
+    split_config.check_execution_window(clock.unix_timestamp)?;
+
+    let terms = split_config.plan_execution(amount, clock.unix_timestamp)?;
+
+    Ok(Some((split_config, terms)))
+}
+
+/// Require the sender to afford `delivered` and keep its configured buffer
+fn check_sender_funds(sender: &Signer, split_config: &SplitConfig, delivered: u64) -> Result<()> {
+    let balance = sender.get_lamports();
+    require!(balance >= delivered, SplitError::InsufficientBalance);
+    require!(
+        balance - delivered >= split_config.sender_min_balance,
+        SplitError::SenderBalanceBelowMinimum
+    );
+    Ok(())
+}
+
+/// Numeric code of an error, as reported to clients
+fn error_code(err: &Error) -> u32 {
+    match err {
+        Error::AnchorError(err) => err.error_code_number,
+        Error::ProgramError(err) => u64::from(err.program_error.clone()) as u32,
+    }
+}
+
+/// Canonical SPL Token associated token account of `owner` for `mint`
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ATA_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Validate a raw account is the real SPL Token program
+pub fn assert_token_program(info: &AccountInfo) -> Result<()> {
+    require_keys_eq!(info.key(), TOKEN_PROGRAM_ID, SplitError::InvalidTokenProgram);
+    require!(info.executable, SplitError::InvalidTokenProgram);
+    Ok(())
+}
+
+/// Validate a raw account is the real Associated Token Account program
+pub fn assert_ata_program(info: &AccountInfo) -> Result<()> {
+    require_keys_eq!(info.key(), ATA_PROGRAM_ID, SplitError::InvalidAtaProgram);
+    require!(info.executable, SplitError::InvalidAtaProgram);
+    Ok(())
+}
+
+/// Append a lifecycle record to the sender's outbox when one was passed.
+/// Records overwrite the oldest slot once the ring is full and never fail the
+/// surrounding instruction; overwritten unacknowledged records bump `lost_events`.
+fn record_outbox(
+    outbox: &mut Option<Box<Account<Outbox>>>,
+    config: Pubkey,
+    event_type: LifecycleEvent,
+    amount: u64,
+) -> Result<()> {
+    let Some(outbox) = outbox else {
+        return Ok(());
+    };
+
+    let seq = outbox.next_seq;
+    let record = OutboxRecord {
+        config,
+        event_type,
+        amount,
+        seq,
+    };
+
+    if outbox.records.len() < OUTBOX_CAPACITY {
+        outbox.records.push(record);
+    } else {
+        let slot = ((seq - 1) % OUTBOX_CAPACITY as u64) as usize;
+        if outbox.records[slot].seq > outbox.acked_seq {
+            outbox.lost_events = outbox.lost_events.saturating_add(1);
+        }
+        outbox.records[slot] = record;
+    }
+
+    outbox.next_seq = seq.saturating_add(1);
+
+    Ok(())
+}
+
+/// Apply the admin's program-wide execution limits: the soft pause ceiling and
+/// the per-epoch volume circuit breaker, which this execution's amount is added
+/// to. Before the program config exists there are no limits to enforce.
+fn apply_program_limits(
+    program_config: &AccountInfo,
+    split_config: &SplitConfig,
+    amount: u64,
+) -> Result<()> {
+    if program_config.data_is_empty() {
+        return Ok(());
+    }
+    require_keys_eq!(*program_config.owner, crate::ID, SplitError::InvalidConfigState);
+
+    let mut config = ProgramConfig::try_deserialize(&mut &program_config.data.borrow()[..])?;
+
+    // Configs past the global lifetime can only be cleaned up
+    require!(
+        !config.config_expired(split_config.created_at, Clock::get()?.unix_timestamp),
+        SplitError::ConfigExpired
+    );
+
+    // A soft pause only lets small executions through
+    require!(
+        !config.soft_paused || amount <= config.max_execution_during_pause,
+        SplitError::SoftPauseLimitExceeded
+    );
+
+    let epoch = Clock::get()?.epoch;
+
+    if config.epoch != epoch {
+        config.epoch = epoch;
+        config.epoch_volume = 0;
+    }
+
+    let volume = config
+        .epoch_volume
+        .checked_add(amount)
+        .ok_or(SplitError::MathOverflow)?;
+
+    if config.epoch_volume_ceiling > 0 && volume > config.epoch_volume_ceiling {
+        let headroom = config.epoch_volume_ceiling.saturating_sub(config.epoch_volume);
+
+        // The failed transaction rolls back any state, but its logs are kept, so
+        // the event still reaches indexers for every tripped attempt
+        emit!(CircuitBreakerTripped {
+            schema_version: EVENT_SCHEMA_VERSION,
+            epoch,
+            epoch_volume: config.epoch_volume,
+            ceiling: config.epoch_volume_ceiling,
+            attempted_amount: amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!(
+            "Circuit breaker tripped: {} lamports requested, {} lamports of headroom left in epoch {}",
+            amount,
+            headroom,
+            epoch
+        );
+        return err!(SplitError::CircuitBreakerTripped);
+    }
+
+    config.epoch_volume = volume;
+    config.try_serialize(&mut &mut program_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Bond open executors must hold, zero while the program config is missing
+fn min_executor_bond(program_config: &AccountInfo) -> Result<u64> {
+    if program_config.data_is_empty() {
+        return Ok(0);
+    }
+    require_keys_eq!(*program_config.owner, crate::ID, SplitError::InvalidConfigState);
+
+    let config = ProgramConfig::try_deserialize(&mut &program_config.data.borrow()[..])?;
+    Ok(config.min_executor_bond)
+}
+
+/// Creation fee set in the program config, none if it doesn't exist
+fn creation_fee(program_config: &AccountInfo) -> Result<u64> {
+    if program_config.data_is_empty() {
+        return Ok(0);
+    }
+    require_keys_eq!(*program_config.owner, crate::ID, SplitError::InvalidConfigState);
+
+    let config = ProgramConfig::try_deserialize(&mut &program_config.data.borrow()[..])?;
+    Ok(config.creation_fee_lamports)
+}
+
+/// Cluster guard recorded in the program config, zero if unset or the program
+/// config doesn't exist
+fn genesis_guard(program_config: &AccountInfo) -> Result<[u8; 8]> {
+    if program_config.data_is_empty() {
+        return Ok([0u8; 8]);
+    }
+    require_keys_eq!(*program_config.owner, crate::ID, SplitError::InvalidConfigState);
+
+    let config = ProgramConfig::try_deserialize(&mut &program_config.data.borrow()[..])?;
+    Ok(config.genesis_guard)
+}
+
+/// Nonce a config is stored and derived under: the caller's nonce, or on a
+/// guarded deployment that nonce hashed with the cluster guard
+pub fn guarded_nonce(nonce: u64, genesis_guard: [u8; 8]) -> u64 {
+    if genesis_guard == [0u8; 8] {
+        return nonce;
+    }
+    let digest = hashv(&[b"genesis_guard", &genesis_guard, &nonce.to_le_bytes()]).to_bytes();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// Address a config for `nonce` and `terms_seed` would have without the
+/// cluster guard
+fn unguarded_split_config_address(sender: &Pubkey, nonce: u64, terms_seed: &[u8]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"split_config", sender.as_ref(), &nonce.to_le_bytes(), terms_seed],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Nonce seed of a config being initialized. On a guarded deployment an
+/// address derived from the bare nonce is rejected with
+/// `ClusterGuardMismatch` rather than a generic seeds error; that check is the
+/// only extra derivation, and unguarded deployments skip it.
+fn split_config_seed_nonce(
+    program_config: &AccountInfo,
+    split_config: &Pubkey,
+    sender: &Pubkey,
+    nonce: u64,
+    terms_seed: &[u8],
+) -> Result<[u8; 8]> {
+    let guard = genesis_guard(program_config)?;
+    if guard == [0u8; 8] {
+        return Ok(nonce.to_le_bytes());
+    }
+    require_keys_neq!(
+        *split_config,
+        unguarded_split_config_address(sender, nonce, terms_seed),
+        SplitError::ClusterGuardMismatch
+    );
+    Ok(guarded_nonce(nonce, guard).to_le_bytes())
+}
+
+/// Canonical hash of the terms a committed config's address commits to: both
+/// recipients, their percentages, and the payout flags packed as bits in this
+/// order: escrow, claim_mode, anyone_can_execute, allow_partial,
+/// rotate_remainder, close_residual_to_recipients, defer_reclaim
+pub fn terms_hash(
+    recipient1: &Pubkey,
+    recipient2: &Pubkey,
+    recipient1_percentage: u8,
+    recipient2_percentage: u8,
+    options: &SplitOptions,
+) -> [u8; 32] {
+    let flags = [
+        options.escrow,
+        options.claim_mode,
+        options.anyone_can_execute,
+        options.allow_partial,
+        options.rotate_remainder,
+        options.close_residual_to_recipients,
+        options.defer_reclaim,
+    ]
+    .into_iter()
+    .enumerate()
+    .fold(0u16, |bits, (bit, set)| bits | (u16::from(set) << bit));
+
+    hashv(&[
+        b"solsplit_terms",
+        recipient1.as_ref(),
+        recipient2.as_ref(),
+        &[recipient1_percentage, recipient2_percentage],
+        &flags.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Whether the admin has opted out of rejecting a `close_split` composed after
+/// an execution, false if the program config doesn't exist
+fn composed_close_allowed(program_config: &AccountInfo) -> Result<bool> {
+    if program_config.data_is_empty() {
+        return Ok(false);
+    }
+    require_keys_eq!(*program_config.owner, crate::ID, SplitError::InvalidConfigState);
+
+    let config = ProgramConfig::try_deserialize(&mut &program_config.data.borrow()[..])?;
+    Ok(config.allow_composed_close)
+}
+
+/// Whether any execution of `split_config` runs before the current
+/// instruction in this transaction
+fn executed_earlier_in_transaction(instructions: &AccountInfo, split_config: &Pubkey) -> Result<bool> {
+    let current = sysvar::instructions::load_current_index_checked(instructions)?;
+    for index in 0..current {
+        let instruction = sysvar::instructions::load_instruction_at_checked(index as usize, instructions)?;
+        if instruction.program_id != crate::ID {
+            continue;
+        }
+        let Some((first, stride)) = executed_config_positions(&instruction.data) else {
+            continue;
+        };
+        let mut configs = instruction.accounts.iter().skip(first);
+        let executed = if cascades_from(&instruction.data) {
+            // Cascaded children travel as the parent's recipient or in its
+            // remaining accounts, so any of its accounts may have executed
+            configs.any(|meta| meta.pubkey == *split_config)
+        } else if stride == 0 {
+            configs.next().is_some_and(|meta| meta.pubkey == *split_config)
+        } else {
+            configs.step_by(stride).any(|meta| meta.pubkey == *split_config)
+        };
+        if executed {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Account index of the config an instruction of this program executes, with
+/// the stride between further configs for batches (zero for a single config),
+/// or None for instructions that don't execute a config
+fn executed_config_positions(data: &[u8]) -> Option<(usize, usize)> {
+    use crate::instruction as ix;
+
+    // Wrapping executions take the inner execution's accounts first
+    let single: &[&[u8]] = &[
+        ix::ExecuteSplit::DISCRIMINATOR,
+        ix::ExecuteSplitWithMemo::DISCRIMINATOR,
+        ix::ExecuteSplitMulti::DISCRIMINATOR,
+        ix::ExecuteSplitAfterSwap::DISCRIMINATOR,
+        ix::ExecuteSplitSpl::DISCRIMINATOR,
+        ix::ExecuteSplitSplSolFee::DISCRIMINATOR,
+        ix::ExecuteSplitDelegatedToken::DISCRIMINATOR,
+        ix::ExecuteAndCloseSplit::DISCRIMINATOR,
+        ix::ExecuteFromDeposit::DISCRIMINATOR,
+        ix::ConfirmAndRelease::DISCRIMINATOR,
+        ix::ReleaseOnCondition::DISCRIMINATOR,
+        // Only the last page executes, but any page may be it
+        ix::CommitSplitPage::DISCRIMINATOR,
+        #[cfg(feature = "auto-swap")]
+        ix::ExecuteSplitSplAutoSwap::DISCRIMINATOR,
+    ];
+
+    if single.iter().any(|discriminator| data.starts_with(discriminator)) {
+        Some((0, 0))
+    } else if data.starts_with(ix::ExecuteRouted::DISCRIMINATOR) {
+        // The route table precedes the execution's accounts
+        Some((1, 0))
+    } else if data.starts_with(ix::ExecuteSplitsBatch::DISCRIMINATOR) {
+        // Each entry is a config and its two recipients after the three fixed accounts
+        Some((3, 3))
+    } else {
+        None
+    }
+}
+
+/// Whether an instruction of this program may execute linked child configs
+/// through `cascade_share`
+fn cascades_from(data: &[u8]) -> bool {
+    use crate::instruction as ix;
+
+    [
+        ix::ExecuteSplit::DISCRIMINATOR,
+        ix::ExecuteSplitWithMemo::DISCRIMINATOR,
+        ix::ExecuteSplitAfterSwap::DISCRIMINATOR,
+        ix::ExecuteAndCloseSplit::DISCRIMINATOR,
+        ix::ExecuteRouted::DISCRIMINATOR,
+    ]
+    .iter()
+    .any(|discriminator| data.starts_with(discriminator))
+}
+
+/// Global config lifetime in seconds, zero if unset or the program config
+/// doesn't exist
+fn max_config_lifetime(program_config: &AccountInfo) -> Result<i64> {
+    if program_config.data_is_empty() {
+        return Ok(0);
+    }
+    require_keys_eq!(*program_config.owner, crate::ID, SplitError::InvalidConfigState);
+
+    let config = ProgramConfig::try_deserialize(&mut &program_config.data.borrow()[..])?;
+    Ok(config.max_config_lifetime_seconds)
+}
+
+/// Reject a config past the global lifetime, which can only be cleaned up
+fn check_config_lifetime(
+    program_config: &AccountInfo,
+    split_config: &SplitConfig,
+    now: i64,
+) -> Result<()> {
+    let lifetime = max_config_lifetime(program_config)?;
+    require!(
+        lifetime == 0 || now < split_config.created_at.saturating_add(lifetime),
+        SplitError::ConfigExpired
+    );
+    Ok(())
+}
+
+/// Close a program-owned account by hand: move all its lamports to
+/// `destination`, zero its data and return it to the system program at zero
+/// length, so nothing of the old account survives to be revived later in the
+/// transaction. Returns the lamports moved.
+fn close_account_safely(info: &AccountInfo, destination: &AccountInfo) -> Result<u64> {
+    let lamports = info.lamports();
+    info.sub_lamports(lamports)?;
+    destination.add_lamports(lamports)?;
+
+    info.try_borrow_mut_data()?.fill(0);
+    info.assign(&system_program::ID);
+    info.resize(0)?;
+
+    Ok(lamports)
+}
+
+/// Sender-owned account kinds `teardown_sender` closes, in closing order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum TeardownKind {
+    History,
+    RecipientSet,
+    Config,
+    RouteTable,
+    ReclaimQueue,
+    SenderSettings,
+    Outbox,
+}
+
+/// Identify an account passed to `teardown_sender` and prove it belongs to
+/// `sender`, along with why a config can't be torn down yet. Histories and
+/// recipient sets prove ownership through their config, which must be in the
+/// same batch; so must the reclaim queue of a config queued in it.
+fn teardown_kind(
+    info: &AccountInfo,
+    sender: &Pubkey,
+    batch: &[AccountInfo],
+) -> Result<(TeardownKind, Option<&'static str>)> {
+    require_keys_eq!(*info.owner, crate::ID, SplitError::InvalidTeardownAccount);
+    require!(info.is_writable, SplitError::InvalidTeardownAccount);
+
+    let data = info.try_borrow_data()?;
+    let at = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &crate::ID).0 == *info.key;
+    let sender_pda = |seed: &[u8], owner: Pubkey| owner == *sender && at(&[seed, sender.as_ref()]);
+    let queue_in_batch = || {
+        let queue = Pubkey::find_program_address(&[b"reclaim_queue", sender.as_ref()], &crate::ID).0;
+        batch.iter().any(|other| *other.key == queue)
+    };
+
+    let (kind, owned, offence) = if data.starts_with(SplitConfig::DISCRIMINATOR) {
+        let config = SplitConfig::try_deserialize(&mut &data[..])?;
+        let offence = if config.deposited_amount > 0 {
+            Some("holds a deposit")
+        } else if config.has_outstanding_claims() {
+            Some("has unclaimed shares")
+        } else if config.layout == LAYOUT_STAGED {
+            Some("has a staged split being committed")
+        } else if config.rent_split.is_some() {
+            Some("splits its rent; close it with close_split or cancel_split")
+        } else if config.forward_receipts
+            && !config.is_terminal()
+            && info.lamports() > Rent::get()?.minimum_balance(info.data_len())
+        {
+            Some("holds receipts not yet forwarded; crank forward_and_split first")
+        } else if config.defer_reclaim
+            && config.is_terminal()
+            && !queue_in_batch()
+        {
+            Some("is queued for reclaim; pass the reclaim queue too")
+        } else {
+            None
+        };
+        let owned = config.sender == *sender
+            && at(&[
+                b"split_config",
+                sender.as_ref(),
+                &config.nonce.to_le_bytes(),
+                config.terms_seed(),
+            ]);
+        (TeardownKind::Config, owned, offence)
+    } else if data.starts_with(SplitHistory::DISCRIMINATOR) {
+        let history = SplitHistory::try_deserialize(&mut &data[..])?;
+        let owned = batch.iter().any(|other| *other.key == history.split_config)
+            && at(&[b"history", history.split_config.as_ref()]);
+        (TeardownKind::History, owned, None)
+    } else if data.starts_with(RecipientSet::DISCRIMINATOR) {
+        let set = RecipientSet::try_deserialize(&mut &data[..])?;
+        let owned = batch.iter().any(|other| *other.key == set.split_config)
+            && at(&[b"recipient_set", set.split_config.as_ref()]);
+        (TeardownKind::RecipientSet, owned, None)
+    } else if data.starts_with(RouteTable::DISCRIMINATOR) {
+        let table = RouteTable::try_deserialize(&mut &data[..])?;
+        (TeardownKind::RouteTable, sender_pda(b"route_table", table.sender), None)
+    } else if data.starts_with(ReclaimQueue::DISCRIMINATOR) {
+        let queue = ReclaimQueue::try_deserialize(&mut &data[..])?;
+        (TeardownKind::ReclaimQueue, sender_pda(b"reclaim_queue", queue.sender), None)
+    } else if data.starts_with(SenderSettings::DISCRIMINATOR) {
+        let settings = SenderSettings::try_deserialize(&mut &data[..])?;
+        (TeardownKind::SenderSettings, sender_pda(b"sender_settings", settings.sender), None)
+    } else if data.starts_with(Outbox::DISCRIMINATOR) {
+        let outbox = Outbox::try_deserialize(&mut &data[..])?;
+        (TeardownKind::Outbox, sender_pda(b"outbox", outbox.sender), None)
+    } else {
+        return err!(SplitError::InvalidTeardownAccount);
+    };
+    require!(owned, SplitError::InvalidTeardownAccount);
+
+    Ok((kind, offence))
+}
+
+/// Swap programs whitelisted in the program config, none if it doesn't exist
+fn swap_programs(program_config: &AccountInfo) -> Result<Vec<Pubkey>> {
+    if program_config.data_is_empty() {
+        return Ok(Vec::new());
+    }
+    require_keys_eq!(*program_config.owner, crate::ID, SplitError::InvalidConfigState);
+
+    let config = ProgramConfig::try_deserialize(&mut &program_config.data.borrow()[..])?;
+    Ok(config.swap_programs)
+}
+
+/// Venue routes for the auto-swapped legs of a token execution
+#[cfg_attr(not(feature = "auto-swap"), allow(dead_code))]
+struct SwapRoutes<'a, 'info> {
+    whitelist: Vec<Pubkey>,
+    legs: Vec<SwapLeg>,
+    accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> SwapRoutes<'a, 'info> {
+    /// The leg for `slot` and its accounts, none if no leg was given or its
+    /// venue isn't whitelisted
+    fn leg(&self, slot: u8, venue: &Pubkey) -> Result<Option<(&SwapLeg, &'a [AccountInfo<'info>])>> {
+        let mut offset = 0usize;
+        for leg in &self.legs {
+            let end = offset + 2 + leg.account_count as usize;
+            let accounts = self
+                .accounts
+                .get(offset..end)
+                .ok_or(SplitError::SwapRouteInvalid)?;
+            if leg.slot == slot {
+                return Ok(self.whitelist.contains(venue).then_some((leg, accounts)));
+            }
+            offset = end;
+        }
+        Ok(None)
+    }
+}
+
+/// Swap a leg through its venue with the sender's signature, then check the
+/// venue spent exactly the share and the recipient's target mint ATA received
+/// at least the slippage floor. Returns the amount received.
+#[cfg(feature = "auto-swap")]
+fn swap_token_share<'info>(
+    source: &mut Account<'info, TokenAccount>,
+    recipient: &Pubkey,
+    spec: &SwapSpec,
+    leg: &SwapLeg,
+    leg_accounts: &[AccountInfo<'info>],
+    share: u64,
+) -> Result<u64> {
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+
+    let [venue, target, venue_accounts @ ..] = leg_accounts else {
+        return err!(SplitError::SwapRouteInvalid);
+    };
+    require_keys_eq!(venue.key(), spec.venue_program, SplitError::SwapRouteInvalid);
+    require_keys_eq!(
+        target.key(),
+        associated_token_address(recipient, &spec.target_mint),
+        SplitError::SwapRouteInvalid
+    );
+    require!(
+        target.owner == &TOKEN_PROGRAM_ID && !target.data_is_empty(),
+        SplitError::SwapRouteInvalid
+    );
+
+    // Earlier legs moved tokens out of the source since it was loaded
+    source.reload()?;
+    let source_before = source.amount;
+    let target_before = TokenAccount::try_deserialize(&mut &target.data.borrow()[..])?.amount;
+
+    let instruction = Instruction {
+        program_id: spec.venue_program,
+        accounts: venue_accounts
+            .iter()
+            .map(|info| AccountMeta {
+                pubkey: info.key(),
+                is_signer: info.is_signer,
+                is_writable: info.is_writable,
+            })
+            .collect(),
+        data: leg.data.clone(),
+    };
+    let mut infos = venue_accounts.to_vec();
+    infos.push(venue.clone());
+    invoke(&instruction, &infos)?;
+
+    source.reload()?;
+    let spent = source_before.saturating_sub(source.amount);
+    require!(spent == share, SplitError::SwapInputMismatch);
+
+    let target_after = TokenAccount::try_deserialize(&mut &target.data.borrow()[..])?.amount;
+    let received = target_after.saturating_sub(target_before);
+    let min_out = safe_mul_div(share, spec.min_out_bps as u64, BPS_DENOMINATOR)?;
+    require!(received >= min_out, SplitError::SwapOutputTooSmall);
+
+    Ok(received)
+}
+
+/// Builds without the `auto-swap` feature never invoke a venue
+#[cfg(not(feature = "auto-swap"))]
+fn swap_token_share(
+    _: &mut Account<TokenAccount>,
+    _: &Pubkey,
+    _: &SwapSpec,
+    _: &SwapLeg,
+    _: &[AccountInfo],
+    _: u64,
+) -> Result<u64> {
+    err!(SplitError::AutoSwapDisabled)
+}
+
+/// Pay an armed cancel penalty out of the escrow deposit being returned to
+/// the sender, split between the recipients like a release of the deposit,
+/// and disarm it. Returns each recipient's share.
+fn pay_cancel_penalty<'info>(
+    split_config: &mut Account<'info, SplitConfig>,
+    recipient1: Option<&UncheckedAccount<'info>>,
+    recipient2: Option<&UncheckedAccount<'info>>,
+) -> Result<(u64, u64)> {
+    let penalty = if split_config.cancel_penalty_armed {
+        safe_mul_div(
+            split_config.deposited_amount,
+            split_config.cancel_penalty_bps as u64,
+            BPS_DENOMINATOR,
+        )?
+    } else {
+        0
+    };
+    let recipient1_bps = split_config
+        .recipient1_bps_for(split_config.deposited_amount, Clock::get()?.unix_timestamp);
+    let (penalty1, penalty2) = compute_shares(penalty, recipient1_bps)?;
+    for (recipient, share) in [(recipient1, penalty1), (recipient2, penalty2)] {
+        if share == 0 {
+            continue;
+        }
+        let recipient = recipient.ok_or(SplitError::PenaltyRecipientsRequired)?;
+        split_config.sub_lamports(share)?;
+        recipient.add_lamports(share)?;
+    }
+    split_config.recipient1_penalty_paid = split_config
+        .recipient1_penalty_paid
+        .checked_add(penalty1)
+        .ok_or(SplitError::MathOverflow)?;
+    split_config.recipient2_penalty_paid = split_config
+        .recipient2_penalty_paid
+        .checked_add(penalty2)
+        .ok_or(SplitError::MathOverflow)?;
+    split_config.cancel_penalty_armed = false;
+
+    Ok((penalty1, penalty2))
+}
+
+/// Send the configured share of a closing config's lamports to the rent split
+/// destination. The close constraint then returns the rest to the sender.
+fn distribute_rent<'info>(
+    split_config: &Account<'info, SplitConfig>,
+    sender: &AccountInfo<'info>,
+    destination: Option<&UncheckedAccount<'info>>,
+) -> Result<()> {
+    let lamports = split_config.get_lamports();
+
+    let (destination_key, destination_amount) = match &split_config.rent_split {
+        Some(rent_split) => {
+            let destination = destination.ok_or(SplitError::InvalidRentSplit)?;
+            require_keys_eq!(
+                destination.key(),
+                rent_split.destination,
+                SplitError::InvalidRentSplit
+            );
+
+            let amount = safe_mul_div(lamports, rent_split.percentage as u64, 100)?;
+            split_config.sub_lamports(amount)?;
+            destination.add_lamports(amount)?;
+            (rent_split.destination, amount)
+        }
+        None => (Pubkey::default(), 0),
+    };
+
+    emit!(RentDistributed {
+        schema_version: EVENT_SCHEMA_VERSION,
+        split_config: split_config.key(),
+        sender: sender.key(),
+        sender_amount: lamports - destination_amount,
+        destination: destination_key,
+        destination_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Split whatever the config holds above its rent-exempt minimum between the
+/// recipients, before the rent itself is reclaimed
+fn distribute_residual<'info>(
+    split_config: &Account<'info, SplitConfig>,
+    recipient1: Option<&AccountInfo<'info>>,
+    recipient2: Option<&AccountInfo<'info>>,
+) -> Result<()> {
+    let rent_exempt = Rent::get()?.minimum_balance(split_config.to_account_info().data_len());
+    let residual = split_config.get_lamports().saturating_sub(rent_exempt);
+    if residual == 0 {
+        return Ok(());
+    }
+
+    let (recipient1, recipient2) = match (recipient1, recipient2) {
+        (Some(recipient1), Some(recipient2))
+            if recipient1.key() == split_config.recipient1
+                && recipient2.key() == split_config.recipient2 =>
+        {
+            (recipient1, recipient2)
+        }
+        _ => return err!(SplitError::InvalidRecipient),
+    };
+
+    let recipient1_bps = split_config.recipient1_bps_for(residual, Clock::get()?.unix_timestamp);
+    let (amount1, amount2) = compute_shares(residual, recipient1_bps)?;
+    split_config.sub_lamports(residual)?;
+    recipient1.add_lamports(amount1)?;
+    recipient2.add_lamports(amount2)?;
+
+    emit!(ResidualDistributed {
+        schema_version: EVENT_SCHEMA_VERSION,
+        split_config: split_config.key(),
+        recipient1: event_recipient(split_config, &split_config.recipient1),
+        recipient2: event_recipient(split_config, &split_config.recipient2),
+        amount1,
+        amount2,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// A withholding must take a real part of at most MAX_WITHHOLDING_BPS from
+/// slot 1 or 2, to a destination other than the recipients
+fn validate_withholding(withholding: &Withholding, recipient1: &Pubkey, recipient2: &Pubkey) -> Result<()> {
+    require!(
+        withholding.bps > 0 && withholding.bps <= MAX_WITHHOLDING_BPS,
+        SplitError::InvalidWithholding
+    );
+    require!(
+        withholding.applies_to_slot == 1 || withholding.applies_to_slot == 2,
+        SplitError::InvalidWithholding
+    );
+    require!(
+        withholding.destination != Pubkey::default()
+            && withholding.destination != system_program::ID
+            && withholding.destination != *recipient1
+            && withholding.destination != *recipient2,
+        SplitError::InvalidWithholding
+    );
+    Ok(())
+}
+
+/// Withholding is only taken on paths paying the recipients directly, so it
+/// can't combine with claims, cascades, residual payouts or ledger updates
+fn withholding_compatible(
+    claim_mode: bool,
+    cascades: bool,
+    close_residual_to_recipients: bool,
+    update_recipient_ledger: bool,
+) -> bool {
+    !(claim_mode || cascades || close_residual_to_recipients || update_recipient_ledger)
+}
+
+/// Take the config's withholding out of `slot`'s gross `share`, paying it with
+/// `pay` to the destination passed in the remaining accounts. Returns the net
+/// share left for the recipient.
+fn withhold_share<'info>(
+    split_config: &mut Account<'info, SplitConfig>,
+    remaining_accounts: &[AccountInfo<'info>],
+    slot: u8,
+    share: u64,
+    pay: impl FnOnce(&Account<'info, SplitConfig>, &AccountInfo<'info>, u64) -> Result<()>,
+) -> Result<u64> {
+    let Some(withholding) = split_config.withholding.filter(|w| w.applies_to_slot == slot) else {
+        return Ok(share);
+    };
+
+    let withheld = safe_mul_div(share, withholding.bps as u64, BPS_DENOMINATOR)?;
+    if withheld > 0 {
+        let destination = find_account(remaining_accounts, &withholding.destination)
+            .ok_or(SplitError::WithholdingDestinationMissing)?;
+        pay(split_config, destination, withheld)?;
+    }
+    let net = share - withheld;
+
+    emit_recorded!(split_config, ShareWithheld {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sender: split_config.sender,
+        nonce: split_config.nonce,
+        slot,
+        destination: withholding.destination,
+        gross: share,
+        withheld,
+        net,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(net)
+}
+
+/// Find a passed account by address
+fn find_account<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    key: &Pubkey,
+) -> Option<&'a AccountInfo<'info>> {
+    accounts.iter().find(|info| info.key == key)
+}
+
+/// Validate an escrow's allowed depositors: distinct, non-default, within capacity
+fn validate_allowed_depositors(depositors: &[Pubkey]) -> Result<()> {
+    require!(
+        depositors.len() <= MAX_ALLOWED_DEPOSITORS,
+        SplitError::InvalidDepositors
+    );
+    for (i, depositor) in depositors.iter().enumerate() {
+        require!(
+            *depositor != Pubkey::default() && !depositors[..i].contains(depositor),
+            SplitError::InvalidDepositors
+        );
+    }
+    Ok(())
+}
+
+/// Validate a wrapped SOL token account still exists and belongs to the recipient
+fn validate_wrapped_sol_account(info: &AccountInfo, recipient: &Pubkey) -> Result<()> {
+    // Closed token accounts are left empty and owned by the System Program
+    require!(
+        info.owner == &TOKEN_PROGRAM_ID && !info.data_is_empty(),
+        SplitError::WrappedAccountMissing
+    );
+
+    let token_account = TokenAccount::try_deserialize(&mut &info.data.borrow()[..])
+        .map_err(|_| SplitError::WrappedAccountMissing)?;
+    require_keys_eq!(token_account.mint, native_mint::ID, SplitError::InvalidWrappedAccount);
+    require_keys_eq!(token_account.owner, *recipient, SplitError::InvalidWrappedAccount);
+
+    Ok(())
+}
+
+/// Deliver a recipient's share according to its delivery preference. `pay` moves
+/// the lamports to the given destination; wrapped SOL destinations are synced
+/// afterwards so the token balance reflects the deposit, and stake destinations
+/// are delegated before being handed to the recipient.
+fn deliver_share<'info>(
+    split_config: &Account<'info, SplitConfig>,
+    delivery: &DeliverAs,
+    recipient: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    share: u64,
+    pay: impl FnOnce(&AccountInfo<'info>) -> Result<()>,
+) -> Result<()> {
+    match delivery {
+        DeliverAs::Native => pay(recipient),
+        DeliverAs::WrappedSol { token_account } => {
+            let wrapped = find_account(remaining_accounts, token_account)
+                .ok_or(SplitError::WrappedAccountMissing)?;
+            validate_wrapped_sol_account(wrapped, recipient.key)?;
+
+            let token_program = find_account(remaining_accounts, &TOKEN_PROGRAM_ID)
+                .ok_or(SplitError::InvalidTokenProgram)?;
+            assert_token_program(token_program)?;
+
+            pay(wrapped)?;
+
+            token::sync_native(CpiContext::new(
+                token_program.clone(),
+                SyncNative {
+                    account: wrapped.clone(),
+                },
+            ))?;
+
+            msg!("Delivered {} lamports as wrapped SOL", share);
+
+            Ok(())
+        }
+        DeliverAs::Stake { vote_account } => deliver_as_stake(
+            split_config,
+            vote_account,
+            recipient,
+            remaining_accounts,
+            share,
+            pay,
+        ),
+    }
+}
+
+/// Fund a fresh stake account with the share, delegate it to the vote account,
+/// then hand the staker authority to the recipient. The config PDA is the staker
+/// only for the delegation; the recipient is the withdrawer throughout.
+fn deliver_as_stake<'info>(
+    split_config: &Account<'info, SplitConfig>,
+    vote_account: &Pubkey,
+    recipient: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    share: u64,
+    pay: impl FnOnce(&AccountInfo<'info>) -> Result<()>,
+) -> Result<()> {
+    // The stake account is a new keypair signing the transaction
+    let stake = remaining_accounts
+        .iter()
+        .find(|info| {
+            info.is_signer
+                && info.is_writable
+                && info.owner == &system_program::ID
+                && info.data_is_empty()
+                && info.lamports() == 0
+        })
+        .ok_or(SplitError::StakeAccountMissing)?;
+    let find = |key: &Pubkey| {
+        find_account(remaining_accounts, key).ok_or(SplitError::StakeAccountMissing)
+    };
+    let vote = find(vote_account)?;
+    let stake_program = find(&solana_stake_interface::program::ID)?;
+    let system = find(&system_program::ID)?;
+    let clock = find(&sysvar::clock::ID)?;
+    let rent = find(&sysvar::rent::ID)?;
+    let stake_history = find(&sysvar::stake_history::ID)?;
+    let stake_config = find(&solana_stake_interface::config::ID)?;
+
+    assert_system_program(system)?;
+    require!(stake_program.executable, SplitError::StakeAccountMissing);
+
+    let space = StakeStateV2::size_of();
+    require!(
+        share >= Rent::get()?.minimum_balance(space),
+        SplitError::StakeShareTooSmall
+    );
+
+    pay(stake)?;
+
+    system_program::allocate(
+        CpiContext::new(
+            system.clone(),
+            system_program::Allocate {
+                account_to_allocate: stake.clone(),
+            },
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new(
+            system.clone(),
+            system_program::Assign {
+                account_to_assign: stake.clone(),
+            },
+        ),
+        stake_program.key,
+    )?;
+
+    let config_key = split_config.key();
+    let nonce = split_config.nonce.to_le_bytes();
+    let seeds: &[&[u8]] = &[
+        b"split_config",
+        split_config.sender.as_ref(),
+        &nonce,
+        split_config.terms_seed(),
+        &[split_config.bump],
+    ];
+    let config_info = split_config.to_account_info();
+
+    invoke(
+        &stake_instruction::initialize(
+            stake.key,
+            &Authorized {
+                staker: config_key,
+                withdrawer: recipient.key(),
+            },
+            &Lockup::default(),
+        ),
+        &[stake.clone(), rent.clone()],
+    )?;
+    invoke_signed(
+        &stake_instruction::delegate_stake(stake.key, &config_key, vote.key),
+        &[
+            stake.clone(),
+            vote.clone(),
+            clock.clone(),
+            stake_history.clone(),
+            stake_config.clone(),
+            config_info.clone(),
+        ],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &stake_instruction::authorize(
+            stake.key,
+            &config_key,
+            recipient.key,
+            StakeAuthorize::Staker,
+            None,
+        ),
+        &[stake.clone(), clock.clone(), config_info],
+        &[seeds],
+    )?;
+
+    msg!("Delivered {} lamports as stake to {}", share, vote_account);
+
+    Ok(())
+}
+
+/// Rent paid for every account an initialize variant creates, given as
+/// (address, space, refundable on close) entries
+fn initialization_rent_costs(created: &[(Pubkey, usize, bool)]) -> Result<(Vec<RentCost>, u64)> {
+    let rent = Rent::get()?;
+    let mut total_rent: u64 = 0;
+
+    let rent_costs = created
+        .iter()
+        .map(|&(account, space, refundable)| {
+            let lamports = rent.minimum_balance(space);
+            total_rent = total_rent
+                .checked_add(lamports)
+                .ok_or(SplitError::MathOverflow)?;
+            Ok(RentCost {
+                account,
+                lamports,
+                refundable,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((rent_costs, total_rent))
+}
+
+/// Lamports a config holds beyond rent, the prefunded deposit and claimable
+/// shares, which only direct transfers to its address put there
+fn unaccounted_lamports(split_config: &Account<SplitConfig>) -> Result<u64> {
+    let info = split_config.to_account_info();
+    let held = Rent::get()?
+        .minimum_balance(info.data_len())
+        .checked_add(split_config.deposited_amount)
+        .and_then(|held| held.checked_add(split_config.recipient1_claimable))
+        .and_then(|held| held.checked_add(split_config.recipient2_claimable))
+        .ok_or(SplitError::MathOverflow)?;
+    Ok(info.lamports().saturating_sub(held))
+}
+
+/// Move two shares out of the config's deposited balance to the recipients. The
+/// config PDA is program-owned, so the lamports move directly. In claim mode the
+/// lamports stay put and the shares are credited for the recipients to claim.
+fn pay_from_deposit<'info>(
+    split_config: &mut Account<'info, SplitConfig>,
+    recipient1: &AccountInfo<'info>,
+    recipient2: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount1: u64,
+    amount2: u64,
+) -> Result<()> {
+    let amount = amount1.checked_add(amount2).ok_or(SplitError::MathOverflow)?;
+    split_config.deposited_amount = split_config
+        .deposited_amount
+        .checked_sub(amount)
+        .ok_or(SplitError::InsufficientBalance)?;
+
+    if split_config.claim_mode {
+        let config_key = split_config.key();
+        return split_config.credit_claims(&config_key, amount1, amount2);
+    }
+
+    let mut shares = [amount1, amount2];
+    for (slot, share) in (1u8..).zip(shares.iter_mut()) {
+        *share = withhold_share(split_config, remaining_accounts, slot, *share, |config, to, lamports| {
+            config.sub_lamports(lamports)?;
+            to.add_lamports(lamports)?;
+            Ok(())
+        })?;
+    }
+    let [amount1, amount2] = shares;
+
+    // Each share leaves the config as it is paid, keeping balances even across
+    // any CPI a delivery makes
+    for (delivery, recipient, share) in [
+        (&split_config.recipient1_delivery, recipient1, amount1),
+        (&split_config.recipient2_delivery, recipient2, amount2),
+    ] {
+        // A recipient at their cap gets nothing more
+        if share == 0 {
+            continue;
+        }
+        deliver_share(split_config, delivery, recipient, remaining_accounts, share, |to| {
+            split_config.sub_lamports(share)?;
+            to.add_lamports(share)?;
+            Ok(())
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Pay an escrow's whole deposit out to the recipients and mark the config
+/// executed, returning the amount and each share
+fn release_escrow<'info>(
+    split_config: &mut Account<'info, SplitConfig>,
+    recipient1: &AccountInfo<'info>,
+    recipient2: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    clock: &Clock,
+) -> Result<(u64, u64, u64)> {
+    let amount = split_config.deposited_amount;
+    require!(amount >= MINIMUM_SPLIT_AMOUNT, SplitError::AmountTooSmall);
+
+    let recipient1_bps = split_config.recipient1_bps_for(amount, clock.unix_timestamp);
+    let (amount1, amount2) = compute_shares(amount, recipient1_bps)?;
+
+    pay_from_deposit(
+        split_config,
+        recipient1,
+        recipient2,
+        remaining_accounts,
+        amount1,
+        amount2,
+    )?;
+    split_config.record_received(amount1, amount2)?;
+    split_config.record_distribution(amount)?;
+    split_config.mark_executed(clock.unix_timestamp);
+
+    emit_split_executed!(split_config, SplitExecuted {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sender: split_config.sender,
+        recipient1: event_recipient(split_config, &split_config.recipient1),
+        recipient2: event_recipient(split_config, &split_config.recipient2),
+        amount1,
+        amount2,
+        total_amount: amount,
+        topic: split_config.topic,
+        agreement_hash: split_config.agreement_hash,
+        route_id: [0u8; 16],
+        valid_until_slot: 0,
+        slot: clock.slot,
+        external_ref: split_config.external_ref,
+        client: None,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok((amount, amount1, amount2))
+}
+
+/// Accounts and execution context shared by every level of a cascade
+#[derive(Clone, Copy)]
+struct Cascade<'a, 'info> {
+    sender: &'a Signer<'info>,
+    system_program: &'a Program<'info, System>,
+    program_config: &'a AccountInfo<'info>,
+    remaining_accounts: &'a [AccountInfo<'info>],
+    clock: &'a Clock,
+    memo_given: bool,
+}
+
+/// Execute the linked child config a cascading recipient slot points to with
+/// that slot's share, paid straight from the sender to the child's recipients.
+/// The child must belong to the same sender and pay natively; it is marked
+/// executed like a direct execution and passes the same gates: review period,
+/// memo requirement, recipient data length and the global lifetime. Epoch
+/// volume and the soft pause are left to the parent, which already counted the
+/// whole execution. Nested cascades are followed up to `MAX_CASCADE_DEPTH`
+/// levels, and revisiting a config is rejected as a cycle.
+fn cascade_share<'info>(
+    ctx: &Cascade<'_, 'info>,
+    child_info: &AccountInfo<'info>,
+    amount: u64,
+    visited: &mut Vec<Pubkey>,
+) -> Result<()> {
+    let Cascade { sender, system_program, program_config, remaining_accounts, clock, memo_given } =
+        *ctx;
+
+    require!(visited.len() <= MAX_CASCADE_DEPTH, SplitError::CascadeTooDeep);
+    require!(!visited.contains(child_info.key), SplitError::CascadeCycle);
+    require_keys_eq!(*child_info.owner, crate::ID, SplitError::InvalidCascadeTarget);
+    require!(child_info.is_writable, SplitError::InvalidCascadeTarget);
+
+    let mut child = SplitConfig::try_deserialize(&mut &child_info.try_borrow_data()?[..])?;
+    child.verify()?;
+
+    // Another sender's config can't be executed on their behalf
+    require_keys_eq!(child.sender, sender.key(), SplitError::InvalidCascadeTarget);
+    require!(
+        !child.is_terminal()
+            && !child.escrow
+            && !child.claim_mode
+            && child.deposited_amount == 0
+            && !child.defer_reclaim
+            && !child.update_recipient_ledger
+            && child.withholding.is_none()
+            && child.approvals_required == 0
+            && child.recipient1_delivery == DeliverAs::Native
+            && child.recipient2_delivery == DeliverAs::Native,
+        SplitError::InvalidCascadeTarget
+    );
+
+    child.require_two_recipients()?;
+
+    // The parent's memo covers the whole execution, cascades included
+    require!(
+        memo_given || !child.require_execution_memo,
+        SplitError::ExecutionMemoRequired
+    );
+    require!(
+        clock.unix_timestamp >= child.review_ends_at(),
+        SplitError::ReviewPeriodActive
+    );
+
+    let terms = child.plan_execution(amount, clock.unix_timestamp)?;
+    require!(terms.blocked_until == 0, SplitError::ExecutionInBlackout);
+
+    // Capped overflow never leaves the sender
+    let delivered = amount - terms.overflow;
+    check_config_lifetime(program_config, &child, clock.unix_timestamp)?;
+    child.record_received(terms.amount1, terms.amount2)?;
+    child.record_distribution(delivered)?;
+    child.mark_executed(clock.unix_timestamp);
+
+    visited.push(child_info.key());
+    let depth = visited.len() as u8 - 1;
+
+    // Recorded before the child is written back, logged once its shares are paid
+    let cascaded = anchor_lang::Event::data(&SplitCascaded {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sender: sender.key(),
+        child: child_info.key(),
+        depth,
+        amount,
+        amount1: terms.amount1,
+        amount2: terms.amount2,
+        timestamp: clock.unix_timestamp,
+    });
+    child.record_history(&cascaded);
+    child.try_serialize(&mut &mut child_info.try_borrow_mut_data()?[..])?;
+
+    for (recipient, share, cascade) in [
+        (child.recipient1, terms.amount1, child.cascade_target1.is_some()),
+        (child.recipient2, terms.amount2, false),
+    ] {
+        if share == 0 {
+            continue;
+        }
+        let info = find_account(remaining_accounts, &recipient)
+            .ok_or(SplitError::CascadeAccountMissing)?;
+        require!(
+            info.data_len() as u64 >= child.min_recipient_data_len,
+            SplitError::RecipientTooSmall
+        );
+        if cascade {
+            cascade_share(ctx, info, share, visited)?;
+            continue;
+        }
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: sender.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            share,
+        )?;
+    }
+
+    anchor_lang::solana_program::log::sol_log_data(&[&cascaded]);
+
+    Ok(())
+}
+
+/// Append an entry to a claim queue. A full queue first merges its two oldest
+/// entries, keeping the older date and flagging the result; returns whether
+/// that happened.
+fn enqueue_claim(queue: &mut Vec<ClaimEntry>, entry: ClaimEntry) -> Result<bool> {
+    let merged_oldest = queue.len() >= CLAIM_QUEUE_CAPACITY;
+    if merged_oldest {
+        let second = queue.remove(1);
+        let oldest = &mut queue[0];
+        oldest.amount = oldest
+            .amount
+            .checked_add(second.amount)
+            .ok_or(SplitError::MathOverflow)?;
+        oldest.merged = true;
+    }
+    queue.push(entry);
+    Ok(merged_oldest)
+}
+
+/// Salted hash standing in for `recipient` in the events of a config with
+/// `private_recipients`: sha256(recipient || config)
+pub fn private_recipient_key(config: &Pubkey, recipient: &Pubkey) -> Pubkey {
+    Pubkey::new_from_array(hashv(&[recipient.as_ref(), config.as_ref()]).to_bytes())
+}
+
+/// `recipient` as it appears in `split_config`'s events
+fn event_recipient(split_config: &Account<SplitConfig>, recipient: &Pubkey) -> Pubkey {
+    split_config.mask_recipient(&split_config.key(), recipient)
+}
+
+/// Freeze recipient `slot`'s claims, recording who asked for it
+fn freeze_claims(split_config: &mut Account<SplitConfig>, slot: u8, frozen_by: Pubkey) -> Result<()> {
+    split_config.verify()?;
+
+    require!(split_config.claim_mode, SplitError::NotClaimMode);
+
+    let (status, _) = split_config.claim_status_mut(slot)?;
+    require!(*status == ClaimStatus::Open, SplitError::ClaimsFrozen);
+    *status = ClaimStatus::Frozen;
+
+    let (recipient, claimable) = if slot == 1 {
+        (split_config.recipient1, split_config.recipient1_claimable)
+    } else {
+        (split_config.recipient2, split_config.recipient2_claimable)
+    };
+    // A recipient freezing their own claims is masked like the slot's key
+    let frozen_by = if frozen_by == split_config.sender {
+        frozen_by
+    } else {
+        event_recipient(split_config, &frozen_by)
+    };
+
+    emit_recorded!(split_config, ClaimsFrozen {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sender: split_config.sender,
+        nonce: split_config.nonce,
+        slot,
+        recipient: event_recipient(split_config, &recipient),
+        frozen_by,
+        claimable,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Claims of slot {} frozen by {}", slot, frozen_by);
+
+    Ok(())
+}
+
+/// Pay out up to `max_entries` of the signing recipient's queued claims in order
+fn process_claim_queue<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimSplit<'info>>,
+    max_entries: usize,
+) -> Result<()> {
+    let split_config = &mut ctx.accounts.split_config;
+    let recipient = ctx.accounts.recipient.to_account_info();
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    split_config.verify()?;
+
+    require!(split_config.claim_mode, SplitError::NotClaimMode);
+    split_config.require_claims_open(&recipient.key())?;
+
+    let queue = split_config.claim_queue_mut(&recipient.key())?;
+    require!(!queue.is_empty(), SplitError::NothingToClaim);
+    let entries: Vec<ClaimEntry> = queue.drain(..max_entries.min(queue.len())).collect();
+
+    let mut claimed: u64 = 0;
+    for entry in &entries {
+        claimed = claimed
+            .checked_add(entry.amount)
+            .ok_or(SplitError::MathOverflow)?;
+
+        emit_recorded!(split_config, ClaimEntryPaid {
+            schema_version: EVENT_SCHEMA_VERSION,
+            recipient: event_recipient(split_config, &recipient.key()),
+            nonce: split_config.nonce,
+            amount: entry.amount,
+            execution_count: entry.execution_count,
+            enqueued_at: entry.timestamp,
+            merged: entry.merged,
+            timestamp,
+        });
+    }
+
+    let claimable = split_config.claimable_mut(&recipient.key())?;
+    *claimable = claimable
+        .checked_sub(claimed)
+        .ok_or(SplitError::MathOverflow)?;
+    record_ledger_claim(split_config, &mut ctx.accounts.recipient_ledger, claimed)?;
+
+    // The config PDA is program-owned, so claimed lamports move directly
+    split_config.sub_lamports(claimed)?;
+    let delivery = if recipient.key() == split_config.recipient1 {
+        &split_config.recipient1_delivery
+    } else {
+        &split_config.recipient2_delivery
+    };
+    deliver_share(split_config, delivery, &recipient, ctx.remaining_accounts, claimed, |to| {
+        to.add_lamports(claimed)?;
+        Ok(())
+    })?;
+
+    record_outbox(
+        &mut ctx.accounts.outbox,
+        split_config.key(),
+        LifecycleEvent::Claimed,
+        claimed,
+    )?;
+
+    msg!("Claimed {} queued entries for {} lamports", entries.len(), claimed);
+
+    Ok(())
+}
+
+/// Record executed shares on both recipients' ledgers when the config opted in
+fn update_recipient_ledgers(
+    split_config: &SplitConfig,
+    ledgers: [&mut Option<Box<Account<RecipientLedger>>>; 2],
+    amount1: u64,
+    amount2: u64,
+) -> Result<()> {
+    if !split_config.update_recipient_ledger {
+        return Ok(());
+    }
+
+    for ((ledger, recipient), share) in ledgers
+        .into_iter()
+        .zip([split_config.recipient1, split_config.recipient2])
+        .zip([amount1, amount2])
+    {
+        let ledger = ledger.as_mut().ok_or(SplitError::RecipientLedgerRequired)?;
+        ledger.record_share(recipient, share, split_config.claim_mode)?;
+    }
+
+    Ok(())
+}
+
+/// Move a claimed amount from pending to paid on an opted-in config's ledger
+fn record_ledger_claim(
+    split_config: &SplitConfig,
+    ledger: &mut Option<Box<Account<RecipientLedger>>>,
+    claimed: u64,
+) -> Result<()> {
+    if !split_config.update_recipient_ledger {
+        return Ok(());
+    }
+
+    ledger
+        .as_mut()
+        .ok_or(SplitError::RecipientLedgerRequired)?
+        .record_claim(claimed)
+}
+
+/// Compute `amount * numerator / denominator` in 128 bits. Every proportional
+/// share goes through here, so a zero denominator or a result that doesn't fit
+/// back in a u64 fails with `MathOverflow` rather than truncating.
+fn safe_mul_div(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    let result = (amount as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(SplitError::MathOverflow)?
+        .checked_div(denominator as u128)
+        .ok_or(SplitError::MathOverflow)?;
+
+    u64::try_from(result).map_err(|_| error!(SplitError::MathOverflow))
+}
+
+/// Split an amount by recipient1's share in basis points, giving recipient2 the remainder
+fn compute_shares(amount: u64, recipient1_bps: u16) -> Result<(u64, u64)> {
+    let amount1 = safe_mul_div(amount, recipient1_bps as u64, BPS_DENOMINATOR)?;
+
+    // Calculate amount2 as remainder to avoid rounding errors
+    let amount2 = amount
+        .checked_sub(amount1)
+        .ok_or(SplitError::MathOverflow)?;
+
+    Ok((amount1, amount2))
+}
+
+/// Caller annotations recorded with an execution
+#[derive(Default)]
+struct ExecutionNote {
+    memo: Option<String>,
+    client_id: Option<[u8; 8]>,
+}
+
+/// Shared execution path for direct and routed executions
+fn process_execute_split<'info>(
+    accounts: &mut ExecuteSplit<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    history_bump: Option<u8>,
+    amount: u64,
+    route_id: [u8; 16],
+    valid_until_slot: u64,
+    note: ExecutionNote,
+) -> Result<()> {
+    let split_config = &mut accounts.split_config;
+    let sender = &accounts.sender;
+    let recipient1 = &accounts.recipient1;
+    let recipient2 = &accounts.recipient2;
+
+    split_config.verify()?;
+
+    // Ensure split hasn't been executed yet
+    require!(!split_config.is_terminal(), SplitError::AlreadyExecuted);
+
+    // Escrow configs only release through recipient confirmation
+    require!(!split_config.escrow, SplitError::EscrowConfig);
+
+    // A prefunded balance must be spent or refunded before the final execution
+    require!(split_config.deposited_amount == 0, SplitError::EscrowNotEmpty);
+
+    // Multi-recipient configs execute through execute_split_multi
+    split_config.require_two_recipients()?;
+
+    // Validate sender matches the original configuration
+    require!(
+        split_config.sender == sender.key(),
+        SplitError::UnauthorizedSender
+    );
+
+    // Validate recipients match configuration
+    require!(
+        split_config.recipient1 == recipient1.key(),
+        SplitError::InvalidRecipient
+    );
+    require!(
+        split_config.recipient2 == recipient2.key(),
+        SplitError::InvalidRecipient
+    );
+
+    split_config.check_recipient_data_len(recipient1, recipient2)?;
+
+    // An empty memo counts as none
+    let memo = note.memo.filter(|memo| !memo.is_empty());
+    require!(
+        memo.is_some() || !split_config.require_execution_memo,
+        SplitError::ExecutionMemoRequired
+    );
+    require!(
+        memo.as_ref().is_none_or(|memo| memo.len() <= MAX_EXECUTION_MEMO_LEN),
+        SplitError::ExecutionMemoTooLong
+    );
+
+    let clock = Clock::get()?;
+    // Recipients get the full review period before any execution
+    require!(
+        clock.unix_timestamp >= split_config.review_ends_at(),
+        SplitError::ReviewPeriodActive
+    );
+
+    let terms = split_config.plan_execution(amount, clock.unix_timestamp)?;
+    let (amount1, amount2) = (terms.amount1, terms.amount2);
+
+    require!(terms.blocked_until == 0, SplitError::ExecutionInBlackout);
+    split_config.consume_approvals()?;
+
+    // Capped overflow never leaves the sender
+    let delivered = amount - terms.overflow;
+
+    // Program-enforced execution window, independent of blockhash expiry
+    require!(
+        valid_until_slot == 0 || clock.slot <= valid_until_slot,
+        SplitError::ExecutionWindowExpiredSlot
+    );
+
+    // Verify sender has sufficient balance
+    let sender_balance = sender.get_lamports();
+    require!(
+        sender_balance >= delivered,
+        SplitError::InsufficientBalance
+    );
+
+    // Verify sender keeps its configured buffer after the transfers
+    require!(
+        sender_balance - delivered >= split_config.sender_min_balance,
+        SplitError::SenderBalanceBelowMinimum
+    );
+
+    apply_program_limits(&accounts.program_config, split_config, delivered)?;
+
+    // Count the execution first so queued claims carry its number
+    split_config.record_received(amount1, amount2)?;
+    split_config.record_distribution(delivered)?;
+    update_recipient_ledgers(
+        split_config,
+        [&mut accounts.recipient1_ledger, &mut accounts.recipient2_ledger],
+        amount1,
+        amount2,
+    )?;
+
+    if terms.claim_mode {
+        // Shares stay in the config PDA until each recipient claims them
+        system_program::transfer(
+            CpiContext::new(
+                accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: sender.to_account_info(),
+                    to: split_config.to_account_info(),
+                },
+            ),
+            delivered,
+        )?;
+        let config_key = split_config.key();
+        split_config.credit_claims(&config_key, amount1, amount2)?;
+    } else {
+        // Withholding comes out of the slot's gross share before delivery
+        let mut shares = [amount1, amount2];
+        for (slot, share) in (1u8..).zip(shares.iter_mut()) {
+            *share = withhold_share(split_config, remaining_accounts, slot, *share, |_, to, lamports| {
+                system_program::transfer(
+                    CpiContext::new(
+                        accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: sender.to_account_info(),
+                            to: to.clone(),
+                        },
+                    ),
+                    lamports,
+                )
+            })?;
+        }
+        let [net1, net2] = shares;
+
+        let cascades = split_config.cascade_target1.is_some();
+        for (delivery, recipient, share, cascade) in [
+            (&split_config.recipient1_delivery, recipient1, net1, cascades),
+            (&split_config.recipient2_delivery, recipient2, net2, false),
+        ] {
+            // A recipient at their cap gets nothing more
+            if share == 0 {
+                continue;
+            }
+            if cascade {
+                let mut visited = vec![split_config.key()];
+                let cascade = Cascade {
+                    sender,
+                    system_program: &accounts.system_program,
+                    program_config: &accounts.program_config,
+                    remaining_accounts,
+                    clock: &clock,
+                    memo_given: memo.is_some(),
+                };
+                cascade_share(&cascade, recipient, share, &mut visited)?;
+                continue;
+            }
+            deliver_share(split_config, delivery, recipient, remaining_accounts, share, |to| {
+                system_program::transfer(
+                    CpiContext::new(
+                        accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: sender.to_account_info(),
+                            to: to.clone(),
+                        },
+                    ),
+                    share,
+                )
+            })?;
+        }
+    }
+
+    // Mark as executed to prevent replay
+    split_config.mark_executed(clock.unix_timestamp);
+
+    // Rent comes back later through sweep_reclaim instead of close_split
+    if split_config.defer_reclaim {
+        let queue = accounts
+            .reclaim_queue
+            .as_mut()
+            .ok_or(SplitError::ReclaimQueueRequired)?;
+        require!(
+            queue.configs.len() < RECLAIM_QUEUE_CAPACITY,
+            SplitError::ReclaimQueueFull
+        );
+        queue.configs.push(split_config.key());
+    }
+
+    emit_split_executed!(split_config, SplitExecuted {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sender: split_config.sender,
+        recipient1: event_recipient(split_config, &split_config.recipient1),
+        recipient2: event_recipient(split_config, &split_config.recipient2),
+        amount1,
+        amount2,
+        total_amount: amount,
+        topic: split_config.topic,
+        agreement_hash: split_config.agreement_hash,
+        route_id,
+        valid_until_slot,
+        slot: clock.slot,
+        external_ref: split_config.external_ref,
+        client: attribute_client(
+            note.client_id,
+            accounts.client_registry.as_deref().map(|registry| &**registry),
+        ),
+        timestamp: clock.unix_timestamp,
+    });
+
+    if let Some(memo) = memo {
+        emit_recorded!(split_config, ExecutionMemo {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sender: split_config.sender,
+            nonce: split_config.nonce,
+            execution_count: split_config.execution_count,
+            memo,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    record_outbox(
+        &mut accounts.outbox,
+        split_config.key(),
+        LifecycleEvent::Executed,
+        amount,
+    )?;
+
+    if let Some(history) = accounts.history.as_mut() {
+        history.record_execution(split_config.key(), history_bump, amount, clock.unix_timestamp)?;
+    }
+
+    msg!(
+        "Split executed: {} lamports to recipient1, {} lamports to recipient2", 
+        amount1, 
+        amount2
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(recipient1_percentage: u8, recipient2_percentage: u8, nonce: u64)]
+pub struct InitializeSplit<'info> {
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + SplitConfig::INIT_SPACE,
+        seeds = [
+            b"split_config",
+            sender.key().as_ref(),
+            &split_config_seed_nonce(&program_config, &split_config.key(), sender.key, nonce, &[])?[..],
+        ],
+        bump
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    /// CHECK: Validated in instruction logic
+    pub recipient1: AccountInfo<'info>,
+    
+    /// CHECK: Validated in instruction logic
+    pub recipient2: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, read by creation_fee
+    #[account(seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+    
+    /// CHECK: Fixed address; must be the initialized treasury when a fee is due
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", sender.key().as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+    
+    #[account(
+        seeds = [b"sender_settings", sender.key().as_ref()],
+        bump = sender_settings.bump,
+    )]
+    pub sender_settings: Option<Account<'info, SenderSettings>>,
+    
+    #[account(
+        seeds = [b"fee_exemption", sender.key().as_ref()],
+        bump = fee_exemption.bump,
+    )]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    
+    #[account(
+        seeds = [b"client_registry", client_registry.client_id.as_ref()],
+        bump = client_registry.bump,
+    )]
+    pub client_registry: Option<Account<'info, ClientRegistry>>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    recipient1_percentage: u8,
+    recipient2_percentage: u8,
+    nonce: u64,
+    options: SplitOptions,
+)]
+pub struct InitializeSplitCommitted<'info> {
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + SplitConfig::INIT_SPACE,
+        seeds = [
+            b"split_config",
+            sender.key().as_ref(),
+            &split_config_seed_nonce(
+                &program_config,
+                &split_config.key(),
+                sender.key,
+                nonce,
+                &terms_hash(
+                    recipient1.key,
+                    recipient2.key,
+                    recipient1_percentage,
+                    recipient2_percentage,
+                    &options,
+                ),
+            )?[..],
+            &terms_hash(
+                recipient1.key,
+                recipient2.key,
+                recipient1_percentage,
+                recipient2_percentage,
+                &options,
+            ),
+        ],
+        bump
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    /// CHECK: Validated in instruction logic
+    pub recipient1: AccountInfo<'info>,
+    
+    /// CHECK: Validated in instruction logic
+    pub recipient2: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, read by creation_fee
+    #[account(seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+    
+    /// CHECK: Fixed address; must be the initialized treasury when a fee is due
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", sender.key().as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+    
+    #[account(
+        seeds = [b"fee_exemption", sender.key().as_ref()],
+        bump = fee_exemption.bump,
+    )]
+    pub fee_exemption: Option<Account<'info, FeeExemption>>,
+    
+    #[account(
+        seeds = [b"client_registry", client_registry.client_id.as_ref()],
+        bump = client_registry.bump,
+    )]
+    pub client_registry: Option<Account<'info, ClientRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSenderSettings<'info> {
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + SenderSettings::INIT_SPACE,
+        seeds = [b"sender_settings", sender.key().as_ref()],
+        bump
+    )]
+    pub sender_settings: Account<'info, SenderSettings>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSenderSettings<'info> {
+    #[account(
+        mut,
+        seeds = [b"sender_settings", sender.key().as_ref()],
+        bump = sender_settings.bump,
+    )]
+    pub sender_settings: Account<'info, SenderSettings>,
+    
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSenderSettings<'info> {
+    #[account(
+        mut,
+        close = sender,
+        seeds = [b"sender_settings", sender.key().as_ref()],
+        bump = sender_settings.bump,
+    )]
+    pub sender_settings: Account<'info, SenderSettings>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSplit<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    /// CHECK: Validated against split_config
+    #[account(mut)]
+    pub recipient1: AccountInfo<'info>,
+    
+    /// CHECK: Validated against split_config
+    #[account(mut)]
+    pub recipient2: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, checked in apply_program_limits
+    #[account(mut, seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", sender.key().as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+    
+    // Created on the first execution that passes it, funded by the signing sender
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + SplitHistory::INIT_SPACE,
+        seeds = [b"history", split_config.key().as_ref()],
+        bump
+    )]
+    pub history: Option<Box<Account<'info, SplitHistory>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"reclaim_queue", sender.key().as_ref()],
+        bump = reclaim_queue.bump,
+    )]
+    pub reclaim_queue: Option<Box<Account<'info, ReclaimQueue>>>,
+    
+    // Created on the first execution that passes them, funded by the sender
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + RecipientLedger::INIT_SPACE,
+        seeds = [b"recipient_ledger", split_config.recipient1.as_ref()],
+        bump
+    )]
+    pub recipient1_ledger: Option<Box<Account<'info, RecipientLedger>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + RecipientLedger::INIT_SPACE,
+        seeds = [b"recipient_ledger", split_config.recipient2.as_ref()],
+        bump
+    )]
+    pub recipient2_ledger: Option<Box<Account<'info, RecipientLedger>>>,
+    
+    #[account(
+        seeds = [b"client_registry", client_registry.client_id.as_ref()],
+        bump = client_registry.bump,
+    )]
+    pub client_registry: Option<Box<Account<'info, ClientRegistry>>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSplitMulti<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Box<Account<'info, SplitConfig>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, checked in apply_program_limits
+    #[account(mut, seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+    
+    // Required for multi-recipient configs
+    #[account(
+        seeds = [b"recipient_set", split_config.key().as_ref()],
+        bump
+    )]
+    pub recipient_set: Option<Box<Account<'info, RecipientSet>>>,
+}
+
+#[derive(Accounts)]
+pub struct StageSplit<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Box<Account<'info, SplitConfig>>,
+    
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + StagedSplit::INIT_SPACE,
+        seeds = [b"staged_split", split_config.key().as_ref()],
+        bump
+    )]
+    pub staged_split: Box<Account<'info, StagedSplit>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, checked in apply_program_limits
+    #[account(mut, seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+    
+    // Required for multi-recipient configs
+    #[account(
+        seeds = [b"recipient_set", split_config.key().as_ref()],
+        bump
+    )]
+    pub recipient_set: Option<Box<Account<'info, RecipientSet>>>,
+}
+
+#[derive(Accounts)]
+pub struct CommitSplitPage<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Box<Account<'info, SplitConfig>>,
+    
+    // Absent until the config is staged
+    #[account(
+        mut,
+        seeds = [b"staged_split", split_config.key().as_ref()],
+        bump = staged_split.bump,
+    )]
+    pub staged_split: Option<Box<Account<'info, StagedSplit>>>,
+    
+    /// CHECK: Receives the staged account's rent once complete
+    #[account(mut, address = split_config.sender @ SplitError::UnauthorizedSender)]
+    pub sender: UncheckedAccount<'info>,
+    
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSplitsBatch<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, checked in apply_program_limits
+    #[account(mut, seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BatchUpgradeToBps<'info> {
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitRecipientSet<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Box<Account<'info, SplitConfig>>,
+    
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + RecipientSet::INIT_SPACE,
+        seeds = [b"recipient_set", split_config.key().as_ref()],
+        bump
+    )]
+    pub recipient_set: Box<Account<'info, RecipientSet>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(recipient: Pubkey)]
+pub struct InitRecipientLedger<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RecipientLedger::INIT_SPACE,
+        seeds = [b"recipient_ledger", recipient.as_ref()],
+        bump
+    )]
+    pub recipient_ledger: Box<Account<'info, RecipientLedger>>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRecipientPreference<'info> {
+    #[account(
+        init_if_needed,
+        payer = recipient,
+        space = 8 + RecipientPreference::INIT_SPACE,
+        seeds = [b"pref", recipient.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub preference: Box<Account<'info, RecipientPreference>>,
+    
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        token::mint = mint,
+        token::authority = recipient,
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRecipientPreference<'info> {
+    #[account(
+        mut,
+        close = recipient,
+        seeds = [b"pref", recipient.key().as_ref(), preference.mint.as_ref()],
+        bump = preference.bump,
+    )]
+    pub preference: Box<Account<'info, RecipientPreference>>,
+    
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRecipientLedger<'info> {
+    #[account(
+        mut,
+        close = recipient,
+        seeds = [b"recipient_ledger", recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_ledger: Box<Account<'info, RecipientLedger>>,
+    
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitReclaimQueue<'info> {
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + ReclaimQueue::INIT_SPACE,
+        seeds = [b"reclaim_queue", sender.key().as_ref()],
+        bump
+    )]
+    pub reclaim_queue: Box<Account<'info, ReclaimQueue>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SweepReclaim<'info> {
+    #[account(
+        mut,
+        seeds = [b"reclaim_queue", sender.key().as_ref()],
+        bump = reclaim_queue.bump,
+    )]
+    pub reclaim_queue: Box<Account<'info, ReclaimQueue>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TeardownSender<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSplit<'info> {
+    #[account(
+        mut,
+        close = sender,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+        constraint = !split_config.is_terminal() @ SplitError::AlreadyExecuted
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", sender.key().as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+    
+    /// CHECK: Validated against the config's rent split
+    #[account(mut)]
+    pub rent_split_destination: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Option<Box<Account<'info, Treasury>>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSplit<'info> {
+    #[account(
+        mut,
+        close = sender,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+        constraint = split_config.is_terminal() @ SplitError::NotExecuted
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", sender.key().as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+    
+    /// CHECK: Validated against the config's rent split
+    #[account(mut)]
+    pub rent_split_destination: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Validated against split_config when the residual goes to recipients
+    #[account(mut)]
+    pub recipient1: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Validated against split_config when the residual goes to recipients
+    #[account(mut)]
+    pub recipient2: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, read by composed_close_allowed
+    #[account(seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+    
+    /// CHECK: The instructions sysvar, read to find an earlier execution
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteAndCloseSplit<'info> {
+    pub execute: ExecuteSplit<'info>,
+    
+    /// CHECK: Validated against the config's rent split
+    #[account(mut)]
+    pub rent_split_destination: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SweepVaults<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CleanupExpiredConfig<'info> {
+    #[account(
+        mut,
+        close = sender,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    /// CHECK: Receives the config's lamports; must be the recorded sender
+    #[account(mut, address = split_config.sender @ SplitError::UnauthorizedSender)]
+    pub sender: UncheckedAccount<'info>,
+    
+    #[account(seeds = [b"program_config"], bump = program_config.bump)]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub cleaner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", split_config.sender.as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+    
+    /// CHECK: Validated against the config's rent split
+    #[account(mut)]
+    pub rent_split_destination: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SetTopic<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReconfigureSplit<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterRoute<'info> {
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + RouteTable::INIT_SPACE,
+        seeds = [b"route_table", sender.key().as_ref()],
+        bump
+    )]
+    pub route_table: Account<'info, RouteTable>,
+    
+    #[account(
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRouted<'info> {
+    #[account(
+        seeds = [b"route_table", execute.sender.key().as_ref()],
+        bump = route_table.bump,
+    )]
+    pub route_table: Account<'info, RouteTable>,
+    
+    pub execute: ExecuteSplit<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSplitAfterSwap<'info> {
+    pub execute: ExecuteSplit<'info>,
+    
+    /// CHECK: The instructions sysvar, read to find the preceding swap
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSplitSpl<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = sender,
+    )]
+    pub sender_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = recipient1_token_account.owner == split_config.recipient1 @ SplitError::InvalidRecipient,
+    )]
+    pub recipient1_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = recipient2_token_account.owner == split_config.recipient2 @ SplitError::InvalidRecipient,
+    )]
+    pub recipient2_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", sender.key().as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+    
+    #[account(
+        seeds = [b"pref", split_config.recipient1.as_ref(), mint.key().as_ref()],
+        bump = recipient1_preference.bump,
+    )]
+    pub recipient1_preference: Option<Box<Account<'info, RecipientPreference>>>,
+    
+    #[account(
+        seeds = [b"pref", split_config.recipient2.as_ref(), mint.key().as_ref()],
+        bump = recipient2_preference.bump,
+    )]
+    pub recipient2_preference: Option<Box<Account<'info, RecipientPreference>>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSplitSplSolFee<'info> {
+    pub execute: ExecuteSplitSpl<'info>,
+    
+    #[account(seeds = [b"program_config"], bump = program_config.bump)]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(feature = "auto-swap")]
+#[derive(Accounts)]
+pub struct ExecuteSplitSplAutoSwap<'info> {
+    pub execute: ExecuteSplitSpl<'info>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, read by swap_programs
+    #[account(seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSplitDelegatedToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub executor: Signer<'info>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = split_config.sender,
+    )]
+    pub sender_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = recipient1_token_account.owner == split_config.recipient1 @ SplitError::InvalidRecipient,
+    )]
+    pub recipient1_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = recipient2_token_account.owner == split_config.recipient2 @ SplitError::InvalidRecipient,
+    )]
+    pub recipient2_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    #[account(
+        seeds = [b"pref", split_config.recipient1.as_ref(), mint.key().as_ref()],
+        bump = recipient1_preference.bump,
+    )]
+    pub recipient1_preference: Option<Box<Account<'info, RecipientPreference>>>,
+    
+    #[account(
+        seeds = [b"pref", split_config.recipient2.as_ref(), mint.key().as_ref()],
+        bump = recipient2_preference.bump,
+    )]
+    pub recipient2_preference: Option<Box<Account<'info, RecipientPreference>>>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, read by check_config_lifetime
+    #[account(seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", split_config.sender.as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToSplit<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    /// Funds the deposit: the config's sender or, on escrow configs, a
+    /// depositor it allows. Keeps its name for existing clients.
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", split_config.sender.as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+}
+
+#[derive(Accounts)]
+pub struct SetDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub delegate: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestAgreement<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub recipient: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeMyClaims<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+
+    pub recipient: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveExecution<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ForwardAndSplit<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub cranker: Signer<'info>,
+    
+    /// CHECK: Validated against split_config
+    #[account(mut)]
+    pub recipient1: AccountInfo<'info>,
+    
+    /// CHECK: Validated against split_config
+    #[account(mut)]
+    pub recipient2: AccountInfo<'info>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, checked in apply_program_limits
+    #[account(mut, seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteFromDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub executor: Signer<'info>,
+    
+    /// CHECK: Validated against split_config
+    #[account(mut)]
+    pub recipient1: AccountInfo<'info>,
+    
+    /// CHECK: Validated against split_config
+    #[account(mut)]
+    pub recipient2: AccountInfo<'info>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, checked in apply_program_limits
+    #[account(mut, seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", split_config.sender.as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"executor_bond", executor.key().as_ref()],
+        bump = executor_bond.bump,
+    )]
+    pub executor_bond: Option<Box<Account<'info, ExecutorBond>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"recipient_ledger", split_config.recipient1.as_ref()],
+        bump
+    )]
+    pub recipient1_ledger: Option<Box<Account<'info, RecipientLedger>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"recipient_ledger", split_config.recipient2.as_ref()],
+        bump
+    )]
+    pub recipient2_ledger: Option<Box<Account<'info, RecipientLedger>>>,
+}
+
+#[derive(Accounts)]
+pub struct PostExecutorBond<'info> {
+    #[account(
+        init_if_needed,
+        payer = executor,
+        space = 8 + ExecutorBond::INIT_SPACE,
+        seeds = [b"executor_bond", executor.key().as_ref()],
+        bump
+    )]
+    pub executor_bond: Account<'info, ExecutorBond>,
+    
+    #[account(mut)]
+    pub executor: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawExecutorBond<'info> {
+    #[account(
+        mut,
+        close = executor,
+        seeds = [b"executor_bond", executor.key().as_ref()],
+        bump = executor_bond.bump,
+    )]
+    pub executor_bond: Account<'info, ExecutorBond>,
+    
+    #[account(mut)]
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SlashExecutorBond<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"executor_bond", executor_bond.executor.as_ref()],
+        bump = executor_bond.bump,
+    )]
+    pub executor_bond: Account<'info, ExecutorBond>,
+    
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmAndRelease<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub recipient: Signer<'info>,
+    
+    /// CHECK: Validated against split_config
+    #[account(mut)]
+    pub recipient1: AccountInfo<'info>,
+    
+    /// CHECK: Validated against split_config
+    #[account(mut)]
+    pub recipient2: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", split_config.sender.as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseOnCondition<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    /// CHECK: Validated against the config's oracle condition
+    pub oracle: UncheckedAccount<'info>,
+    
+    /// CHECK: Validated against split_config
+    #[account(mut)]
+    pub recipient1: AccountInfo<'info>,
+    
+    /// CHECK: Validated against split_config
+    #[account(mut)]
+    pub recipient2: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", split_config.sender.as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+}
+
+#[derive(Accounts)]
+pub struct RefundEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", sender.key().as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+    
+    /// CHECK: Receives lamports only; must be the config's recipient1
+    #[account(mut, address = split_config.recipient1 @ SplitError::InvalidRecipient)]
+    pub recipient1: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Receives lamports only; must be the config's recipient2
+    #[account(mut, address = split_config.recipient2 @ SplitError::InvalidRecipient)]
+    pub recipient2: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAllowedDepositors<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ComputeGrossForNet<'info> {
+    pub split_config: Account<'info, SplitConfig>,
+}
+
+#[derive(Accounts)]
+pub struct EstimateRent {}
+
+#[derive(Accounts)]
+pub struct QuoteTokenSplit<'info> {
+    pub split_config: Account<'info, SplitConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveEffectiveTerms<'info> {
+    pub split_config: Account<'info, SplitConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateTokenAccounts<'info> {
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    /// CHECK: Compared against the derived associated token account
+    pub sender_token_account: UncheckedAccount<'info>,
+    
+    /// CHECK: Compared against the derived associated token account
+    pub recipient1_token_account: UncheckedAccount<'info>,
+    
+    /// CHECK: Compared against the derived associated token account
+    pub recipient2_token_account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewDistribution<'info> {
+    pub split_config: Account<'info, SplitConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ReadHistoryDigest<'info> {
+    pub split_config: Account<'info, SplitConfig>,
+}
+
+#[derive(Accounts)]
+pub struct TimeUntilExecutable<'info> {
+    pub split_config: Account<'info, SplitConfig>,
+    
+    /// CHECK: Fixed address; may not be initialized yet, read by max_config_lifetime
+    #[account(seeds = [b"program_config"], bump)]
+    pub program_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AssertRecipientTotal<'info> {
+    pub split_config: Account<'info, SplitConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RepairBump<'info> {
+    // Derived from the canonical bump rather than the stored one, which may be wrong
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyConfig<'info> {
+    pub split_config: Account<'info, SplitConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProgramConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProgramConfig::INIT_SPACE,
+        seeds = [b"program_config"],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Solsplit>,
+    
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ SplitError::UnauthorizedAdmin
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetEpochCeiling<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxConfigLifetime<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+        close = admin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSwapPrograms<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTokenSplitFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGenesisGuard<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAllowComposedClose<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCreationFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(sender: Pubkey)]
+pub struct GrantFeeExemption<'info> {
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + FeeExemption::INIT_SPACE,
+        seeds = [b"fee_exemption", sender.as_ref()],
+        bump
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(client_id: [u8; 8])]
+pub struct RegisterClient<'info> {
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ClientRegistry::INIT_SPACE,
+        seeds = [b"client_registry", client_id.as_ref()],
+        bump
+    )]
+    pub client_registry: Account<'info, ClientRegistry>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeFeeExemption<'info> {
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"fee_exemption", fee_exemption.sender.as_ref()],
+        bump = fee_exemption.bump,
+    )]
+    pub fee_exemption: Account<'info, FeeExemption>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMinExecutorBond<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSoftPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTreasuryStakeholders<'info> {
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + Treasury::INIT_SPACE,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeTreasury<'info> {
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    /// CHECK: Validated against the treasury's stakeholder table
+    #[account(mut, address = treasury.stakeholder1 @ SplitError::InvalidRecipient)]
+    pub stakeholder1: UncheckedAccount<'info>,
+    
+    /// CHECK: Validated against the treasury's stakeholder table
+    #[account(mut, address = treasury.stakeholder2 @ SplitError::InvalidRecipient)]
+    pub stakeholder2: UncheckedAccount<'info>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMigrationWindow<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BackfillCounters<'info> {
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDonationPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ SplitError::UnauthorizedAdmin,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimSplit<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", split_config.sender.as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
     )]
     pub split_config: Account<'info, SplitConfig>,
     
     #[account(mut)]
+    pub recipient: Signer<'info>,
+    
+    #[account(seeds = [b"program_config"], bump = program_config.bump)]
+    pub program_config: Option<Account<'info, ProgramConfig>>,
+    
+    /// CHECK: Validated against the program config's donation policy
+    #[account(mut)]
+    pub donation_destination: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        seeds = [b"outbox", split_config.sender.as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Option<Box<Account<'info, Outbox>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"recipient_ledger", recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_ledger: Option<Box<Account<'info, RecipientLedger>>>,
+}
+
+#[derive(Accounts)]
+pub struct RedirectFrozenClaims<'info> {
+    #[account(
+        mut,
+        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes(), split_config.terms_seed()],
+        bump = split_config.bump,
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
     pub sender: Signer<'info>,
     
-    /// CHECK: Validated in instruction logic
-    pub recipient1: AccountInfo<'info>,
+    pub new_recipient: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitOutbox<'info> {
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + Outbox::INIT_SPACE,
+        seeds = [b"outbox", sender.key().as_ref()],
+        bump
+    )]
+    pub outbox: Box<Account<'info, Outbox>>,
     
-    /// CHECK: Validated in instruction logic
-    pub recipient2: AccountInfo<'info>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
-#[derive(Accounts)]
-pub struct ExecuteSplit<'info> {
-    #[account(
-        mut,
-        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes()],
-        bump = split_config.bump,
-    )]
-    pub split_config: Account<'info, SplitConfig>,
-    
-    #[account(mut)]
-    pub sender: Signer<'info>,
-    
-    /// CHECK: Validated against split_config
-    #[account(mut)]
-    pub recipient1: AccountInfo<'info>,
-    
-    /// CHECK: Validated against split_config
-    #[account(mut)]
-    pub recipient2: AccountInfo<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[derive(Accounts)]
+pub struct AckOutbox<'info> {
+    #[account(
+        mut,
+        seeds = [b"outbox", sender.key().as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Box<Account<'info, Outbox>>,
+    
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseOutbox<'info> {
+    #[account(
+        mut,
+        close = sender,
+        seeds = [b"outbox", sender.key().as_ref()],
+        bump = outbox.bump,
+    )]
+    pub outbox: Box<Account<'info, Outbox>>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+}
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct SplitConfig {
+    pub sender: Pubkey,
+    pub recipient1: Pubkey,
+    pub recipient2: Pubkey,
+    pub recipient1_percentage: u8,
+    pub recipient2_percentage: u8,
+    pub executed: bool,
+    pub nonce: u64,
+    pub created_at: i64,
+    pub executed_at: i64,
+    pub bump: u8,
+    pub topic: [u8; 8],
+    pub sender_min_balance: u64,
+    pub escrow: bool,
+    pub acceptance_deadline: i64,
+    pub deposited_amount: u64,
+    pub recipient1_accepted: bool,
+    pub recipient2_accepted: bool,
+    /// With no allowed depositors listed, lets anyone fund the escrow rather
+    /// than the sender only
+    pub open_deposits: bool,
+    /// Depositors besides the sender; when listed, only they may fund the
+    /// escrow. Refunds of what they deposit go to the sender.
+    #[max_len(MAX_ALLOWED_DEPOSITORS)]
+    pub allowed_depositors: Vec<Pubkey>,
+    pub recipient1_delivery: DeliverAs,
+    pub recipient2_delivery: DeliverAs,
+    pub delegate: Pubkey,
+    pub delegate_daily_limit: u64,
+    pub delegate_window_start: i64,
+    pub delegate_window_spent: u64,
+    pub rotate_remainder: bool,
+    pub remainder_rotation: u8,
+    pub claim_mode: bool,
+    pub recipient1_claimable: u64,
+    pub recipient2_claimable: u64,
+    pub agreement_hash: [u8; 32],
+    pub recipient1_attested: bool,
+    pub recipient2_attested: bool,
+    pub rent_split: Option<RentSplit>,
+    pub min_recipient_data_len: u64,
+    pub execution_count: u64,
+    pub total_distributed: u64,
+    pub backfilled: bool,
+    pub denomination: u64,
+    pub close_residual_to_recipients: bool,
+    pub oracle_condition: Option<OracleCondition>,
+    pub anyone_can_execute: bool,
+    pub executor_quiet_window: Option<QuietWindow>,
+    pub recipient1_cap: u64,
+    pub recipient2_cap: u64,
+    pub recipient1_received: u64,
+    pub recipient2_received: u64,
+    #[max_len(MAX_BLACKOUT_WINDOWS)]
+    pub blackout_windows: Vec<BlackoutWindow>,
+    pub shortfall_policy: ShortfallPolicy,
+    pub allow_partial: bool,
+    pub recipient1_shortfall: u64,
+    pub recipient2_shortfall: u64,
+    #[max_len(CLAIM_QUEUE_CAPACITY)]
+    pub recipient1_claim_queue: Vec<ClaimEntry>,
+    #[max_len(CLAIM_QUEUE_CAPACITY)]
+    pub recipient2_claim_queue: Vec<ClaimEntry>,
+    pub require_execution_memo: bool,
+    pub cascade_target1: Option<Pubkey>,
+    pub defer_reclaim: bool,
+    pub review_period_seconds: u32,
+    pub recipient1_auto_swap: Option<SwapSpec>,
+    pub recipient2_auto_swap: Option<SwapSpec>,
+    pub swap_fallback_to_original: bool,
+    pub update_recipient_ledger: bool,
+    pub cancel_cooldown_seconds: u32,
+    pub creation_fee_paid: u64,
+    pub pending_delegate: Pubkey,
+    pub pending_delegate_daily_limit: u64,
+    #[max_len(MAX_RATIO_TIERS)]
+    pub ratio_tiers: Vec<TierRatio>,
+    pub layout: u8,
+    pub track_history_digest: bool,
+    pub history_digest: [u8; 32],
+    pub forward_receipts: bool,
+    pub recipient1_bps: u16,
+    pub withholding: Option<Withholding>,
+    pub compact_events: bool,
+    /// Terms hash the address commits to, all zeroes for uncommitted configs
+    pub terms_hash: [u8; 32],
+    pub ratio_ramp: Option<RatioRamp>,
+    pub recipient1_claim_status: ClaimStatus,
+    pub recipient2_claim_status: ClaimStatus,
+    /// Key registered by recipient 1 to take over frozen claims, default if none
+    pub recipient1_recovery: Pubkey,
+    /// Key registered by recipient 2 to take over frozen claims, default if none
+    pub recipient2_recovery: Pubkey,
+    /// Account in another program this config is linked to, for off-chain
+    /// correlation only
+    pub external_ref: Option<Pubkey>,
+    /// Replace recipient keys in events with salted hashes
+    pub private_recipients: bool,
+    /// Both recipients signed at init, proving control of their keys
+    pub verified_recipients: bool,
+    /// Shares depend only on the terms and amount, never on when execution lands
+    pub deterministic_only: bool,
+    /// Keys whose approvals count toward `approvals_required`
+    #[max_len(MAX_APPROVERS)]
+    pub approvers: Vec<Pubkey>,
+    /// Approvals needed before each execution, zero for none
+    pub approvals_required: u8,
+    /// Bit i set once `approvers[i]` has approved the next execution
+    pub approvals: u8,
+    /// Client that initiated the config, if it named one
+    pub client: Option<ClientAttribution>,
+    /// Part of the escrow deposit owed to the recipients when the sender
+    /// refunds it after a recipient accepted
+    pub cancel_penalty_bps: u16,
+    /// Set once a recipient accepts the current deposit; resets to
+    /// acceptance don't clear it, only a refund does
+    pub cancel_penalty_armed: bool,
+    pub recipient1_penalty_paid: u64,
+    pub recipient2_penalty_paid: u64,
+}
+
+/// Optional settings accepted by `initialize_split_with_options`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct SplitOptions {
+    pub sender_min_balance: u64,
+    pub escrow: bool,
+    pub acceptance_deadline: i64,
+    pub open_deposits: bool,
+    pub allowed_depositors: Vec<Pubkey>,
+    pub recipient1_delivery: DeliverAs,
+    pub recipient2_delivery: DeliverAs,
+    pub rotate_remainder: bool,
+    pub claim_mode: bool,
+    pub agreement_hash: [u8; 32],
+    pub rent_split: Option<RentSplit>,
+    pub min_recipient_data_len: u64,
+    pub denomination: u64,
+    pub close_residual_to_recipients: bool,
+    pub oracle_condition: Option<OracleCondition>,
+    pub anyone_can_execute: bool,
+    pub executor_quiet_window: Option<QuietWindow>,
+    pub recipient1_cap: u64,
+    pub recipient2_cap: u64,
+    pub blackout_windows: Vec<BlackoutWindow>,
+    pub shortfall_policy: ShortfallPolicy,
+    pub allow_partial: bool,
+    pub require_execution_memo: bool,
+    pub cascade_target1: Option<Pubkey>,
+    pub defer_reclaim: bool,
+    pub review_period_seconds: u32,
+    pub recipient1_auto_swap: Option<SwapSpec>,
+    pub recipient2_auto_swap: Option<SwapSpec>,
+    pub swap_fallback_to_original: bool,
+    pub update_recipient_ledger: bool,
+    pub cancel_cooldown_seconds: u32,
+    pub ratio_tiers: Vec<TierRatio>,
+    pub track_history_digest: bool,
+    pub forward_receipts: bool,
+    pub withholding: Option<Withholding>,
+    pub compact_events: bool,
+    pub ratio_ramp: Option<RatioRamp>,
+    pub external_ref: Option<Pubkey>,
+    pub private_recipients: bool,
+    pub deterministic_only: bool,
+    pub approvers: Vec<Pubkey>,
+    pub approvals_required: u8,
+    pub client_id: Option<[u8; 8]>,
+    pub cancel_penalty_bps: u16,
+}
+
+/// Part of one recipient's gross share withheld to a tax authority wallet
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct Withholding {
+    pub destination: Pubkey,
+    /// Withheld part of the share, at most MAX_WITHHOLDING_BPS
+    pub bps: u16,
+    /// Recipient slot withheld from, 1 or 2
+    pub applies_to_slot: u8,
+}
+
+/// Swap of a recipient's token leg into another mint through a venue program
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct SwapSpec {
+    pub target_mint: Pubkey,
+    /// Slippage floor: minimum output in target mint base units per 10_000
+    /// input units
+    pub min_out_bps: u16,
+    pub venue_program: Pubkey,
+}
+
+/// Venue instruction for one auto-swapped leg of `execute_split_spl_auto_swap`.
+/// Its remaining accounts are the venue program, the recipient's target mint
+/// ATA, then `account_count` accounts passed to the venue.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwapLeg {
+    pub slot: u8,
+    pub account_count: u8,
+    pub data: Vec<u8>,
+}
+
+/// Fully resolved outcome of executing an amount, from `plan_execution`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EffectiveTerms {
+    pub amount: u64,
+    pub amount1: u64,
+    pub amount2: u64,
+    pub overflow: u64,
+    pub blocked_until: i64,
+    pub claim_mode: bool,
+    pub recipient1_delivery: DeliverAs,
+    pub recipient2_delivery: DeliverAs,
+}
+
+/// Result of `preview_distribution`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DistributionPreview {
+    pub amount: u64,
+    pub blocked_until: i64,
+    pub entries: Vec<DistributionEntry>,
+}
+
+/// One destination of a previewed execution. Wrapped SOL and stake deliveries
+/// land in the recipient's wrapped or new stake account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DistributionEntry {
+    pub kind: DistributionKind,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+/// Token account slot of a token split, reported by `validate_token_accounts`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenAccountSlot {
+    Sender,
+    Recipient1,
+    Recipient2,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DistributionKind {
+    Recipient1,
+    Recipient2,
+    /// Capped overflow, which never leaves the sender
+    CapOverflow,
+    /// Part of a recipient's share withheld to the withholding destination
+    Withholding,
+}
+
+/// Result of `time_until_executable`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ExecutionCountdown {
+    pub executable_at: i64,
+    pub seconds_until_executable: u64,
+    /// None when the config never expires
+    pub expires_at: Option<i64>,
+    pub seconds_until_expiry: Option<u64>,
+}
+
+/// Result of `quote_token_split`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TokenSplitQuote {
+    pub denominated: bool,
+    pub burned: u64,
+    pub amount1: u64,
+    pub amount2: u64,
+    pub lower_valid_amount: u64,
+    pub upper_valid_amount: u64,
+}
+
+/// Options `initialize_split_with_defaults` takes from the sender's settings
+/// when left as `None`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct SplitOverrides {
+    pub sender_min_balance: Option<u64>,
+    pub rotate_remainder: Option<bool>,
+    pub min_recipient_data_len: Option<u64>,
+    pub denomination: Option<u64>,
+    pub close_residual_to_recipients: Option<bool>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SenderSettings {
+    pub sender: Pubkey,
+    pub defaults: SenderDefaults,
+    pub bump: u8,
+}
+
+/// Default values a sender's new configs start from
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct SenderDefaults {
+    pub sender_min_balance: u64,
+    pub rotate_remainder: bool,
+    pub min_recipient_data_len: u64,
+    pub denomination: u64,
+    pub close_residual_to_recipients: bool,
+}
+
+impl SenderDefaults {
+    /// Build the options for a new config, taking each unset override from the
+    /// defaults. Also returns a bitmask of the defaulted fields, bit `i` set for
+    /// the `i`-th field of `SplitOverrides`.
+    pub fn resolve(&self, overrides: SplitOverrides) -> (SplitOptions, u16) {
+        fn pick<T>(value: Option<T>, default: T, bit: u16, defaults_applied: &mut u16) -> T {
+            value.unwrap_or_else(|| {
+                *defaults_applied |= 1 << bit;
+                default
+            })
+        }
+
+        let mut defaults_applied = 0u16;
+        let options = SplitOptions {
+            sender_min_balance: pick(
+                overrides.sender_min_balance,
+                self.sender_min_balance,
+                0,
+                &mut defaults_applied,
+            ),
+            rotate_remainder: pick(
+                overrides.rotate_remainder,
+                self.rotate_remainder,
+                1,
+                &mut defaults_applied,
+            ),
+            min_recipient_data_len: pick(
+                overrides.min_recipient_data_len,
+                self.min_recipient_data_len,
+                2,
+                &mut defaults_applied,
+            ),
+            denomination: pick(
+                overrides.denomination,
+                self.denomination,
+                3,
+                &mut defaults_applied,
+            ),
+            close_residual_to_recipients: pick(
+                overrides.close_residual_to_recipients,
+                self.close_residual_to_recipients,
+                4,
+                &mut defaults_applied,
+            ),
+            ..SplitOptions::default()
+        };
+
+        (options, defaults_applied)
+    }
+}
+
+/// Share of the reclaimed rent sent to another address when the config closes
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RentSplit {
+    pub destination: Pubkey,
+    pub percentage: u8,
+}
+
+/// Oracle account and threshold an escrow releases on. The oracle's value is
+/// read as a little-endian u64 at `value_offset` in its data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct OracleCondition {
+    pub oracle: Pubkey,
+    pub owner: Pubkey,
+    pub value_offset: u16,
+    pub condition_value: u64,
+}
+
+impl OracleCondition {
+    /// Read the oracle's current value from its account data
+    pub fn read_value(&self, data: &[u8]) -> Result<u64> {
+        let start = self.value_offset as usize;
+        let bytes = data
+            .get(start..start + 8)
+            .ok_or(SplitError::InvalidOracle)?;
+        let mut value = [0u8; 8];
+        value.copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(value))
+    }
+}
+
+/// A credited claim-mode share waiting in a recipient's queue. `merged` marks
+/// an entry that absorbed a newer one when the queue overflowed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ClaimEntry {
+    pub amount: u64,
+    pub execution_count: u64,
+    pub timestamp: i64,
+    pub merged: bool,
+}
+
+/// Period during which open executors must not execute a config. Executions
+/// inside it are not rejected but forfeit the executor's bond.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct QuietWindow {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl QuietWindow {
+    /// Whether `timestamp` falls inside the window
+    pub fn contains(&self, timestamp: i64) -> bool {
+        timestamp >= self.start && timestamp < self.end
+    }
+}
+
+/// Bracket of a ratio table: amounts of at least `min_amount`, up to the next
+/// bracket's threshold, split with this recipient 1 percentage
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct TierRatio {
+    pub min_amount: u64,
+    pub recipient1_percentage: u8,
+}
+
+/// State an execution signed ahead of time expects to find, so it fails
+/// rather than landing after circumstances changed. Each is checked only
+/// when set.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ExecutionPreconditions {
+    /// Executions the config must have recorded so far
+    pub expected_execution_count: Option<u64>,
+    /// `terms_hash` of the config's current terms
+    pub expected_terms_hash: Option<[u8; 32]>,
+    /// Latest unix time the execution may land at
+    pub max_unix_time: Option<i64>,
+}
+
+impl ExecutionPreconditions {
+    fn check(&self, config: &SplitConfig, now: i64) -> Result<()> {
+        require!(
+            self.expected_execution_count
+                .is_none_or(|count| count == config.execution_count),
+            SplitError::StaleExecutionCount
+        );
+        require!(
+            self.expected_terms_hash
+                .is_none_or(|hash| hash == config.current_terms_hash()),
+            SplitError::TermsChangedSinceSigning
+        );
+        require!(
+            self.max_unix_time.is_none_or(|max| now <= max),
+            SplitError::ExecutionTooLate
+        );
+        Ok(())
+    }
+}
+
+/// Recipient 1's percentage moving linearly from `start_recipient1_percentage`
+/// at `ramp_start` to `end_recipient1_percentage` at `ramp_end`, and held at
+/// the nearer end outside that window
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct RatioRamp {
+    pub start_recipient1_percentage: u8,
+    pub end_recipient1_percentage: u8,
+    pub ramp_start: i64,
+    pub ramp_end: i64,
+}
+
+impl RatioRamp {
+    /// Both ends must give both recipients a non-zero share, over a window
+    /// that ends after it starts
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            (1..=99).contains(&self.start_recipient1_percentage)
+                && (1..=99).contains(&self.end_recipient1_percentage)
+                && self.ramp_start < self.ramp_end,
+            SplitError::InvalidRatioRamp
+        );
+        Ok(())
+    }
+
+    /// Recipient 1's share in basis points at `now`, interpolated between the
+    /// two ends and truncated toward the start
+    pub fn recipient1_bps_at(&self, now: i64) -> u16 {
+        let start = self.start_recipient1_percentage as i128 * 100;
+        let end = self.end_recipient1_percentage as i128 * 100;
+        let elapsed = now.clamp(self.ramp_start, self.ramp_end) as i128 - self.ramp_start as i128;
+        let span = self.ramp_end as i128 - self.ramp_start as i128;
+
+        (start + (end - start) * elapsed / span) as u16
+    }
+}
+
+/// One recipient of a multi-recipient config and their share in basis points
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RecipientShare {
+    pub recipient: Pubkey,
+    pub bps: u16,
+}
+
+/// Period during which execution is forbidden. With a zero period the window
+/// is a one-off starting at the absolute `start_offset_seconds`; otherwise it
+/// recurs every `period_seconds` from that anchor. Windows are half-open, so
+/// execution is allowed again at exactly the end second.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct BlackoutWindow {
+    pub start_offset_seconds: i64,
+    pub duration_seconds: i64,
+    pub period_seconds: i64,
+}
+
+impl BlackoutWindow {
+    /// End of the occurrence containing `timestamp`, if any. Times before the
+    /// anchor are never blacked out.
+    pub fn end_if_active(&self, timestamp: i64) -> Option<i64> {
+        let elapsed = timestamp.checked_sub(self.start_offset_seconds)?;
+        if elapsed < 0 {
+            return None;
+        }
+        let phase = if self.period_seconds == 0 {
+            elapsed
+        } else {
+            elapsed.rem_euclid(self.period_seconds)
+        };
+        (phase < self.duration_seconds).then(|| timestamp - phase + self.duration_seconds)
+    }
+}
+
+/// Ratio table thresholds must strictly increase and every bracket must give
+/// both recipients a non-zero share
+fn validate_ratio_tiers(tiers: &[TierRatio]) -> Result<()> {
+    require!(tiers.len() <= MAX_RATIO_TIERS, SplitError::InvalidRatioTiers);
+    require!(
+        tiers.windows(2).all(|pair| pair[0].min_amount < pair[1].min_amount),
+        SplitError::InvalidRatioTiers
+    );
+    require!(
+        tiers
+            .iter()
+            .all(|tier| (1..=99).contains(&tier.recipient1_percentage)),
+        SplitError::InvalidRatioTiers
+    );
+
+    Ok(())
+}
+
+/// Attribute `client_id`, verified when the matching registry entry is passed
+fn attribute_client(
+    client_id: Option<[u8; 8]>,
+    registry: Option<&ClientRegistry>,
+) -> Option<ClientAttribution> {
+    client_id.map(|client_id| ClientAttribution {
+        client_id,
+        verified: registry.is_some_and(|registry| registry.client_id == client_id),
+    })
+}
+
+/// Approvers must be distinct, non-default keys, enough of them to meet the
+/// threshold
+fn validate_approvers(approvers: &[Pubkey], approvals_required: u8) -> Result<()> {
+    require!(
+        approvers.len() <= MAX_APPROVERS
+            && approvals_required as usize <= approvers.len()
+            && approvers.iter().all(|approver| *approver != Pubkey::default())
+            && approvers
+                .iter()
+                .enumerate()
+                .all(|(i, approver)| !approvers[..i].contains(approver)),
+        SplitError::InvalidApprovers
+    );
+    Ok(())
+}
+
+/// A recipient set needs 2 to `max` distinct recipients, each
+/// with a nonzero share, together totalling 10_000 basis points
+fn validate_recipient_shares(shares: &[RecipientShare], max: usize) -> Result<()> {
+    require!(
+        (2..=max).contains(&shares.len()),
+        SplitError::InvalidRecipientShares
+    );
+    require!(shares.iter().all(|share| share.bps > 0), SplitError::InvalidRecipientShares);
+    require!(
+        shares.iter().map(|share| share.bps as u32).sum::<u32>() == 10_000,
+        SplitError::InvalidRecipientShares
+    );
+    for (i, share) in shares.iter().enumerate() {
+        require!(
+            shares[..i].iter().all(|other| other.recipient != share.recipient),
+            SplitError::InvalidRecipientShares
+        );
+    }
+
+    Ok(())
+}
+
+/// Blackout windows must be non-empty, shorter than their period and together
+/// leave part of every cycle open, so execution always becomes possible again
+fn validate_blackout_windows(windows: &[BlackoutWindow]) -> Result<()> {
+    require!(
+        windows.len() <= MAX_BLACKOUT_WINDOWS,
+        SplitError::InvalidBlackoutWindow
+    );
+
+    let mut duty_bps: u128 = 0;
+    for window in windows {
+        require!(
+            window.start_offset_seconds >= 0
+                && window.duration_seconds > 0
+                && window.period_seconds >= 0,
+            SplitError::InvalidBlackoutWindow
+        );
+        window
+            .start_offset_seconds
+            .checked_add(window.duration_seconds)
+            .ok_or(SplitError::InvalidBlackoutWindow)?;
+
+        if window.period_seconds > 0 {
+            require!(
+                window.duration_seconds < window.period_seconds,
+                SplitError::InvalidBlackoutWindow
+            );
+            // Round each window's share of its period up so the sum is conservative
+            let period = window.period_seconds as u128;
+            duty_bps += (window.duration_seconds as u128 * BPS_DENOMINATOR as u128)
+                .div_ceil(period);
+        }
+    }
+    require!(
+        duty_bps < BPS_DENOMINATOR as u128,
+        SplitError::InvalidBlackoutWindow
+    );
+
+    Ok(())
+}
+
+/// How a recipient slot receives its SOL share
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq, Eq, InitSpace)]
+pub enum DeliverAs {
+    #[default]
+    Native,
+    WrappedSol { token_account: Pubkey },
+    Stake { vote_account: Pubkey },
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RouteTable {
+    pub sender: Pubkey,
+    #[max_len(MAX_ROUTES)]
+    pub routes: Vec<Route>,
+    pub bump: u8,
+}
+
+impl SplitConfig {
+    /// How `recipient` appears in this config's events: as is, or for configs
+    /// with `private_recipients` as its `private_recipient_key` under the
+    /// config address `config`
+    pub fn mask_recipient(&self, config: &Pubkey, recipient: &Pubkey) -> Pubkey {
+        if !self.private_recipients {
+            return *recipient;
+        }
+        private_recipient_key(config, recipient)
+    }
+
+    /// Trailing PDA seed of a config committed to its terms. Uncommitted
+    /// configs get an empty seed, which derives the same address as none.
+    pub fn terms_seed(&self) -> &[u8] {
+        if self.terms_hash == [0u8; 32] {
+            &[]
+        } else {
+            &self.terms_hash
+        }
+    }
+
+    /// `terms_hash` of the config's terms as they stand, which equals the
+    /// committed hash for configs committed to their terms
+    pub fn current_terms_hash(&self) -> [u8; 32] {
+        terms_hash(
+            &self.recipient1,
+            &self.recipient2,
+            self.recipient1_percentage,
+            self.recipient2_percentage,
+            &SplitOptions {
+                escrow: self.escrow,
+                claim_mode: self.claim_mode,
+                anyone_can_execute: self.anyone_can_execute,
+                allow_partial: self.allow_partial,
+                rotate_remainder: self.rotate_remainder,
+                close_residual_to_recipients: self.close_residual_to_recipients,
+                defer_reclaim: self.defer_reclaim,
+                ..SplitOptions::default()
+            },
+        )
+    }
+
+    /// Whether the config can no longer be executed
+    pub fn is_terminal(&self) -> bool {
+        self.executed
+    }
+
+    /// Execution timestamp, present only once the config has executed. A
+    /// never-executed config stores zero, which must not be read as a time;
+    /// durations since execution go through this instead of `executed_at`.
+    pub fn executed_at_checked(&self) -> Option<i64> {
+        self.is_terminal().then_some(self.executed_at)
+    }
+
+    /// Whether an execution may happen at `now`, honoring the escrow acceptance window
+    pub fn can_execute(&self, now: i64) -> bool {
+        !self.is_terminal()
+            && (!self.escrow || self.acceptance_deadline == 0 || now < self.acceptance_deadline)
+    }
+
+    /// Whether `depositor` may fund the config: the sender always; on escrow
+    /// configs anyone listed, or anyone at all if nobody is listed and
+    /// deposits are open
+    pub fn depositor_allowed(&self, depositor: &Pubkey) -> bool {
+        *depositor == self.sender
+            || (self.escrow
+                && (self.allowed_depositors.contains(depositor)
+                    || (self.open_deposits && self.allowed_depositors.is_empty())))
+    }
+
+    /// Mark the config executed, keeping the flag and timestamp in step
+    pub fn mark_executed(&mut self, now: i64) {
+        self.executed = true;
+        self.executed_at = now;
+    }
+
+    /// Shares for one execution of the reusable prefunded path. With remainder
+    /// rotation enabled the rounding lamport alternates between the slots, starting
+    /// with recipient2, and the cursor advances on every execution.
+    pub fn next_reusable_shares(&mut self, amount: u64, now: i64) -> Result<(u64, u64)> {
+        let recipient1_bps = self.recipient1_bps_for(amount, now);
+        if !self.rotate_remainder {
+            return compute_shares(amount, recipient1_bps);
+        }
+
+        let slot = self.remainder_rotation;
+        self.remainder_rotation = (slot + 1) % 2;
+
+        if slot == 0 {
+            return compute_shares(amount, recipient1_bps);
+        }
+        let (amount2, amount1) = compute_shares(amount, BPS_DENOMINATOR as u16 - recipient1_bps)?;
+        Ok((amount1, amount2))
+    }
+
+    /// Recipient 1's share in basis points: the migrated bps value, or the
+    /// legacy whole percentage scaled up
+    pub fn recipient1_bps(&self) -> u16 {
+        if self.recipient1_bps != 0 {
+            self.recipient1_bps
+        } else {
+            self.recipient1_percentage as u16 * 100
+        }
+    }
+
+    /// Recipient 1's share in basis points for a lamport execution of
+    /// `amount` at `now`: the ramp's current share, the highest ratio table
+    /// bracket the amount reaches, or the config's own share below the first
+    /// threshold
+    pub fn recipient1_bps_for(&self, amount: u64, now: i64) -> u16 {
+        if let Some(ramp) = &self.ratio_ramp {
+            return ramp.recipient1_bps_at(now);
+        }
+        self.ratio_tiers
+            .iter()
+            .rev()
+            .find(|tier| amount >= tier.min_amount)
+            .map_or(self.recipient1_bps(), |tier| tier.recipient1_percentage as u16 * 100)
+    }
+
+    /// Plan a lamport execution of `amount` at `now`: enforce the minimum,
+    /// compute the shares, hold them to the recipient caps, resolve where they
+    /// go and when execution is next allowed. Shared by `execute_split` and
+    /// `resolve_effective_terms` so the two can never disagree.
+    pub fn plan_execution(&self, amount: u64, now: i64) -> Result<EffectiveTerms> {
+        // Validate minimum amount to prevent dust
+        require!(amount >= MINIMUM_SPLIT_AMOUNT, SplitError::AmountTooSmall);
+
+        let (amount1, amount2) = compute_shares(amount, self.recipient1_bps_for(amount, now))?;
+        let (amount1, amount2, overflow) = self.apply_caps(amount1, amount2);
+
+        let next_allowed_at = self.next_allowed_at(now)?;
+
+        Ok(EffectiveTerms {
+            amount,
+            amount1,
+            amount2,
+            overflow,
+            blocked_until: if next_allowed_at > now { next_allowed_at } else { 0 },
+            claim_mode: self.claim_mode,
+            recipient1_delivery: self.recipient1_delivery.clone(),
+            recipient2_delivery: self.recipient2_delivery.clone(),
+        })
+    }
+
+    /// When the review period after initialization ends
+    pub fn review_ends_at(&self) -> i64 {
+        self.created_at.saturating_add(self.review_period_seconds as i64)
+    }
+
+    /// When the sender may first cancel the config
+    pub fn cancel_allowed_at(&self) -> i64 {
+        self.created_at.saturating_add(self.cancel_cooldown_seconds as i64)
+    }
+
+    /// Earliest time at or after `now` that is past the review period and
+    /// outside every blackout window, walking through back-to-back windows
+    pub fn next_allowed_at(&self, now: i64) -> Result<i64> {
+        let mut at = now.max(self.review_ends_at());
+        for _ in 0..MAX_BLACKOUT_STEPS {
+            match self.blackout_windows.iter().filter_map(|w| w.end_if_active(at)).max() {
+                Some(end) => at = end,
+                None => return Ok(at),
+            }
+        }
+        err!(SplitError::ExecutionInBlackout)
+    }
+
+    /// Require the approval threshold to be met, if the config sets one, and
+    /// clear the approvals for the next execution
+    pub fn consume_approvals(&mut self) -> Result<()> {
+        if self.approvals_required == 0 {
+            return Ok(());
+        }
+        require!(
+            self.approvals.count_ones() >= self.approvals_required as u32,
+            SplitError::ApprovalsPending
+        );
+        self.approvals = 0;
+        Ok(())
+    }
+
+    /// Reject execution during the review period or any blackout window
+    pub fn check_execution_window(&self, now: i64) -> Result<()> {
+        require!(now >= self.review_ends_at(), SplitError::ReviewPeriodActive);
+        require!(self.next_allowed_at(now)? == now, SplitError::ExecutionInBlackout);
+        Ok(())
+    }
+
+    /// Limit each share to what is left of its recipient's lifetime cap, zero
+    /// meaning uncapped. Returns the capped shares and the overflow that stays
+    /// with the sender.
+    pub fn apply_caps(&self, amount1: u64, amount2: u64) -> (u64, u64, u64) {
+        let cap = |share: u64, cap: u64, received: u64| {
+            if cap == 0 {
+                share
+            } else {
+                share.min(cap.saturating_sub(received))
+            }
+        };
+        let capped1 = cap(amount1, self.recipient1_cap, self.recipient1_received);
+        let capped2 = cap(amount2, self.recipient2_cap, self.recipient2_received);
+        let overflow = (amount1 - capped1) + (amount2 - capped2);
+        (capped1, capped2, overflow)
+    }
+
+    /// Whether a partial execution left either recipient owed
+    pub fn has_shortfall(&self) -> bool {
+        self.recipient1_shortfall > 0 || self.recipient2_shortfall > 0
+    }
+
+    /// Whether either recipient slot carries amounts from earlier activity:
+    /// received, owed, queued or paid as a cancel penalty
+    pub fn has_recipient_history(&self) -> bool {
+        self.recipient1_received > 0
+            || self.recipient2_received > 0
+            || self.has_shortfall()
+            || self.has_outstanding_claims()
+            || !self.recipient1_claim_queue.is_empty()
+            || !self.recipient2_claim_queue.is_empty()
+            || self.recipient1_penalty_paid > 0
+            || self.recipient2_penalty_paid > 0
+    }
+
+    /// How much of each owed share the deposit can cover. Short deposits fail
+    /// unless partial execution is allowed, in which case the shortfall policy
+    /// decides who is paid first.
+    pub fn fundable_shares(&self, owed1: u64, owed2: u64) -> Result<(u64, u64)> {
+        let available = self.deposited_amount;
+        let owed = owed1.checked_add(owed2).ok_or(SplitError::MathOverflow)?;
+        if owed <= available {
+            return Ok((owed1, owed2));
+        }
+        require!(self.allow_partial, SplitError::InsufficientBalance);
+
+        match self.shortfall_policy {
+            ShortfallPolicy::FailEntirely => err!(SplitError::InsufficientBalance),
+            ShortfallPolicy::PrioritizeSlot(1) => {
+                let paid1 = owed1.min(available);
+                Ok((paid1, owed2.min(available - paid1)))
+            }
+            ShortfallPolicy::PrioritizeSlot(_) => {
+                let paid2 = owed2.min(available);
+                Ok((owed1.min(available - paid2), paid2))
+            }
+            ShortfallPolicy::ProRata => {
+                let paid1 = safe_mul_div(owed1, available, owed)?;
+                Ok((paid1, owed2.min(available - paid1)))
+            }
+        }
+    }
+
+    /// Lamports `recipient` has received from this config so far
+    pub fn received_by(&self, recipient: &Pubkey) -> Result<u64> {
+        if *recipient == self.recipient1 {
+            Ok(self.recipient1_received)
+        } else if *recipient == self.recipient2 {
+            Ok(self.recipient2_received)
+        } else {
+            err!(SplitError::InvalidRecipient)
+        }
+    }
+
+    /// Add delivered shares to each recipient's lifetime total
+    pub fn record_received(&mut self, amount1: u64, amount2: u64) -> Result<()> {
+        self.recipient1_received = self
+            .recipient1_received
+            .checked_add(amount1)
+            .ok_or(SplitError::MathOverflow)?;
+        self.recipient2_received = self
+            .recipient2_received
+            .checked_add(amount2)
+            .ok_or(SplitError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Burned amount and recipient shares of a token split at `now`, each a
+    /// multiple of the denomination, which the amount itself must be. Burns
+    /// round down to the denomination and recipient 1's remainder goes to
+    /// recipient 2, as with lamport splits; a ratio ramp applies as it does to
+    /// them.
+    pub fn token_shares(&self, amount: u64, burn_bps: u16, now: i64) -> Result<(u64, u64, u64)> {
+        // Withheld lamport shares have no token counterpart
+        require!(self.withholding.is_none(), SplitError::WithholdingUnsupported);
+        // Nor do ratio table thresholds, which are lamport amounts
+        require!(self.ratio_tiers.is_empty(), SplitError::RatioTiersUnsupported);
+
+        let denomination = self.denomination;
+        require!(
+            amount.is_multiple_of(denomination),
+            SplitError::AmountNotDenominated
+        );
+
+        let burned = safe_mul_div(amount, burn_bps as u64, BPS_DENOMINATOR)?;
+        let burned = burned - burned % denomination;
+        let distributable = amount
+            .checked_sub(burned)
+            .ok_or(SplitError::MathOverflow)?;
+
+        let (amount1, _) = compute_shares(distributable, self.recipient1_bps_for(distributable, now))?;
+        let amount1 = amount1 - amount1 % denomination;
+        let amount2 = distributable
+            .checked_sub(amount1)
+            .ok_or(SplitError::MathOverflow)?;
+
+        Ok((burned, amount1, amount2))
+    }
+
+    /// Reject recipient accounts holding less data than the configured minimum,
+    /// for integrators paying into data accounts
+    pub fn check_recipient_data_len(
+        &self,
+        recipient1: &AccountInfo,
+        recipient2: &AccountInfo,
+    ) -> Result<()> {
+        require!(
+            recipient1.data_len() as u64 >= self.min_recipient_data_len
+                && recipient2.data_len() as u64 >= self.min_recipient_data_len,
+            SplitError::RecipientTooSmall
+        );
+        Ok(())
+    }
+
+    /// Count an execution and the lamports it distributed
+    pub fn record_distribution(&mut self, amount: u64) -> Result<()> {
+        self.execution_count = self
+            .execution_count
+            .checked_add(1)
+            .ok_or(SplitError::MathOverflow)?;
+        self.total_distributed = self
+            .total_distributed
+            .checked_add(amount)
+            .ok_or(SplitError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Whether any credited share is still waiting to be claimed
+    pub fn has_outstanding_claims(&self) -> bool {
+        self.recipient1_claimable > 0 || self.recipient2_claimable > 0
+    }
+
+    /// Credit executed shares to the recipients' claimable balances, queueing
+    /// each as an entry dated with the current execution. `config` is this
+    /// config's address.
+    pub fn credit_claims(&mut self, config: &Pubkey, amount1: u64, amount2: u64) -> Result<()> {
+        self.recipient1_claimable = self
+            .recipient1_claimable
+            .checked_add(amount1)
+            .ok_or(SplitError::MathOverflow)?;
+        self.recipient2_claimable = self
+            .recipient2_claimable
+            .checked_add(amount2)
+            .ok_or(SplitError::MathOverflow)?;
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        for (recipient, amount) in [(self.recipient1, amount1), (self.recipient2, amount2)] {
+            if amount == 0 {
+                continue;
+            }
+            let entry = ClaimEntry {
+                amount,
+                execution_count: self.execution_count,
+                timestamp,
+                merged: false,
+            };
+            let merged_oldest = enqueue_claim(self.claim_queue_mut(&recipient)?, entry)?;
+
+            emit_recorded!(self, ClaimEnqueued {
+                schema_version: EVENT_SCHEMA_VERSION,
+                recipient: self.mask_recipient(config, &recipient),
+                nonce: self.nonce,
+                amount,
+                execution_count: self.execution_count,
+                enqueued_at: timestamp,
+                merged_oldest,
+            });
+        }
+        Ok(())
+    }
+
+    /// The claim queue belonging to `recipient`
+    pub fn claim_queue_mut(&mut self, recipient: &Pubkey) -> Result<&mut Vec<ClaimEntry>> {
+        if *recipient == self.recipient1 {
+            Ok(&mut self.recipient1_claim_queue)
+        } else if *recipient == self.recipient2 {
+            Ok(&mut self.recipient2_claim_queue)
+        } else {
+            err!(SplitError::InvalidRecipient)
+        }
+    }
+
+    /// The claimable balance belonging to `recipient`
+    pub fn claimable_mut(&mut self, recipient: &Pubkey) -> Result<&mut u64> {
+        if *recipient == self.recipient1 {
+            Ok(&mut self.recipient1_claimable)
+        } else if *recipient == self.recipient2 {
+            Ok(&mut self.recipient2_claimable)
+        } else {
+            err!(SplitError::InvalidRecipient)
+        }
+    }
+
+    /// Slot (1 or 2) that `recipient` occupies
+    pub fn recipient_slot(&self, recipient: &Pubkey) -> Result<u8> {
+        if *recipient == self.recipient1 {
+            Ok(1)
+        } else if *recipient == self.recipient2 {
+            Ok(2)
+        } else {
+            err!(SplitError::InvalidRecipient)
+        }
+    }
+
+    /// Claim status and registered recovery key of recipient `slot`
+    pub fn claim_status_mut(&mut self, slot: u8) -> Result<(&mut ClaimStatus, &mut Pubkey)> {
+        match slot {
+            1 => Ok((&mut self.recipient1_claim_status, &mut self.recipient1_recovery)),
+            2 => Ok((&mut self.recipient2_claim_status, &mut self.recipient2_recovery)),
+            _ => err!(SplitError::InvalidRecipient),
+        }
+    }
+
+    /// Require `recipient`'s claims not to be frozen
+    pub fn require_claims_open(&self, recipient: &Pubkey) -> Result<()> {
+        let status = match self.recipient_slot(recipient)? {
+            1 => self.recipient1_claim_status,
+            _ => self.recipient2_claim_status,
+        };
+        require!(status == ClaimStatus::Open, SplitError::ClaimsFrozen);
+        Ok(())
+    }
+
+    /// Whether a delegate has been assigned
+    pub fn has_delegate(&self) -> bool {
+        self.delegate != Pubkey::default()
+    }
+
+    /// Charge a delegate execution against the current limit window, starting a
+    /// new window once the previous one has elapsed
+    pub fn consume_delegate_allowance(&mut self, now: i64, amount: u64) -> Result<()> {
+        if now >= self.delegate_window_start.saturating_add(DELEGATE_LIMIT_WINDOW_SECONDS) {
+            self.delegate_window_start = now;
+            self.delegate_window_spent = 0;
+        }
+
+        let spent = self
+            .delegate_window_spent
+            .checked_add(amount)
+            .ok_or(SplitError::MathOverflow)?;
+        require!(
+            self.delegate_daily_limit == 0 || spent <= self.delegate_daily_limit,
+            SplitError::DelegateLimitExceeded
+        );
+
+        self.delegate_window_spent = spent;
+        Ok(())
+    }
+
+    /// Fold one logged event into the history digest, if tracked
+    pub fn record_history(&mut self, event_data: &[u8]) {
+        if self.track_history_digest {
+            self.history_digest = hashv(&[&self.history_digest, event_data]).to_bytes();
+        }
+    }
+
+    /// Reject execution paths that only pay the recipient pair
+    pub fn require_two_recipients(&self) -> Result<()> {
+        require!(
+            self.layout == LAYOUT_TWO_RECIPIENTS,
+            SplitError::MultiRecipientLayout
+        );
+        Ok(())
+    }
+
+    /// Whether the config uses only options that `execute_split_multi` and
+    /// the multi-recipient layout can honour
+    pub fn supports_multi_recipients(&self) -> bool {
+        !self.claim_mode
+            && self.cascade_target1.is_none()
+            && !self.defer_reclaim
+            && !self.require_execution_memo
+            && !self.update_recipient_ledger
+            && !self.rotate_remainder
+            && !self.close_residual_to_recipients
+            && self.rent_split.is_none()
+            && self.withholding.is_none()
+            && self.min_recipient_data_len == 0
+            && self.recipient1_delivery == DeliverAs::Native
+            && self.recipient2_delivery == DeliverAs::Native
+            && self.recipient1_auto_swap.is_none()
+            && self.recipient2_auto_swap.is_none()
+    }
+
+    /// Reject field combinations no instruction should be able to produce
+    pub fn verify(&self) -> Result<()> {
+        require!(
+            self.recipient1_percentage as u16 + self.recipient2_percentage as u16 == 100,
+            SplitError::InvalidConfigState
+        );
+        require!(
+            self.recipient1_bps as u64 <= BPS_DENOMINATOR,
+            SplitError::InvalidConfigState
+        );
+        require!(
+            self.executed == (self.executed_at != 0),
+            SplitError::InvalidConfigState
+        );
+        require!(
+            self.escrow || (!self.recipient1_accepted && !self.recipient2_accepted),
+            SplitError::InvalidConfigState
+        );
+        require!(
+            !self.executed || self.deposited_amount == 0,
+            SplitError::InvalidConfigState
+        );
+        require!(
+            self.claim_mode || !self.has_outstanding_claims(),
+            SplitError::InvalidConfigState
+        );
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SplitHistory {
+    pub split_config: Pubkey,
+    pub execution_count: u64,
+    pub total_amount: u64,
+    pub last_amount: u64,
+    pub last_executed_at: i64,
+    pub bump: u8,
+}
+
+impl SplitHistory {
+    /// Fold an execution into the history, initializing it on first use
+    pub fn record_execution(
+        &mut self,
+        split_config: Pubkey,
+        bump: Option<u8>,
+        amount: u64,
+        now: i64,
+    ) -> Result<()> {
+        if self.split_config == Pubkey::default() {
+            self.split_config = split_config;
+            self.bump = bump.ok_or(SplitError::InvalidConfigState)?;
+        }
+
+        self.execution_count = self
+            .execution_count
+            .checked_add(1)
+            .ok_or(SplitError::MathOverflow)?;
+        self.total_amount = self
+            .total_amount
+            .checked_add(amount)
+            .ok_or(SplitError::MathOverflow)?;
+        self.last_amount = amount;
+        self.last_executed_at = now;
+
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    pub epoch_volume_ceiling: u64,
+    pub epoch: u64,
+    pub epoch_volume: u64,
+    pub charity: Pubkey,
+    pub open_donations: bool,
+    pub soft_paused: bool,
+    pub max_execution_during_pause: u64,
+    pub migration_window_end: i64,
+    pub bump: u8,
+    pub max_config_lifetime_seconds: i64,
+    pub min_executor_bond: u64,
+    #[max_len(MAX_SWAP_PROGRAMS)]
+    pub swap_programs: Vec<Pubkey>,
+    pub token_split_fee_lamports: u64,
+    pub creation_fee_lamports: u64,
+    pub genesis_guard: [u8; 8],
+    pub allow_composed_close: bool,
+}
+
+impl ProgramConfig {
+    /// Whether a config created at `created_at` has outlived the global
+    /// maximum lifetime, if one is set
+    pub fn config_expired(&self, created_at: i64, now: i64) -> bool {
+        self.max_config_lifetime_seconds > 0
+            && now >= created_at.saturating_add(self.max_config_lifetime_seconds)
+    }
+
+    /// Whether the counter backfill window has been set and has passed
+    pub fn migration_window_closed(&self, now: i64) -> bool {
+        self.migration_window_end != 0 && now >= self.migration_window_end
+    }
+}
+
+/// Lifetime lamport statement for one recipient across every config that
+/// opts in with `update_recipient_ledger`. Token executions aren't recorded.
+/// Each execution or claim of an opted-in config write-locks the ledger, so
+/// transactions naming the same recipient serialize instead of running in
+/// parallel; configs that don't opt in never touch it.
+#[account]
+#[derive(InitSpace)]
+pub struct RecipientLedger {
+    pub recipient: Pubkey,
+    /// Every share credited, whether paid or still pending
+    pub earned: u64,
+    /// Credited to claim-mode configs and not yet claimed
+    pub pending: u64,
+    pub paid: u64,
+}
+
+/// Token account a recipient wants their token split shares of one mint paid
+/// to, overriding whichever of their accounts an executor would pick
+#[account]
+#[derive(InitSpace)]
+pub struct RecipientPreference {
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub bump: u8,
+}
+
+/// Recipients of a config using the multi-recipient layout, in payout order
+#[account]
+#[derive(InitSpace)]
+pub struct RecipientSet {
+    pub split_config: Pubkey,
+    #[max_len(MAX_STAGED_RECIPIENTS)]
+    pub shares: Vec<RecipientShare>,
+}
+
+impl RecipientSet {
+    pub fn recipients(&self) -> Vec<Pubkey> {
+        self.shares.iter().map(|share| share.recipient).collect()
+    }
+
+    /// Each recipient's share of `amount`, the last taking the rounding
+    /// remainder
+    pub fn payouts(&self, amount: u64) -> Result<Vec<u64>> {
+        bps_payouts(&self.shares, amount)
+    }
+}
+
+/// Each share of `amount` by its basis points, the last taking the rounding
+/// remainder
+fn bps_payouts(shares: &[RecipientShare], amount: u64) -> Result<Vec<u64>> {
+    let mut payouts = Vec::with_capacity(shares.len());
+    let mut paid: u64 = 0;
+    for share in &shares[..shares.len() - 1] {
+        let payout = safe_mul_div(amount, share.bps as u64, BPS_DENOMINATOR)?;
+        paid += payout;
+        payouts.push(payout);
+    }
+    payouts.push(amount - paid);
+    Ok(payouts)
+}
+
+/// A recipient's amount locked by `stage_split`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct StagedPayout {
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// Amounts of a split staged for paging, holding their total until paid
+#[account]
+#[derive(InitSpace)]
+pub struct StagedSplit {
+    pub split_config: Pubkey,
+    pub total: u64,
+    pub next_index: u16,
+    pub prior_layout: u8,
+    pub bump: u8,
+    #[max_len(MAX_STAGED_RECIPIENTS)]
+    pub payouts: Vec<StagedPayout>,
+}
+
+impl RecipientLedger {
+    /// Record a share from an execution, pending until claimed in claim mode
+    pub fn record_share(&mut self, recipient: Pubkey, share: u64, claim_mode: bool) -> Result<()> {
+        // Ledgers created lazily by an execution start out zeroed
+        self.recipient = recipient;
+        self.earned = self.earned.checked_add(share).ok_or(SplitError::MathOverflow)?;
+        let bucket = if claim_mode { &mut self.pending } else { &mut self.paid };
+        *bucket = bucket.checked_add(share).ok_or(SplitError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Move a claimed amount from pending to paid
+    pub fn record_claim(&mut self, claimed: u64) -> Result<()> {
+        self.pending = self.pending.checked_sub(claimed).ok_or(SplitError::MathOverflow)?;
+        self.paid = self.paid.checked_add(claimed).ok_or(SplitError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// Lamports an open executor has at stake, with its last execution kept as
+/// evidence for slashing
+#[account]
+#[derive(InitSpace)]
+pub struct ExecutorBond {
+    pub executor: Pubkey,
+    pub amount: u64,
+    pub last_active_at: i64,
+    pub last_config: Pubkey,
+    pub last_executed_at: i64,
+    pub bump: u8,
+}
+
+impl ExecutorBond {
+    /// Time of the last recorded open execution, none if the bond has never
+    /// executed and `last_executed_at` still holds its zero sentinel
+    pub fn last_executed_at_checked(&self) -> Option<i64> {
+        (self.last_executed_at != 0).then_some(self.last_executed_at)
+    }
+
+    /// Forget which config the last execution was of once it has been slashed,
+    /// so the same violation can't also take later top-ups. The time stays, as
+    /// the bond has still executed.
+    pub fn clear_execution(&mut self) {
+        self.last_config = Pubkey::default();
+    }
+
+    /// Record an open execution, which also restarts the withdrawal cooldown
+    pub fn record_execution(&mut self, split_config: Pubkey, now: i64) {
+        self.last_config = split_config;
+        self.last_executed_at = now;
+        self.last_active_at = now;
+    }
+}
+
+/// Protocol treasury. Fees are paid into this PDA and periodically split
+/// between the stakeholders by `distribute_treasury`.
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub stakeholder1: Pubkey,
+    pub stakeholder2: Pubkey,
+    pub stakeholder1_percentage: u8,
+    pub stakeholder2_percentage: u8,
+    pub total_distributed: u64,
+    pub bump: u8,
+    /// Creation fees still refundable, collected on `refund_reserve_day`
+    /// and the day before it. `distribute_treasury` leaves them in place.
+    pub refund_reserve: [u64; 2],
+    pub refund_reserve_day: i64,
+}
+
+impl Treasury {
+    /// Creation fees still inside their refund window at `now`
+    pub fn pending_refunds(&mut self, now: i64) -> u64 {
+        self.roll_refund_reserve(now);
+        self.refund_reserve[0].saturating_add(self.refund_reserve[1])
+    }
+
+    /// Hold a fee collected at `now` back from distribution until its
+    /// refund window has passed
+    pub fn reserve_refund(&mut self, fee: u64, now: i64) {
+        self.roll_refund_reserve(now);
+        self.refund_reserve[0] = self.refund_reserve[0].saturating_add(fee);
+    }
+
+    /// Release the reservation of a fee collected at `created_at`, once
+    /// refunded
+    pub fn release_refund(&mut self, fee: u64, created_at: i64, now: i64) {
+        self.roll_refund_reserve(now);
+        let age = refund_day(now) - refund_day(created_at);
+        if let Some(reserved) = usize::try_from(age)
+            .ok()
+            .and_then(|age| self.refund_reserve.get_mut(age))
+        {
+            *reserved = reserved.saturating_sub(fee);
+        }
+    }
+
+    /// Move the reserve forward to the day of `now`. Fees collected two or
+    /// more days earlier are past their window and drop out.
+    fn roll_refund_reserve(&mut self, now: i64) {
+        let day = refund_day(now);
+        match day - self.refund_reserve_day {
+            0 => {}
+            1 => self.refund_reserve = [0, self.refund_reserve[0]],
+            _ => self.refund_reserve = [0, 0],
+        }
+        self.refund_reserve_day = day;
+    }
+}
+
+/// Day a creation fee's refund reservation is counted under
+fn refund_day(timestamp: i64) -> i64 {
+    timestamp.div_euclid(CREATION_FEE_REFUND_WINDOW_SECONDS)
+}
+
+/// Marks a sender the admin exempted from the creation fee
+#[account]
+#[derive(InitSpace)]
+pub struct FeeExemption {
+    pub sender: Pubkey,
+    pub bump: u8,
+}
+
+/// A client ID the admin approved for attribution
+#[account]
+#[derive(InitSpace)]
+pub struct ClientRegistry {
+    pub client_id: [u8; 8],
+    /// Hash of the client's display name, kept off-chain
+    pub name_hash: [u8; 32],
+    pub bump: u8,
+}
+
+/// A client naming itself on initialization or execution. Unregistered IDs
+/// are kept but marked unverified.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct ClientAttribution {
+    pub client_id: [u8; 8],
+    pub verified: bool,
+}
+
+/// Executed configs of one sender waiting for `sweep_reclaim`
+#[account]
+#[derive(InitSpace)]
+pub struct ReclaimQueue {
+    pub sender: Pubkey,
+    #[max_len(RECLAIM_QUEUE_CAPACITY)]
+    pub configs: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Outbox {
+    pub sender: Pubkey,
+    pub next_seq: u64,
+    pub acked_seq: u64,
+    pub lost_events: u64,
+    #[max_len(OUTBOX_CAPACITY)]
+    pub records: Vec<OutboxRecord>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct OutboxRecord {
+    pub config: Pubkey,
+    pub event_type: LifecycleEvent,
+    pub amount: u64,
+    pub seq: u64,
+}
+
+/// Why a split was cancelled, reported in `SplitCancelled`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CancelReason {
+    UserRequested,
+    Dispute,
+    Expired,
+    Compliance,
+    Other(u8),
+}
+
+/// Whether a recipient slot's credited claims can be withdrawn. A frozen slot
+/// reopens only when its claims are redirected to a registered recovery key.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum ClaimStatus {
+    #[default]
+    Open,
+    Frozen,
+}
+
+/// Who gets paid when a prefunded execution asks for more than the deposit holds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum ShortfallPolicy {
+    #[default]
+    FailEntirely,
+    /// Make the given slot (1 or 2) whole before paying the other
+    PrioritizeSlot(u8),
+    ProRata,
+}
+
+/// Outcome of one `execute_splits_batch` entry. Failures carry the error
+/// code the entry would have failed the batch with.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchStatus {
+    Executed,
+    SkippedAlreadyExecuted,
+    FailedValidation(u32),
+    FailedTransfer(u32),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchEntryReport {
+    pub status: BatchStatus,
+    pub amount1: u64,
+    pub amount2: u64,
+}
+
+impl BatchEntryReport {
+    fn unpaid(status: BatchStatus) -> Self {
+        Self { status, amount1: 0, amount2: 0 }
+    }
+}
+
+/// Result of `execute_splits_batch`, one entry per config in call order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchReport {
+    pub entries: Vec<BatchEntryReport>,
+}
+
+/// Why `sweep_vaults` left a vault untouched
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SweepSkipReason {
+    SeedMismatch,
+    LiveConfig,
+    Empty,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum LifecycleEvent {
+    Initialized,
+    Executed,
+    Cancelled,
+    Closed,
+    Deposited,
+    Refunded,
+    Claimed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RentCost {
+    pub account: Pubkey,
+    pub lamports: u64,
+    pub refundable: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Route {
+    pub route_id: [u8; 16],
+    pub split_config: Pubkey,
+}
+
+#[event]
+pub struct SplitInitialized {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub recipient1: Pubkey,
+    pub recipient2: Pubkey,
+    pub recipient1_percentage: u8,
+    pub recipient2_percentage: u8,
+    pub nonce: u64,
+    pub topic: [u8; 8],
+    pub agreement_hash: [u8; 32],
+    pub defaults_applied: u16,
+    pub review_period_seconds: u32,
+    pub rent_costs: Vec<RentCost>,
+    pub total_rent: u64,
+    pub external_ref: Option<Pubkey>,
+    pub client: Option<ClientAttribution>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplitExecuted {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub recipient1: Pubkey,
+    pub recipient2: Pubkey,
+    pub amount1: u64,
+    pub amount2: u64,
+    pub total_amount: u64,
+    pub topic: [u8; 8],
+    pub agreement_hash: [u8; 32],
+    pub route_id: [u8; 16],
+    pub valid_until_slot: u64,
+    pub slot: u64,
+    pub external_ref: Option<Pubkey>,
+    pub client: Option<ClientAttribution>,
+    pub timestamp: i64,
+}
+
+/// Leading bytes of a compact `SplitExecuted` record, which no Anchor event
+/// discriminator starts with
+pub const COMPACT_SPLIT_EXECUTED_TAG: [u8; 3] = *b"ssx";
+
+/// Length of a compact `SplitExecuted` record
+pub const COMPACT_SPLIT_EXECUTED_LEN: usize = 92;
+
+impl SplitExecuted {
+    /// Fixed-size record logged with `sol_log_data` in place of the event for
+    /// configs with `compact_events`. Recipients, agreement hash, route, slot
+    /// bound, external reference and client are left out; the config's sender and
+    /// nonce identify them. Integers are little-endian.
+    ///
+    /// | offset | size | field            |
+    /// |--------|------|------------------|
+    /// | 0      | 3    | tag `ssx`        |
+    /// | 3      | 1    | `schema_version` |
+    /// | 4      | 32   | `sender`         |
+    /// | 36     | 8    | config nonce     |
+    /// | 44     | 8    | `amount1`        |
+    /// | 52     | 8    | `amount2`        |
+    /// | 60     | 8    | `total_amount`   |
+    /// | 68     | 8    | `topic`          |
+    /// | 76     | 8    | `slot`           |
+    /// | 84     | 8    | `timestamp`      |
+    pub fn compact(&self, nonce: u64) -> [u8; COMPACT_SPLIT_EXECUTED_LEN] {
+        let mut data = [0u8; COMPACT_SPLIT_EXECUTED_LEN];
+        data[..3].copy_from_slice(&COMPACT_SPLIT_EXECUTED_TAG);
+        data[3] = self.schema_version;
+        data[4..36].copy_from_slice(self.sender.as_ref());
+        data[36..44].copy_from_slice(&nonce.to_le_bytes());
+        data[44..52].copy_from_slice(&self.amount1.to_le_bytes());
+        data[52..60].copy_from_slice(&self.amount2.to_le_bytes());
+        data[60..68].copy_from_slice(&self.total_amount.to_le_bytes());
+        data[68..76].copy_from_slice(&self.topic);
+        data[76..84].copy_from_slice(&self.slot.to_le_bytes());
+        data[84..92].copy_from_slice(&self.timestamp.to_le_bytes());
+        data
+    }
+}
+
+#[event]
+pub struct TokenSplitExecuted {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub mint: Pubkey,
+    pub recipient1: Pubkey,
+    pub recipient2: Pubkey,
+    pub amount1: u64,
+    pub amount2: u64,
+    pub burned: u64,
+    pub total_amount: u64,
+    pub topic: [u8; 8],
+    /// Per recipient, whether a passed preference chose the destination
+    pub preferences_honored: [bool; 2],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplitCancelled {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub topic: [u8; 8],
+    pub reason: CancelReason,
+    /// Cancellation penalty paid to each recipient over the config's life
+    pub penalty1: u64,
+    pub penalty2: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplitClosed {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub topic: [u8; 8],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryDistributed {
+    pub schema_version: u8,
+    pub stakeholder1: Pubkey,
+    pub stakeholder2: Pubkey,
+    pub amount1: u64,
+    pub amount2: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResidualDistributed {
+    pub schema_version: u8,
+    pub split_config: Pubkey,
+    pub recipient1: Pubkey,
+    pub recipient2: Pubkey,
+    pub amount1: u64,
+    pub amount2: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultSwept {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub vault: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub skip_reason: Option<SweepSkipReason>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigExpired {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub cleaner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplitReconfigured {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub old_recipient1: Pubkey,
+    pub old_recipient2: Pubkey,
+    pub old_recipient1_percentage: u8,
+    pub old_recipient2_percentage: u8,
+    pub new_recipient1: Pubkey,
+    pub new_recipient2: Pubkey,
+    pub new_recipient1_percentage: u8,
+    pub new_recipient2_percentage: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SolFeeCollected {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub split_config: Pubkey,
+    pub mint: Pubkey,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenLegSwapped {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub split_config: Pubkey,
+    pub slot: u8,
+    pub recipient: Pubkey,
+    pub venue_program: Pubkey,
+    pub target_mint: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AutoSwapFallback {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub split_config: Pubkey,
+    pub slot: u8,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreationFeeCollected {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub split_config: Pubkey,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreationFeeRefunded {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub split_config: Pubkey,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReceiptsForwarded {
+    pub schema_version: u8,
+    pub split_config: Pubkey,
+    pub cranker: Pubkey,
+    pub amount: u64,
+    pub amount1: u64,
+    pub amount2: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchExecuted {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub executed: u16,
+    pub skipped: u16,
+    pub failed: u16,
+    pub total_distributed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplitPrecisionUpgraded {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub recipient1_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MultiSplitExecuted {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub layout: u8,
+    pub recipients: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecipientSetConfigured {
+    pub schema_version: u8,
+    pub split_config: Pubkey,
+    pub recipients: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplitStaged {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub recipients: u8,
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StagedPageCommitted {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub first_index: u16,
+    pub paid: u16,
+    pub complete: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ShareWithheld {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub slot: u8,
+    pub destination: Pubkey,
+    pub gross: u64,
+    pub withheld: u64,
+    pub net: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithholdingUpdated {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub withholding: Option<Withholding>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimsFrozen {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub slot: u8,
+    pub recipient: Pubkey,
+    pub frozen_by: Pubkey,
+    pub claimable: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimsRecoveryRegistered {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub slot: u8,
+    pub recipient: Pubkey,
+    pub recovery: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimsRedirected {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub slot: u8,
+    pub old_recipient: Pubkey,
+    pub new_recipient: Pubkey,
+    pub claimable: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExternalRefUpdated {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub external_ref: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExecutionApproved {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub approver: Pubkey,
+    pub approvals: u8,
+    pub approvals_required: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SenderTornDown {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub dry_run: bool,
+    pub accounts: u32,
+    pub blocked: u32,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RentReclaimed {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub configs: u32,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplitCascaded {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub child: Pubkey,
+    pub depth: u8,
+    pub amount: u64,
+    pub amount1: u64,
+    pub amount2: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExecutionMemo {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub execution_count: u64,
+    pub memo: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimEnqueued {
+    pub schema_version: u8,
+    pub recipient: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub execution_count: u64,
+    pub enqueued_at: i64,
+    pub merged_oldest: bool,
+}
+
+#[event]
+pub struct ClaimEntryPaid {
+    pub schema_version: u8,
+    pub recipient: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub execution_count: u64,
+    pub enqueued_at: i64,
+    pub merged: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PartialExecution {
+    pub schema_version: u8,
+    pub split_config: Pubkey,
+    pub policy: ShortfallPolicy,
+    pub amount1: u64,
+    pub amount2: u64,
+    pub unpaid1: u64,
+    pub unpaid2: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExecutorBondSlashed {
+    pub schema_version: u8,
+    pub split_config: Pubkey,
+    pub executor: Pubkey,
+    pub amount: u64,
+    pub executed_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RentDistributed {
+    pub schema_version: u8,
+    pub split_config: Pubkey,
+    pub sender: Pubkey,
+    pub sender_amount: u64,
+    pub destination: Pubkey,
+    pub destination_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TopicChanged {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub old_topic: [u8; 8],
+    pub new_topic: [u8; 8],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowDeposited {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub deposited_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepositorAllowed {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub depositor: Pubkey,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct CancelSplit<'info> {
-    #[account(
-        mut,
-        close = sender,
-        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes()],
-        bump = split_config.bump,
-        constraint = !split_config.executed @ SplitError::AlreadyExecuted
-    )]
-    pub split_config: Account<'info, SplitConfig>,
-    
-    #[account(mut)]
-    pub sender: Signer<'info>,
+#[event]
+pub struct DepositorRemoved {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub nonce: u64,
+    pub depositor: Pubkey,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct CloseSplit<'info> {
-    #[account(
-        mut,
-        close = sender,
-        seeds = [b"split_config", sender.key().as_ref(), &split_config.nonce.to_le_bytes()],
-        bump = split_config.bump,
-        constraint = split_config.executed @ SplitError::NotExecuted
-    )]
-    pub split_config: Account<'info, SplitConfig>,
-    
-    #[account(mut)]
-    pub sender: Signer<'info>,
+#[event]
+pub struct ReleaseConfirmed {
+    pub schema_version: u8,
+    pub recipient: Pubkey,
+    pub nonce: u64,
+    pub timestamp: i64,
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct SplitConfig {
+#[event]
+pub struct EscrowRefunded {
+    pub schema_version: u8,
     pub sender: Pubkey,
-    pub recipient1: Pubkey,
-    pub recipient2: Pubkey,
-    pub recipient1_percentage: u8,
-    pub recipient2_percentage: u8,
-    pub executed: bool,
     pub nonce: u64,
-    pub created_at: i64,
-    pub executed_at: i64,
-    pub bump: u8,
+    /// Refunded to the sender, after any penalty
+    pub amount: u64,
+    pub penalty1: u64,
+    pub penalty2: u64,
+    pub timestamp: i64,
 }
 
 #[event]
-pub struct SplitInitialized {
+pub struct DelegateProposed {
+    pub schema_version: u8,
     pub sender: Pubkey,
-    pub recipient1: Pubkey,
-    pub recipient2: Pubkey,
-    pub recipient1_percentage: u8,
-    pub recipient2_percentage: u8,
     pub nonce: u64,
+    pub delegate: Pubkey,
+    pub daily_limit: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct SplitExecuted {
+pub struct DelegateUpdated {
+    pub schema_version: u8,
     pub sender: Pubkey,
-    pub recipient1: Pubkey,
-    pub recipient2: Pubkey,
-    pub amount1: u64,
-    pub amount2: u64,
-    pub total_amount: u64,
+    pub nonce: u64,
+    pub delegate: Pubkey,
+    pub daily_limit: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct SplitCancelled {
+pub struct EpochCeilingChanged {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub old_ceiling: u64,
+    pub new_ceiling: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProgramConfigClosed {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SoftPauseChanged {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub soft_paused: bool,
+    pub max_execution_during_pause: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CountersBackfilled {
+    pub schema_version: u8,
+    pub split_config: Pubkey,
+    pub admin: Pubkey,
+    pub execution_count: u64,
+    pub total_distributed: u64,
+    pub proof_note: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerTripped {
+    pub schema_version: u8,
+    pub epoch: u64,
+    pub epoch_volume: u64,
+    pub ceiling: u64,
+    pub attempted_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BumpRepaired {
+    pub schema_version: u8,
     pub sender: Pubkey,
     pub nonce: u64,
+    pub old_bump: u8,
+    pub new_bump: u8,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct SplitClosed {
-    pub sender: Pubkey,
+pub struct SplitClaimed {
+    pub schema_version: u8,
+    pub recipient: Pubkey,
     pub nonce: u64,
+    pub claimed_amount: u64,
+    pub recipient_amount: u64,
+    pub donated_amount: u64,
+    pub donation_destination: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgreementAttested {
+    pub schema_version: u8,
+    pub recipient: Pubkey,
+    pub nonce: u64,
+    pub hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RouteRegistered {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub route_id: [u8; 16],
+    pub split_config: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RouteRepointed {
+    pub schema_version: u8,
+    pub sender: Pubkey,
+    pub route_id: [u8; 16],
+    pub old_split_config: Pubkey,
+    pub new_split_config: Pubkey,
     pub timestamp: i64,
 }
 
@@ -380,6 +9928,417 @@ pub enum SplitError {
     #[msg("Insufficient balance")]
     InsufficientBalance,
     
+    #[msg("Execution would leave the sender below its minimum balance")]
+    SenderBalanceBelowMinimum,
+    
     #[msg("Recipients must be different addresses")]
     DuplicateRecipient,
+    
+    #[msg("Route table is full")]
+    RouteTableFull,
+    
+    #[msg("Route not found")]
+    RouteNotFound,
+    
+    #[msg("Split configuration does not match the route")]
+    RouteConfigMismatch,
+    
+    #[msg("Escrow splits are released by recipient confirmation")]
+    EscrowConfig,
+    
+    #[msg("Split is not an escrow configuration")]
+    NotEscrowConfig,
+    
+    #[msg("Escrow still holds deposited funds")]
+    EscrowNotEmpty,
+    
+    #[msg("Acceptance deadline has passed")]
+    AcceptanceDeadlinePassed,
+    
+    #[msg("Refund is not available before the acceptance deadline")]
+    RefundNotAvailable,
+    
+    #[msg("Depositor is not allowed to fund this config")]
+    DepositorNotAllowed,
+    
+    #[msg("Allowed depositors must be distinct, non-default, and at most 8")]
+    InvalidDepositors,
+    
+    #[msg("Account is not the System Program")]
+    InvalidSystemProgram,
+    
+    #[msg("Account is not the SPL Token program")]
+    InvalidTokenProgram,
+    
+    #[msg("Account is not the Associated Token Account program")]
+    InvalidAtaProgram,
+    
+    #[msg("Net amounts are inconsistent with the split ratio")]
+    NetAmountsInconsistent,
+    
+    #[msg("Cannot acknowledge an outbox sequence that has not been written")]
+    InvalidAckSequence,
+    
+    #[msg("Split configuration is in an inconsistent state")]
+    InvalidConfigState,
+    
+    #[msg("Burn basis points cannot exceed 10000")]
+    InvalidBurnBps,
+    
+    #[msg("Wrapped SOL token account is missing or closed")]
+    WrappedAccountMissing,
+    
+    #[msg("Wrapped SOL token account has the wrong mint or owner")]
+    InvalidWrappedAccount,
+    
+    #[msg("Executor is neither the sender nor the delegate")]
+    UnauthorizedExecutor,
+    
+    #[msg("Execution exceeds the delegate's daily limit")]
+    DelegateLimitExceeded,
+    
+    #[msg("Signer is not the program admin")]
+    UnauthorizedAdmin,
+    
+    #[msg("Program-wide epoch volume ceiling reached")]
+    CircuitBreakerTripped,
+    
+    #[msg("Split options cannot be combined")]
+    IncompatibleOptions,
+    
+    #[msg("Split is not a claim-mode configuration")]
+    NotClaimMode,
+    
+    #[msg("Claim-mode splits only credit SOL shares")]
+    ClaimModeConfig,
+    
+    #[msg("Credited shares have not been claimed yet")]
+    ClaimsOutstanding,
+    
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    
+    #[msg("Donation basis points cannot exceed 10000")]
+    InvalidDonationBps,
+    
+    #[msg("Donation destination is not allowed")]
+    DonationDestinationNotAllowed,
+    
+    #[msg("Split has no agreement hash")]
+    NoAgreement,
+    
+    #[msg("Hash does not match the split's agreement")]
+    AgreementHashMismatch,
+    
+    #[msg("Execution exceeds the amount allowed during the soft pause")]
+    SoftPauseLimitExceeded,
+    
+    #[msg("Rent split is invalid or its destination is missing")]
+    InvalidRentSplit,
+    
+    #[msg("Stake account or a required stake program account is missing")]
+    StakeAccountMissing,
+    
+    #[msg("Share does not cover the stake account's rent")]
+    StakeShareTooSmall,
+    
+    #[msg("Execution window has expired at the current slot")]
+    ExecutionWindowExpiredSlot,
+    
+    #[msg("Recipient account data is smaller than the configured minimum")]
+    RecipientTooSmall,
+    
+    #[msg("Migration window is closed")]
+    MigrationWindowClosed,
+    
+    #[msg("Counters have already been backfilled")]
+    AlreadyBackfilled,
+    
+    #[msg("Treasury holds nothing above its rent-exempt minimum")]
+    TreasuryEmpty,
+    
+    #[msg("Amount is not a multiple of the split's denomination")]
+    AmountNotDenominated,
+    
+    #[msg("Sender token account has not approved the split config as delegate")]
+    TokenDelegationMissing,
+    
+    #[msg("Delegated token allowance does not cover the amount")]
+    TokenDelegationInsufficient,
+    
+    #[msg("Split configs do not support this recipient count or memo length")]
+    UnsupportedConfigLayout,
+    
+    #[msg("Too many vaults in one sweep")]
+    SweepBatchTooLarge,
+    
+    #[msg("Each swept vault needs exactly one nonce")]
+    SweepAccountsMismatch,
+    
+    #[msg("Split has no oracle condition")]
+    NoOracleCondition,
+    
+    #[msg("Oracle account does not match the configured oracle")]
+    InvalidOracle,
+    
+    #[msg("Oracle value has not reached the release condition")]
+    OracleConditionNotMet,
+    
+    #[msg("Sender settings account is required to apply defaults")]
+    SenderSettingsMissing,
+    
+    #[msg("Config lifetime cannot be negative")]
+    InvalidConfigLifetime,
+    
+    #[msg("Split configuration has outlived the maximum lifetime")]
+    ConfigExpired,
+    
+    #[msg("Split configuration has not reached the maximum lifetime")]
+    ConfigNotExpired,
+    
+    #[msg("Cancel reason is reserved for another cancellation path")]
+    InvalidCancelReason,
+    
+    #[msg("Open execution requires an executor bond of at least the program minimum")]
+    ExecutorBondRequired,
+    
+    #[msg("Executor bond is still in its withdrawal cooldown")]
+    BondCooldownActive,
+    
+    #[msg("Executor's last execution of this config did not violate its quiet window")]
+    NoBondViolation,
+    
+    #[msg("Quiet window must end after it starts")]
+    InvalidQuietWindow,
+    
+    #[msg("Blackout windows are malformed or leave no time to execute")]
+    InvalidBlackoutWindow,
+    
+    #[msg("Execution is forbidden during a blackout window")]
+    ExecutionInBlackout,
+    
+    #[msg("Recipient's cumulative receipts differ from the expected total")]
+    TotalMismatch,
+    
+    #[msg("Shortfall policy must prioritize slot 1 or 2")]
+    InvalidShortfallPolicy,
+    
+    #[msg("This config requires a memo on every execution")]
+    ExecutionMemoRequired,
+    
+    #[msg("Execution memo is too long")]
+    ExecutionMemoTooLong,
+    
+    #[msg("Cascade target must be a native, pending config of the same sender")]
+    InvalidCascadeTarget,
+    
+    #[msg("Cascade revisits a config already in the chain")]
+    CascadeCycle,
+    
+    #[msg("Cascade exceeds the maximum depth")]
+    CascadeTooDeep,
+    
+    #[msg("A cascaded recipient account was not provided")]
+    CascadeAccountMissing,
+    
+    #[msg("Cascading configs only execute through execute_split")]
+    CascadeUnsupported,
+    
+    #[msg("Too many swap programs in the whitelist")]
+    TooManySwapPrograms,
+    
+    #[msg("Sender balance grew by less than the minimum swap output")]
+    SwapOutputTooSmall,
+    
+    #[msg("No whitelisted swap program ran before this instruction")]
+    SwapNotFound,
+    
+    #[msg("This config defers rent reclamation and needs the reclaim queue")]
+    ReclaimQueueRequired,
+    
+    #[msg("Reclaim queue is full; sweep it first")]
+    ReclaimQueueFull,
+    
+    #[msg("Accounts passed do not match the reclaim queue")]
+    ReclaimAccountsMismatch,
+    
+    #[msg("Execution is not allowed until the review period has passed")]
+    ReviewPeriodActive,
+    
+    #[msg("Protocol fee exceeds the maximum the signer accepted")]
+    FeeAboveMaximum,
+    
+    #[msg("Auto-swap requires a build with the auto-swap feature")]
+    AutoSwapDisabled,
+    
+    #[msg("Auto-swap slippage floor must be non-zero")]
+    InvalidSwapSpec,
+    
+    #[msg("No whitelisted swap route was given for an auto-swapped leg")]
+    AutoSwapUnavailable,
+    
+    #[msg("Swap route accounts do not match the leg")]
+    SwapRouteInvalid,
+    
+    #[msg("Swap venue did not spend exactly the leg's share")]
+    SwapInputMismatch,
+    
+    #[msg("This config updates recipient ledgers; pass both ledger accounts")]
+    RecipientLedgerRequired,
+    
+    #[msg("Recipient ledger still has pending claims")]
+    LedgerPending,
+    
+    #[msg("Cancellation is not allowed until the cancel cooldown has passed")]
+    CancelCooldownActive,
+    
+    #[msg("The initialized treasury is required to collect or refund the creation fee")]
+    TreasuryRequired,
+    
+    #[msg("No delegate has been proposed")]
+    NoPendingDelegate,
+    
+    #[msg("Only the proposed delegate can accept the role")]
+    UnauthorizedDelegate,
+    
+    #[msg("Ratio tiers must have strictly increasing thresholds and percentages between 1 and 99")]
+    InvalidRatioTiers,
+    
+    #[msg("Teardown takes between 1 and 24 accounts per call")]
+    TeardownBatchTooLarge,
+    
+    #[msg("Account is not one of the sender's closable program accounts")]
+    InvalidTeardownAccount,
+    
+    #[msg("Some configs still hold funds or a rent split; see the logs")]
+    TeardownBlocked,
+    
+    #[msg("Multi-recipient configs execute through execute_split_multi")]
+    MultiRecipientLayout,
+    
+    #[msg("Config uses an option the multi-recipient layout doesn't support")]
+    MultiRecipientUnsupported,
+    
+    #[msg("Recipient shares must be 2 to 8 distinct recipients totalling 10000 bps")]
+    InvalidRecipientShares,
+    
+    #[msg("Multi-recipient configs require their recipient set account")]
+    RecipientSetRequired,
+    
+    #[msg("Config does not track a history digest")]
+    HistoryDigestDisabled,
+    
+    #[msg("Configs tracking a history digest can't use this instruction")]
+    HistoryDigestUnsupported,
+    
+    #[msg("Config does not forward receipts sent to its address")]
+    ForwardingDisabled,
+    
+    #[msg("Destination is not the recipient's preferred token account")]
+    PreferenceViolation,
+    
+    #[msg("A batch executes between 1 and 8 configs")]
+    BatchTooLarge,
+    
+    #[msg("Each batch entry needs its writable config and two recipients")]
+    BatchAccountsMismatch,
+    
+    #[msg("Config uses an option batch execution doesn't support")]
+    BatchUnsupportedConfig,
+    
+    #[msg("An upgrade batch migrates between 1 and 24 configs")]
+    UpgradeBatchTooLarge,
+    
+    #[msg("Config address wasn't derived with this deployment's genesis guard")]
+    ClusterGuardMismatch,
+    
+    #[msg("Config has a staged split being committed")]
+    SplitStaged,
+    
+    #[msg("Config has no staged split to commit")]
+    SplitNotStaged,
+    
+    #[msg("Page recipients must continue the staged order")]
+    StagedPageMismatch,
+    
+    #[msg("Withholding needs 1 to 5000 bps, slot 1 or 2 and a destination other than the recipients")]
+    InvalidWithholding,
+    
+    #[msg("Withholding destination must be passed in the remaining accounts")]
+    WithholdingDestinationMissing,
+    
+    #[msg("Config withholds a share, which this instruction doesn't support")]
+    WithholdingUnsupported,
+    
+    #[msg("Config was executed earlier in this transaction; use execute_and_close_split")]
+    UseExecuteAndClose,
+    
+    #[msg("Config defers its rent to sweep_reclaim and can't close on execution")]
+    ReclaimDeferred,
+    
+    #[msg("Config address commits to its terms, which can't change")]
+    TermsCommitted,
+    
+    #[msg("Ratio ramp ends must be between 1 and 99 percent over a non-empty window")]
+    InvalidRatioRamp,
+    
+    #[msg("Recipient's claims are frozen")]
+    ClaimsFrozen,
+    
+    #[msg("Recipient's claims aren't frozen")]
+    ClaimsNotFrozen,
+    
+    #[msg("Key isn't the recovery key registered for a frozen slot")]
+    RecoveryKeyMismatch,
+    
+    #[msg("Both recipients must sign to verify their addresses")]
+    RecipientSignatureRequired,
+    
+    #[msg("Option makes shares depend on execution timing, which deterministic_only forbids")]
+    NonDeterministicOptionForbidden,
+    
+    #[msg("Approvers must be distinct, at most 8, and at least as many as required")]
+    InvalidApprovers,
+    
+    #[msg("Signer is not one of the config's approvers")]
+    NotAnApprover,
+    
+    #[msg("Execution needs more approvals")]
+    ApprovalsPending,
+    
+    #[msg("Approval thresholds need an execution path that updates the config")]
+    ApprovalsUnsupported,
+    
+    #[msg("Config has executed since the transaction was signed")]
+    StaleExecutionCount,
+    
+    #[msg("Config terms changed since the transaction was signed")]
+    TermsChangedSinceSigning,
+    
+    #[msg("Execution landed after its latest allowed time")]
+    ExecutionTooLate,
+    
+    #[msg("Cancel penalty must be at most 2500 bps and only on escrow configs")]
+    InvalidCancelPenalty,
+    
+    #[msg("Both recipients must be passed to receive the cancel penalty")]
+    PenaltyRecipientsRequired,
+    
+    #[msg("This requires an execution to have happened")]
+    NotYetExecuted,
+    
+    #[msg("Recipients that were already paid or are owed cannot be replaced")]
+    RecipientHistory,
+    
+    #[msg("Config uses a ratio table, which this instruction doesn't support")]
+    RatioTiersUnsupported,
+    
+    #[msg("Recipient set is too large for one transaction; stage the split instead")]
+    RecipientSetTooLarge,
+    
+    #[msg("Staged shares must match the config's recipients and basis points")]
+    StagedSharesMismatch,
+    
+    #[msg("Genesis guard is already set and can't be changed")]
+    GenesisGuardAlreadySet,
 }
\ No newline at end of file
@@ -0,0 +1,207 @@
+//! The interface builders must produce exactly what Anchor's generated client
+//! structs produce. Run with `cargo test --features interface`.
+#![cfg(feature = "interface")]
+
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use solsplit::interface::{self, mock, CommittedTerms, SplitAccounts};
+use solsplit::{RatioRamp, SplitOptions};
+
+fn split_accounts() -> SplitAccounts {
+    SplitAccounts {
+        sender: Pubkey::new_unique(),
+        recipient1: Pubkey::new_unique(),
+        recipient2: Pubkey::new_unique(),
+        nonce: 7,
+        genesis_guard: [0; 8],
+    }
+}
+
+#[test]
+fn initialize_split_matches_anchor_client() {
+    let accounts = split_accounts();
+    let ix = interface::initialize_split_ix(&accounts, 60, 40);
+
+    let expected_metas = solsplit::accounts::InitializeSplit {
+        split_config: interface::split_config_address(&accounts.sender, accounts.nonce),
+        sender: accounts.sender,
+        recipient1: accounts.recipient1,
+        recipient2: accounts.recipient2,
+        system_program: system_program::ID,
+        program_config: interface::program_config_address(),
+        treasury: interface::treasury_address(),
+        outbox: None,
+        sender_settings: None,
+        fee_exemption: None,
+        client_registry: None,
+    }
+    .to_account_metas(None);
+    let expected_data = solsplit::instruction::InitializeSplit {
+        recipient1_percentage: 60,
+        recipient2_percentage: 40,
+        nonce: 7,
+        client_id: None,
+    }
+    .data();
+
+    assert_eq!(ix.program_id, solsplit::ID);
+    assert_eq!(ix.accounts, expected_metas);
+    assert_eq!(ix.data, expected_data);
+}
+
+#[test]
+fn execute_split_matches_anchor_client() {
+    let accounts = split_accounts();
+    let ix = interface::execute_split_ix(&accounts, u64::MAX, 99);
+
+    let expected_metas = solsplit::accounts::ExecuteSplit {
+        split_config: interface::split_config_address(&accounts.sender, accounts.nonce),
+        sender: accounts.sender,
+        recipient1: accounts.recipient1,
+        recipient2: accounts.recipient2,
+        system_program: system_program::ID,
+        program_config: interface::program_config_address(),
+        outbox: None,
+        history: None,
+        reclaim_queue: None,
+        recipient1_ledger: None,
+        recipient2_ledger: None,
+        client_registry: None,
+    }
+    .to_account_metas(None);
+    let expected_data = solsplit::instruction::ExecuteSplit {
+        amount: u64::MAX,
+        valid_until_slot: 99,
+        client_id: None,
+        preconditions: None,
+    }
+    .data();
+
+    assert_eq!(ix.accounts, expected_metas);
+    assert_eq!(ix.data, expected_data);
+}
+
+#[test]
+fn execute_from_deposit_matches_anchor_client() {
+    let accounts = split_accounts();
+    let executor = Pubkey::new_unique();
+    let ix = interface::execute_from_deposit_ix(&accounts, &executor, 1_000_000);
+
+    let expected_metas = solsplit::accounts::ExecuteFromDeposit {
+        split_config: interface::split_config_address(&accounts.sender, accounts.nonce),
+        executor,
+        recipient1: accounts.recipient1,
+        recipient2: accounts.recipient2,
+        program_config: interface::program_config_address(),
+        outbox: None,
+        executor_bond: None,
+        recipient1_ledger: None,
+        recipient2_ledger: None,
+    }
+    .to_account_metas(None);
+    let expected_data = solsplit::instruction::ExecuteFromDeposit { amount: 1_000_000 }.data();
+
+    assert_eq!(ix.accounts, expected_metas);
+    assert_eq!(ix.data, expected_data);
+}
+
+#[test]
+fn mock_plans_like_the_program() {
+    let config = mock::config(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 60);
+
+    let terms = mock::plan(&config, 1_000_001, 0).unwrap();
+    assert_eq!((terms.amount1, terms.amount2), (600_000, 400_001));
+    assert_eq!(terms.overflow, 0);
+    assert_eq!(terms.blocked_until, 0);
+
+    assert_eq!(mock::shares(u64::MAX, 99).unwrap().0, (u64::MAX as u128 * 99 / 100) as u64);
+    assert!(mock::plan(&config, 1, 0).is_err());
+}
+
+#[test]
+fn guarded_initialize_split_derives_cluster_unique_address() {
+    let accounts = SplitAccounts {
+        genesis_guard: [1, 2, 3, 4, 5, 6, 7, 8],
+        ..split_accounts()
+    };
+    let ix = interface::initialize_split_ix(&accounts, 60, 40);
+
+    let guarded = interface::guarded_split_config_address(&accounts.sender, 7, accounts.genesis_guard);
+    assert_eq!(ix.accounts[0].pubkey, guarded);
+    assert_ne!(guarded, interface::split_config_address(&accounts.sender, 7));
+    assert_eq!(
+        interface::guarded_split_config_address(&accounts.sender, 7, [0; 8]),
+        interface::split_config_address(&accounts.sender, 7)
+    );
+}
+
+#[test]
+fn funder_verifies_committed_terms_from_the_address() {
+    let sender = Pubkey::new_unique();
+    let terms = CommittedTerms {
+        recipient1: Pubkey::new_unique(),
+        recipient2: Pubkey::new_unique(),
+        recipient1_percentage: 70,
+        recipient2_percentage: 30,
+        options: SplitOptions {
+            escrow: true,
+            ..SplitOptions::default()
+        },
+    };
+    let address = interface::committed_split_config_address(&sender, 7, [0; 8], &terms);
+
+    assert!(interface::verify_committed_terms(&address, &sender, 7, [0; 8], &terms));
+    assert_ne!(address, interface::split_config_address(&sender, 7));
+
+    let wrong_split = CommittedTerms {
+        recipient1_percentage: 30,
+        recipient2_percentage: 70,
+        options: SplitOptions {
+            escrow: true,
+            ..SplitOptions::default()
+        },
+        ..terms
+    };
+    assert!(!interface::verify_committed_terms(&address, &sender, 7, [0; 8], &wrong_split));
+
+    let wrong_flags = CommittedTerms {
+        recipient1_percentage: 70,
+        recipient2_percentage: 30,
+        options: SplitOptions::default(),
+        ..wrong_split
+    };
+    assert!(!interface::verify_committed_terms(&address, &sender, 7, [0; 8], &wrong_flags));
+}
+
+#[test]
+fn deterministic_plan_ignores_the_clock() {
+    let mut config = mock::config(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 60);
+    config.deterministic_only = true;
+
+    let early = mock::plan(&config, 1_000_001, 0).unwrap();
+    let late = mock::plan(&config, 1_000_001, 1_700_000_000).unwrap();
+    assert_eq!((early.amount1, early.amount2), (late.amount1, late.amount2));
+
+    // A ramp, which deterministic configs can't carry, moves with the clock
+    config.deterministic_only = false;
+    config.ratio_ramp = Some(RatioRamp {
+        start_recipient1_percentage: 80,
+        end_recipient1_percentage: 50,
+        ramp_start: 0,
+        ramp_end: 1_000,
+    });
+    assert_ne!(
+        mock::plan(&config, 1_000_001, 0).unwrap().amount1,
+        mock::plan(&config, 1_000_001, 1_000).unwrap().amount1
+    );
+}
+
+#[test]
+fn never_executed_config_has_no_execution_time() {
+    let mut config = mock::config(Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), 60);
+    assert_eq!(config.executed_at, 0);
+    assert_eq!(config.executed_at_checked(), None);
+
+    config.mark_executed(1_700_000_000);
+    assert_eq!(config.executed_at_checked(), Some(1_700_000_000));
+}
@@ -2,6 +2,21 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Solsplit } from "../target/types/solsplit";
 import { assert } from "chai";
+import { createHash } from "crypto";
+import {
+  approve,
+  closeAccount,
+  createAccount,
+  createAssociatedTokenAccount,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  NATIVE_MINT,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 
 describe("solsplit - Comprehensive Security Tests", () => {
   const provider = anchor.AnchorProvider.env();
@@ -15,23 +30,144 @@ describe("solsplit - Comprehensive Security Tests", () => {
 
   let nonce = 0;
 
-  const getSplitConfigPDA = (senderKey: anchor.web3.PublicKey, nonceValue: number) => {
+  // Nonce bytes a config is derived under, mixed with the deployment's genesis guard if set
+  const guardedNonceBytes = (nonceValue: number, genesisGuard?: number[]) => {
+    const nonceBytes = new anchor.BN(nonceValue).toArrayLike(Buffer, "le", 8);
+    if (!genesisGuard || genesisGuard.every((byte) => byte === 0)) return nonceBytes;
+    return createHash("sha256")
+      .update(Buffer.from("genesis_guard"))
+      .update(Buffer.from(genesisGuard))
+      .update(nonceBytes)
+      .digest()
+      .subarray(0, 8);
+  };
+
+  const getSplitConfigPDA = (
+    senderKey: anchor.web3.PublicKey,
+    nonceValue: number,
+    genesisGuard?: number[]
+  ) => {
     return anchor.web3.PublicKey.findProgramAddressSync(
       [
         Buffer.from("split_config"), 
         senderKey.toBuffer(),
-        new anchor.BN(nonceValue).toArrayLike(Buffer, "le", 8)
+        guardedNonceBytes(nonceValue, genesisGuard)
       ],
       program.programId
     );
   };
 
+  const EVENT_SCHEMA_VERSION = 10;
+
+  const getEvents = async (signature: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    return Array.from(parser.parseLogs(tx.meta.logMessages));
+  };
+
+  const defaultOptions = () => ({
+    senderMinBalance: new anchor.BN(0),
+    escrow: false,
+    acceptanceDeadline: new anchor.BN(0),
+    openDeposits: false,
+    allowedDepositors: [],
+    recipient1Delivery: { native: {} } as any,
+    recipient2Delivery: { native: {} } as any,
+    rotateRemainder: false,
+    claimMode: false,
+    agreementHash: new Array(32).fill(0),
+    rentSplit: null,
+    minRecipientDataLen: new anchor.BN(0),
+    denomination: new anchor.BN(1),
+    closeResidualToRecipients: false,
+    oracleCondition: null,
+    anyoneCanExecute: false,
+    executorQuietWindow: null,
+    recipient1Cap: new anchor.BN(0),
+    recipient2Cap: new anchor.BN(0),
+    blackoutWindows: [],
+    shortfallPolicy: { failEntirely: {} } as any,
+    allowPartial: false,
+    requireExecutionMemo: false,
+    cascadeTarget1: null,
+    deferReclaim: false,
+    reviewPeriodSeconds: 0,
+    recipient1AutoSwap: null,
+    recipient2AutoSwap: null,
+    swapFallbackToOriginal: false,
+    updateRecipientLedger: false,
+    cancelCooldownSeconds: 0,
+    ratioTiers: [],
+    trackHistoryDigest: false,
+    forwardReceipts: false,
+    withholding: null,
+    compactEvents: false,
+    ratioRamp: null,
+    externalRef: null,
+    privateRecipients: false,
+    deterministicOnly: false,
+    approvers: [],
+    approvalsRequired: 0,
+    clientId: null,
+    cancelPenaltyBps: 0,
+  });
+
+  const [programConfigPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("program_config")],
+    program.programId
+  );
+
+  // The program config is global, so tests share one created by the upgrade authority
+  const ensureProgramConfig = async () => {
+    const existing = await program.account.programConfig.fetchNullable(programConfigPda);
+    if (existing) return;
+
+    const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
+    await program.methods
+      .initializeProgramConfig()
+      .accounts({
+        programConfig: programConfigPda,
+        admin: sender.publicKey,
+        program: program.programId,
+        programData,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+  };
+
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  const createSplit = async (p1: number, p2: number) => {
+    const splitNonce = nonce++;
+    const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+    await program.methods
+      .initializeSplit(p1, p2, new anchor.BN(splitNonce), null)
+      .accounts({
+        splitConfig: pda,
+        sender: sender.publicKey,
+        recipient1: recipient1.publicKey,
+        recipient2: recipient2.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    return pda;
+  };
+
   describe("Initialization Tests", () => {
     it("Initializes split configuration with valid percentages", async () => {
       const [splitConfigPDA] = getSplitConfigPDA(sender.publicKey, nonce);
 
       const tx = await program.methods
-        .initializeSplit(60, 40, new anchor.BN(nonce))
+        .initializeSplit(60, 40, new anchor.BN(nonce), null)
         .accounts({
           splitConfig: splitConfigPDA,
           sender: sender.publicKey,
@@ -58,7 +194,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
 
       try {
         await program.methods
-          .initializeSplit(50, 30, new anchor.BN(nonce)) // 80% total
+          .initializeSplit(50, 30, new anchor.BN(nonce), null) // 80% total
           .accounts({
             splitConfig: splitConfigPDA,
             sender: sender.publicKey,
@@ -79,7 +215,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
 
       try {
         await program.methods
-          .initializeSplit(0, 100, new anchor.BN(nonce))
+          .initializeSplit(0, 100, new anchor.BN(nonce), null)
           .accounts({
             splitConfig: splitConfigPDA,
             sender: sender.publicKey,
@@ -100,7 +236,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
 
       try {
         await program.methods
-          .initializeSplit(50, 50, new anchor.BN(nonce))
+          .initializeSplit(50, 50, new anchor.BN(nonce), null)
           .accounts({
             splitConfig: splitConfigPDA,
             sender: sender.publicKey,
@@ -121,7 +257,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
 
       try {
         await program.methods
-          .initializeSplit(50, 50, new anchor.BN(nonce))
+          .initializeSplit(50, 50, new anchor.BN(nonce), null)
           .accounts({
             splitConfig: splitConfigPDA,
             sender: sender.publicKey,
@@ -147,7 +283,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
       [executionPDA] = getSplitConfigPDA(sender.publicKey, executionNonce);
 
       await program.methods
-        .initializeSplit(60, 40, new anchor.BN(executionNonce))
+        .initializeSplit(60, 40, new anchor.BN(executionNonce), null)
         .accounts({
           splitConfig: executionPDA,
           sender: sender.publicKey,
@@ -169,7 +305,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
       );
 
       const tx = await program.methods
-        .executeSplit(amount)
+        .executeSplit(amount, new anchor.BN(0), null, null)
         .accounts({
           splitConfig: executionPDA,
           sender: sender.publicKey,
@@ -209,7 +345,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
     it("Prevents replay attacks", async () => {
       try {
         await program.methods
-          .executeSplit(new anchor.BN(500_000_000))
+          .executeSplit(new anchor.BN(500_000_000), new anchor.BN(0), null, null)
           .accounts({
             splitConfig: executionPDA,
             sender: sender.publicKey,
@@ -230,7 +366,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
       const [smallPDA] = getSplitConfigPDA(sender.publicKey, smallNonce);
 
       await program.methods
-        .initializeSplit(50, 50, new anchor.BN(smallNonce))
+        .initializeSplit(50, 50, new anchor.BN(smallNonce), null)
         .accounts({
           splitConfig: smallPDA,
           sender: sender.publicKey,
@@ -242,7 +378,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
 
       try {
         await program.methods
-          .executeSplit(new anchor.BN(500)) // Below 1000 minimum
+          .executeSplit(new anchor.BN(500), new anchor.BN(0), null, null) // Below 1000 minimum
           .accounts({
             splitConfig: smallPDA,
             sender: sender.publicKey,
@@ -263,7 +399,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
       const [roundingPDA] = getSplitConfigPDA(sender.publicKey, roundingNonce);
 
       await program.methods
-        .initializeSplit(33, 67, new anchor.BN(roundingNonce))
+        .initializeSplit(33, 67, new anchor.BN(roundingNonce), null)
         .accounts({
           splitConfig: roundingPDA,
           sender: sender.publicKey,
@@ -280,7 +416,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
       const senderBefore = await provider.connection.getBalance(sender.publicKey);
 
       await program.methods
-        .executeSplit(amount)
+        .executeSplit(amount, new anchor.BN(0), null, null)
         .accounts({
           splitConfig: roundingPDA,
           sender: sender.publicKey,
@@ -318,7 +454,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
       [cancelPDA] = getSplitConfigPDA(sender.publicKey, cancelNonce);
 
       await program.methods
-        .initializeSplit(70, 30, new anchor.BN(cancelNonce))
+        .initializeSplit(70, 30, new anchor.BN(cancelNonce), null)
         .accounts({
           splitConfig: cancelPDA,
           sender: sender.publicKey,
@@ -331,7 +467,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
 
     it("Cancels split before execution", async () => {
       const tx = await program.methods
-        .cancelSplit()
+        .cancelSplit({ userRequested: {} })
         .accounts({
           splitConfig: cancelPDA,
           sender: sender.publicKey,
@@ -352,7 +488,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
     it("Fails to cancel after execution", async () => {
       // Execute first
       await program.methods
-        .executeSplit(new anchor.BN(10_000_000))
+        .executeSplit(new anchor.BN(10_000_000), new anchor.BN(0), null, null)
         .accounts({
           splitConfig: cancelPDA,
           sender: sender.publicKey,
@@ -365,7 +501,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
       // Try to cancel
       try {
         await program.methods
-          .cancelSplit()
+          .cancelSplit({ userRequested: {} })
           .accounts({
             splitConfig: cancelPDA,
             sender: sender.publicKey,
@@ -388,7 +524,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
       [closePDA] = getSplitConfigPDA(sender.publicKey, closeNonce);
 
       await program.methods
-        .initializeSplit(80, 20, new anchor.BN(closeNonce))
+        .initializeSplit(80, 20, new anchor.BN(closeNonce), null)
         .accounts({
           splitConfig: closePDA,
           sender: sender.publicKey,
@@ -402,7 +538,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
     it("Closes split after execution to reclaim rent", async () => {
       // Execute first
       await program.methods
-        .executeSplit(new anchor.BN(50_000_000))
+        .executeSplit(new anchor.BN(50_000_000), new anchor.BN(0), null, null)
         .accounts({
           splitConfig: closePDA,
           sender: sender.publicKey,
@@ -475,7 +611,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
       [authPDA] = getSplitConfigPDA(sender.publicKey, authNonce);
 
       await program.methods
-        .initializeSplit(50, 50, new anchor.BN(authNonce))
+        .initializeSplit(50, 50, new anchor.BN(authNonce), null)
         .accounts({
           splitConfig: authPDA,
           sender: sender.publicKey,
@@ -491,7 +627,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
 
       try {
         await program.methods
-          .executeSplit(new anchor.BN(10_000_000))
+          .executeSplit(new anchor.BN(10_000_000), new anchor.BN(0), null, null)
           .accounts({
             splitConfig: unauthorizedPDA,
             sender: unauthorizedUser.publicKey,
@@ -519,7 +655,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
 
       // Create first split
       await program.methods
-        .initializeSplit(25, 75, new anchor.BN(nonce1))
+        .initializeSplit(25, 75, new anchor.BN(nonce1), null)
         .accounts({
           splitConfig: pda1,
           sender: sender.publicKey,
@@ -531,7 +667,7 @@ describe("solsplit - Comprehensive Security Tests", () => {
 
       // Create second split
       await program.methods
-        .initializeSplit(75, 25, new anchor.BN(nonce2))
+        .initializeSplit(75, 25, new anchor.BN(nonce2), null)
         .accounts({
           splitConfig: pda2,
           sender: sender.publicKey,
@@ -550,4 +686,8277 @@ describe("solsplit - Comprehensive Security Tests", () => {
       assert.notEqual(config1.nonce.toNumber(), config2.nonce.toNumber());
     });
   });
-});
\ No newline at end of file
+
+  describe("Topic Tests", () => {
+    it("Changes the topic and carries it in subsequent events", async () => {
+      const topicNonce = nonce++;
+      const [topicPDA] = getSplitConfigPDA(sender.publicKey, topicNonce);
+      const newTopic = Array.from(Buffer.from("payroll\0"));
+
+      await program.methods
+        .initializeSplit(50, 50, new anchor.BN(topicNonce), null)
+        .accounts({
+          splitConfig: topicPDA,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const setTx = await program.methods
+        .setTopic(newTopic)
+        .accounts({
+          splitConfig: topicPDA,
+          sender: sender.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const [changed] = await getEvents(setTx);
+      assert.equal(changed.name, "topicChanged");
+      assert.deepEqual(changed.data.oldTopic, new Array(8).fill(0));
+      assert.deepEqual(changed.data.newTopic, newTopic);
+
+      const splitConfig = await program.account.splitConfig.fetch(topicPDA);
+      assert.deepEqual(splitConfig.topic, newTopic);
+
+      const executeTx = await program.methods
+        .executeSplit(new anchor.BN(10_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: topicPDA,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const executed = (await getEvents(executeTx)).find(
+        (e) => e.name === "splitExecuted"
+      );
+      assert.deepEqual(executed.data.topic, newTopic);
+    });
+
+    it("Fails when a non-sender changes the topic", async () => {
+      const topicNonce = nonce++;
+      const [topicPDA] = getSplitConfigPDA(sender.publicKey, topicNonce);
+      const stranger = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .initializeSplit(50, 50, new anchor.BN(topicNonce), null)
+        .accounts({
+          splitConfig: topicPDA,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .setTopic(Array.from(Buffer.from("other\0\0\0")))
+          .accounts({
+            splitConfig: topicPDA,
+            sender: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ConstraintSeeds");
+      }
+    });
+  });
+
+  describe("Routing Tests", () => {
+    const [routeTablePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("route_table"), sender.publicKey.toBuffer()],
+      program.programId
+    );
+    const routeId = Array.from(Buffer.alloc(16, 7));
+
+    const registerRoute = (id: number[], splitConfig: anchor.web3.PublicKey) =>
+      program.methods
+        .registerRoute(id)
+        .accounts({
+          routeTable: routeTablePDA,
+          splitConfig,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    const executeRouted = (id: number[], splitConfig: anchor.web3.PublicKey) =>
+      program.methods
+        .executeRouted(id, new anchor.BN(10_000_000))
+        .accounts({
+          routeTable: routeTablePDA,
+          execute: {
+            splitConfig,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          },
+        })
+        .rpc({ commitment: "confirmed" });
+
+    it("Registers a route and executes through it", async () => {
+      const pda = await createSplit(60, 40);
+
+      const registerTx = await registerRoute(routeId, pda);
+      const [registered] = await getEvents(registerTx);
+      assert.equal(registered.name, "routeRegistered");
+      assert.equal(registered.data.splitConfig.toBase58(), pda.toBase58());
+
+      const executeTx = await executeRouted(routeId, pda);
+      const executed = (await getEvents(executeTx)).find(
+        (e) => e.name === "splitExecuted"
+      );
+      assert.deepEqual(executed.data.routeId, routeId);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.executed, true);
+    });
+
+    it("Re-points an existing route to a new config", async () => {
+      const pda = await createSplit(70, 30);
+
+      const repointTx = await registerRoute(routeId, pda);
+      const [repointed] = await getEvents(repointTx);
+      assert.equal(repointed.name, "routeRepointed");
+      assert.equal(repointed.data.newSplitConfig.toBase58(), pda.toBase58());
+
+      const routeTable = await program.account.routeTable.fetch(routeTablePDA);
+      assert.equal(routeTable.routes.length, 1);
+
+      await executeRouted(routeId, pda);
+    });
+
+    it("Fails with an unknown route ID", async () => {
+      const pda = await createSplit(50, 50);
+
+      try {
+        await executeRouted(Array.from(Buffer.alloc(16, 9)), pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "RouteNotFound");
+      }
+    });
+
+    it("Fails when the config does not match the route", async () => {
+      const pda = await createSplit(50, 50);
+
+      try {
+        await executeRouted(routeId, pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "RouteConfigMismatch");
+      }
+    });
+  });
+
+  describe("Sender Minimum Balance Tests", () => {
+    const TX_FEE = 5000;
+    const minBalance = anchor.web3.LAMPORTS_PER_SOL;
+    let bufferedSender: anchor.web3.Keypair;
+
+    const createBufferedSplit = async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(bufferedSender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(50, 50, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          senderMinBalance: new anchor.BN(minBalance),
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: bufferedSender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([bufferedSender])
+        .rpc({ commitment: "confirmed" });
+
+      return pda;
+    };
+
+    const executeBuffered = (pda: anchor.web3.PublicKey, amount: number) =>
+      program.methods
+        .executeSplit(new anchor.BN(amount), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: bufferedSender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([bufferedSender])
+        .rpc({ commitment: "confirmed" });
+
+    before(async () => {
+      bufferedSender = anchor.web3.Keypair.generate();
+      const airdropSig = await provider.connection.requestAirdrop(
+        bufferedSender.publicKey,
+        3 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+    });
+
+    it("Rejects an execution one lamport past the buffer", async () => {
+      const pda = await createBufferedSplit();
+      const balance = await provider.connection.getBalance(
+        bufferedSender.publicKey,
+        "confirmed"
+      );
+
+      try {
+        await executeBuffered(pda, balance - TX_FEE - minBalance + 1);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "SenderBalanceBelowMinimum");
+      }
+    });
+
+    it("Allows an execution landing exactly on the buffer", async () => {
+      const pda = await createBufferedSplit();
+      const balance = await provider.connection.getBalance(
+        bufferedSender.publicKey,
+        "confirmed"
+      );
+
+      await executeBuffered(pda, balance - TX_FEE - minBalance);
+
+      const balanceAfter = await provider.connection.getBalance(
+        bufferedSender.publicKey,
+        "confirmed"
+      );
+      assert.equal(balanceAfter, minBalance);
+    });
+  });
+
+  describe("Initialization Rent Tests", () => {
+    it("Reports the rent paid at initialization", async () => {
+      const rentNonce = nonce++;
+      const [rentPDA] = getSplitConfigPDA(sender.publicKey, rentNonce);
+
+      const senderBefore = await provider.connection.getBalance(
+        sender.publicKey,
+        "confirmed"
+      );
+
+      const tx = await program.methods
+        .initializeSplit(50, 50, new anchor.BN(rentNonce), null)
+        .accounts({
+          splitConfig: rentPDA,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const senderAfter = await provider.connection.getBalance(
+        sender.publicKey,
+        "confirmed"
+      );
+      const configLamports = await provider.connection.getBalance(
+        rentPDA,
+        "confirmed"
+      );
+
+      const [initialized] = await getEvents(tx);
+      assert.equal(initialized.name, "splitInitialized");
+      assert.equal(initialized.data.rentCosts.length, 1);
+      assert.equal(
+        initialized.data.rentCosts[0].account.toBase58(),
+        rentPDA.toBase58()
+      );
+      assert.equal(initialized.data.rentCosts[0].refundable, true);
+      assert.equal(
+        initialized.data.rentCosts[0].lamports.toNumber(),
+        configLamports
+      );
+      assert.equal(
+        initialized.data.totalRent.toNumber(),
+        senderBefore - senderAfter - 5000
+      );
+    });
+  });
+
+  describe("Escrow Acceptance Tests", () => {
+    const createEscrowSplit = async (acceptanceDeadline: number) => {
+      const escrowNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, escrowNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(escrowNonce), {
+          ...defaultOptions(),
+          escrow: true,
+          acceptanceDeadline: new anchor.BN(acceptanceDeadline),
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .depositToSplit(new anchor.BN(1_000_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const confirm = (pda: anchor.web3.PublicKey, recipient: anchor.web3.Keypair) =>
+      program.methods
+        .confirmAndRelease()
+        .accounts({
+          splitConfig: pda,
+          recipient: recipient.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .signers([recipient])
+        .rpc();
+
+    it("Releases the escrow once both recipients confirm", async () => {
+      const pda = await createEscrowSplit(Math.floor(Date.now() / 1000) + 3600);
+
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+
+      await confirm(pda, recipient1);
+
+      let splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.recipient1Accepted, true);
+      assert.equal(splitConfig.executed, false);
+
+      await confirm(pda, recipient2);
+
+      splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.executed, true);
+      assert.equal(splitConfig.depositedAmount.toNumber(), 0);
+
+      const recipient1After = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2After = await provider.connection.getBalance(recipient2.publicKey);
+      assert.equal(recipient1After - recipient1Before, 600_000_000);
+      assert.equal(recipient2After - recipient2Before, 400_000_000);
+    });
+
+    it("Refunds the sender after the deadline without both acceptances", async () => {
+      const pda = await createEscrowSplit(Math.floor(Date.now() / 1000) + 3);
+
+      await confirm(pda, recipient1);
+
+      try {
+        await program.methods
+          .refundEscrow()
+          .accounts({ splitConfig: pda, sender: sender.publicKey })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "RefundNotAvailable");
+      }
+
+      await sleep(5000);
+
+      try {
+        await confirm(pda, recipient2);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "AcceptanceDeadlinePassed");
+      }
+
+      const senderBefore = await provider.connection.getBalance(sender.publicKey);
+
+      await program.methods
+        .refundEscrow()
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      const senderAfter = await provider.connection.getBalance(sender.publicKey);
+      assert.equal(senderAfter - senderBefore, 1_000_000_000 - 5000);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.depositedAmount.toNumber(), 0);
+      assert.equal(splitConfig.executed, false);
+    });
+
+    it("Rejects direct execution of an escrow config", async () => {
+      const pda = await createEscrowSplit(0);
+
+      try {
+        await program.methods
+          .executeSplit(new anchor.BN(10_000_000), new anchor.BN(0), null, null)
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "EscrowConfig");
+      }
+    });
+  });
+
+  describe("Allowed Depositor Tests", () => {
+    const depositor = anchor.web3.Keypair.generate();
+    const stranger = anchor.web3.Keypair.generate();
+
+    before(async () => {
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          ...[depositor, stranger].map((funder) =>
+            anchor.web3.SystemProgram.transfer({
+              fromPubkey: sender.publicKey,
+              toPubkey: funder.publicKey,
+              lamports: 100_000_000,
+            })
+          )
+        )
+      );
+    });
+
+    const createSplit = async (options: object) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          escrow: true,
+          ...options,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      return pda;
+    };
+
+    // The funder signs as the instruction's `sender` account
+    const deposit = (pda: anchor.web3.PublicKey, funder: anchor.web3.Keypair) =>
+      program.methods
+        .depositToSplit(new anchor.BN(1_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: funder.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([funder])
+        .rpc();
+
+    const expectRejected = async (pda: anchor.web3.PublicKey, funder: anchor.web3.Keypair) => {
+      try {
+        await deposit(pda, funder);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "DepositorNotAllowed");
+      }
+    };
+
+    it("Accepts listed depositors and rejects everyone else", async () => {
+      const pda = await createSplit({ allowedDepositors: [depositor.publicKey] });
+
+      await deposit(pda, depositor);
+      await expectRejected(pda, stranger);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.depositedAmount.toNumber(), 1_000_000);
+    });
+
+    it("Leaves an empty list sender-only unless deposits are open", async () => {
+      await expectRejected(await createSplit({}), stranger);
+
+      const open = await createSplit({ openDeposits: true });
+      await deposit(open, stranger);
+    });
+
+    it("Rejects outside depositors on non-escrow configs", async () => {
+      for (const options of [{ openDeposits: true }, { allowedDepositors: [depositor.publicKey] }]) {
+        try {
+          await createSplit({ escrow: false, ...options });
+          assert.fail("Should have failed");
+        } catch (err) {
+          assert.include(err.toString(), "IncompatibleOptions");
+        }
+      }
+    });
+
+    it("Applies list changes to later deposits only", async () => {
+      const pda = await createSplit({});
+      await expectRejected(pda, depositor);
+
+      const added = await program.methods
+        .addAllowedDepositor(depositor.publicKey)
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc({ commitment: "confirmed" });
+      const allowed = (await getEvents(added)).find((e) => e.name === "depositorAllowed");
+      assert.isTrue(allowed.data.depositor.equals(depositor.publicKey));
+      await deposit(pda, depositor);
+
+      const removed = await program.methods
+        .removeAllowedDepositor(depositor.publicKey)
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc({ commitment: "confirmed" });
+      assert.isDefined((await getEvents(removed)).find((e) => e.name === "depositorRemoved"));
+      await expectRejected(pda, depositor);
+
+      // The earlier deposit stays in the vault
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.depositedAmount.toNumber(), 1_000_000);
+    });
+
+    it("Refunds a depositor's deposit to the sender", async () => {
+      const pda = await createSplit({
+        allowedDepositors: [depositor.publicKey],
+        acceptanceDeadline: new anchor.BN(Math.floor(Date.now() / 1000) + 3),
+      });
+      await deposit(pda, depositor);
+      await sleep(5000);
+
+      const senderBefore = await provider.connection.getBalance(sender.publicKey);
+      const depositorBefore = await provider.connection.getBalance(depositor.publicKey);
+
+      await program.methods
+        .refundEscrow()
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      const senderAfter = await provider.connection.getBalance(sender.publicKey);
+      assert.equal(senderAfter - senderBefore, 1_000_000 - 5000);
+      assert.equal(await provider.connection.getBalance(depositor.publicKey), depositorBefore);
+    });
+  });
+
+  describe("Gross For Net Tests", () => {
+    it("Computes the gross that round-trips to the requested nets", async () => {
+      const pda = await createSplit(60, 40);
+
+      const gross = await program.methods
+        .computeGrossForNet(new anchor.BN(600_000), new anchor.BN(400_000))
+        .accounts({ splitConfig: pda })
+        .view();
+      assert.equal(gross.toNumber(), 1_000_000);
+
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+
+      await program.methods
+        .executeSplit(gross, new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const recipient1After = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2After = await provider.connection.getBalance(recipient2.publicKey);
+      assert.equal(recipient1After - recipient1Before, 600_000);
+      assert.equal(recipient2After - recipient2Before, 400_000);
+    });
+
+    it("Rejects nets inconsistent with the ratio", async () => {
+      const pda = await createSplit(60, 40);
+
+      try {
+        await program.methods
+          .computeGrossForNet(new anchor.BN(600_000), new anchor.BN(500_000))
+          .accounts({ splitConfig: pda })
+          .view();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "NetAmountsInconsistent");
+      }
+    });
+  });
+
+  describe("Outbox Tests", () => {
+    const [outboxPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("outbox"), sender.publicKey.toBuffer()],
+      program.programId
+    );
+    let escrowPDA: anchor.web3.PublicKey;
+
+    const deposit = () =>
+      program.methods
+        .depositToSplit(new anchor.BN(1))
+        .accounts({
+          splitConfig: escrowPDA,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          outbox: outboxPDA,
+        })
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initOutbox()
+        .accounts({
+          outbox: outboxPDA,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const escrowNonce = nonce++;
+      [escrowPDA] = getSplitConfigPDA(sender.publicKey, escrowNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(50, 50, new anchor.BN(escrowNonce), {
+          ...defaultOptions(),
+          escrow: true,
+        })
+        .accounts({
+          splitConfig: escrowPDA,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          outbox: outboxPDA,
+        })
+        .rpc();
+    });
+
+    it("Records lifecycle transitions", async () => {
+      await deposit();
+
+      const outbox = await program.account.outbox.fetch(outboxPDA);
+      assert.equal(outbox.records.length, 2);
+      assert.deepEqual(outbox.records[0].eventType, { initialized: {} });
+      assert.deepEqual(outbox.records[1].eventType, { deposited: {} });
+      assert.equal(outbox.records[1].config.toBase58(), escrowPDA.toBase58());
+      assert.equal(outbox.records[1].amount.toNumber(), 1);
+      assert.equal(outbox.records[1].seq.toNumber(), 2);
+      assert.equal(outbox.nextSeq.toNumber(), 3);
+    });
+
+    it("Wraps around and counts overwritten unacknowledged records", async () => {
+      // 2 records so far; 69 more leaves 71 written into 64 slots
+      for (let i = 0; i < 69; i++) {
+        await deposit();
+      }
+
+      const outbox = await program.account.outbox.fetch(outboxPDA);
+      assert.equal(outbox.records.length, 64);
+      assert.equal(outbox.nextSeq.toNumber(), 72);
+      assert.equal(outbox.lostEvents.toNumber(), 7);
+      // Seq 71 landed in the slot that held seq 7
+      assert.equal(outbox.records[6].seq.toNumber(), 71);
+    });
+
+    it("Does not count acknowledged records as lost", async () => {
+      await program.methods
+        .ackOutbox(new anchor.BN(71))
+        .accounts({ outbox: outboxPDA, sender: sender.publicKey })
+        .rpc();
+
+      await deposit();
+
+      const outbox = await program.account.outbox.fetch(outboxPDA);
+      assert.equal(outbox.ackedSeq.toNumber(), 71);
+      assert.equal(outbox.lostEvents.toNumber(), 7);
+    });
+
+    it("Rejects acknowledging unwritten sequence numbers", async () => {
+      try {
+        await program.methods
+          .ackOutbox(new anchor.BN(1_000))
+          .accounts({ outbox: outboxPDA, sender: sender.publicKey })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidAckSequence");
+      }
+    });
+
+    it("Closes the outbox", async () => {
+      await program.methods
+        .closeOutbox()
+        .accounts({ outbox: outboxPDA, sender: sender.publicKey })
+        .rpc();
+
+      const info = await provider.connection.getAccountInfo(outboxPDA);
+      assert.isNull(info);
+    });
+  });
+
+  describe("Event Schema Tests", () => {
+    it("Stamps the current schema version on lifecycle events", async () => {
+      const schemaNonce = nonce++;
+      const [schemaPDA] = getSplitConfigPDA(sender.publicKey, schemaNonce);
+
+      const initTx = await program.methods
+        .initializeSplit(50, 50, new anchor.BN(schemaNonce), null)
+        .accounts({
+          splitConfig: schemaPDA,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const executeTx = await program.methods
+        .executeSplit(new anchor.BN(10_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: schemaPDA,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const [initialized] = await getEvents(initTx);
+      const [executed] = await getEvents(executeTx);
+
+      assert.equal(initialized.name, "splitInitialized");
+      assert.equal(initialized.data.schemaVersion, EVENT_SCHEMA_VERSION);
+      assert.equal(executed.name, "splitExecuted");
+      assert.equal(executed.data.schemaVersion, EVENT_SCHEMA_VERSION);
+    });
+  });
+
+  describe("Config Invariant Tests", () => {
+    const verify = (pda: anchor.web3.PublicKey) =>
+      program.methods.verifyConfig().accounts({ splitConfig: pda }).rpc();
+
+    it("Accepts configs across the normal lifecycle", async () => {
+      const pda = await createSplit(60, 40);
+      await verify(pda);
+
+      await program.methods
+        .executeSplit(new anchor.BN(10_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      await verify(pda);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.executed, true);
+      assert.isAbove(splitConfig.executedAt.toNumber(), 0);
+    });
+
+    it("Accepts escrow configs holding an unreleased deposit", async () => {
+      const escrowNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, escrowNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(50, 50, new anchor.BN(escrowNonce), {
+          ...defaultOptions(),
+          escrow: true,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .depositToSplit(new anchor.BN(5_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await verify(pda);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.executed, false);
+      assert.equal(splitConfig.executedAt.toNumber(), 0);
+    });
+  });
+
+  describe("SPL Token Burn Tests", () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    let mint: anchor.web3.PublicKey;
+    let senderAta: anchor.web3.PublicKey;
+    let recipient1Ata: anchor.web3.PublicKey;
+    let recipient2Ata: anchor.web3.PublicKey;
+
+    const tokenBalance = async (address: anchor.web3.PublicKey) =>
+      Number((await getAccount(provider.connection, address)).amount);
+
+    const executeSpl = (pda: anchor.web3.PublicKey, amount: number, burnBps: number) =>
+      program.methods
+        .executeSplitSpl(new anchor.BN(amount), burnBps)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          mint,
+          senderTokenAccount: senderAta,
+          recipient1TokenAccount: recipient1Ata,
+          recipient2TokenAccount: recipient2Ata,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    before(async () => {
+      mint = await createMint(provider.connection, payer, sender.publicKey, null, 6);
+      senderAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, sender.publicKey)
+      ).address;
+      recipient1Ata = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, recipient1.publicKey)
+      ).address;
+      recipient2Ata = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, recipient2.publicKey)
+      ).address;
+      await mintTo(provider.connection, payer, mint, senderAta, payer, 10_000_000);
+    });
+
+    it("Burns the configured portion before distributing", async () => {
+      const pda = await createSplit(60, 40);
+      const supplyBefore = Number((await getMint(provider.connection, mint)).supply);
+      const r1Before = await tokenBalance(recipient1Ata);
+      const r2Before = await tokenBalance(recipient2Ata);
+
+      // 10% burned, remaining 900_000 split 60/40
+      await executeSpl(pda, 1_000_000, 1_000);
+
+      const supplyAfter = Number((await getMint(provider.connection, mint)).supply);
+      assert.equal(supplyBefore - supplyAfter, 100_000);
+      assert.equal((await tokenBalance(recipient1Ata)) - r1Before, 540_000);
+      assert.equal((await tokenBalance(recipient2Ata)) - r2Before, 360_000);
+    });
+
+    it("Distributes everything with a zero burn", async () => {
+      const pda = await createSplit(60, 40);
+      const supplyBefore = Number((await getMint(provider.connection, mint)).supply);
+      const r1Before = await tokenBalance(recipient1Ata);
+      const r2Before = await tokenBalance(recipient2Ata);
+
+      await executeSpl(pda, 1_000_000, 0);
+
+      const supplyAfter = Number((await getMint(provider.connection, mint)).supply);
+      assert.equal(supplyAfter, supplyBefore);
+      assert.equal((await tokenBalance(recipient1Ata)) - r1Before, 600_000);
+      assert.equal((await tokenBalance(recipient2Ata)) - r2Before, 400_000);
+    });
+
+    it("Rejects burn basis points above 10000", async () => {
+      const pda = await createSplit(60, 40);
+
+      try {
+        await executeSpl(pda, 1_000_000, 10_001);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidBurnBps");
+      }
+    });
+  });
+
+  describe("Wrapped SOL Delivery Tests", () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    let wsolOwner: anchor.web3.Keypair;
+    let wsolAccount: anchor.web3.PublicKey;
+
+    const createWrappedSplit = async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          recipient2Delivery: { wrappedSol: { tokenAccount: wsolAccount } },
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: wsolOwner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: wsolAccount, isSigner: false, isWritable: false },
+        ])
+        .rpc();
+
+      return pda;
+    };
+
+    const executeWrapped = (pda: anchor.web3.PublicKey, tokenProgram: anchor.web3.PublicKey) =>
+      program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: wsolOwner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: tokenProgram, isSigner: false, isWritable: false },
+          { pubkey: wsolAccount, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+    before(async () => {
+      wsolOwner = anchor.web3.Keypair.generate();
+      wsolAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        payer,
+        NATIVE_MINT,
+        wsolOwner.publicKey
+      );
+    });
+
+    it("Sends native SOL to one slot and wrapped SOL to the other", async () => {
+      const pda = await createWrappedSplit();
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const wsolBefore = Number((await getAccount(provider.connection, wsolAccount)).amount);
+
+      await executeWrapped(pda, TOKEN_PROGRAM_ID);
+
+      const recipient1After = await provider.connection.getBalance(recipient1.publicKey);
+      const wsolAfter = Number((await getAccount(provider.connection, wsolAccount)).amount);
+      assert.equal(recipient1After - recipient1Before, 600_000);
+      assert.equal(wsolAfter - wsolBefore, 400_000);
+    });
+
+    it("Rejects a spoofed token program", async () => {
+      const pda = await createWrappedSplit();
+
+      try {
+        await executeWrapped(pda, anchor.web3.Keypair.generate().publicKey);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidTokenProgram");
+      }
+    });
+
+    it("Rejects a wSOL account owned by someone else at init", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      try {
+        await program.methods
+          .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+            ...defaultOptions(),
+            recipient2Delivery: { wrappedSol: { tokenAccount: wsolAccount } },
+          })
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: wsolAccount, isSigner: false, isWritable: false },
+          ])
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidWrappedAccount");
+      }
+    });
+
+    it("Fails cleanly when the wSOL account was closed before execution", async () => {
+      const pda = await createWrappedSplit();
+
+      await closeAccount(
+        provider.connection,
+        payer,
+        wsolAccount,
+        wsolOwner.publicKey,
+        wsolOwner
+      );
+
+      try {
+        await executeWrapped(pda, TOKEN_PROGRAM_ID);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "WrappedAccountMissing");
+      }
+    });
+  });
+
+  describe("Delegate Limit Tests", () => {
+    const delegate = anchor.web3.Keypair.generate();
+
+    const createDelegatedSplit = async (dailyLimit: number) => {
+      const pda = await createSplit(60, 40);
+
+      await program.methods
+        .setDelegate(delegate.publicKey, new anchor.BN(dailyLimit))
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      await program.methods
+        .depositToSplit(new anchor.BN(10_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const executeFromDeposit = (
+      pda: anchor.web3.PublicKey,
+      amount: number,
+      executor?: anchor.web3.Keypair
+    ) =>
+      program.methods
+        .executeFromDeposit(new anchor.BN(amount))
+        .accounts({
+          splitConfig: pda,
+          executor: executor ? executor.publicKey : sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .signers(executor ? [executor] : [])
+        .rpc();
+
+    it("Lets the delegate execute within its daily limit", async () => {
+      const pda = await createDelegatedSplit(5_000_000);
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+
+      await executeFromDeposit(pda, 3_000_000, delegate);
+
+      const recipient1After = await provider.connection.getBalance(recipient1.publicKey);
+      assert.equal(recipient1After - recipient1Before, 1_800_000);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.depositedAmount.toNumber(), 7_000_000);
+      assert.equal(splitConfig.delegateWindowSpent.toNumber(), 3_000_000);
+      assert.equal(splitConfig.executed, false);
+    });
+
+    it("Stops the delegate at its limit while the sender can still execute", async () => {
+      const pda = await createDelegatedSplit(5_000_000);
+
+      await executeFromDeposit(pda, 4_000_000, delegate);
+
+      try {
+        await executeFromDeposit(pda, 2_000_000, delegate);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "DelegateLimitExceeded");
+      }
+
+      await executeFromDeposit(pda, 6_000_000);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.depositedAmount.toNumber(), 0);
+      assert.equal(splitConfig.delegateWindowSpent.toNumber(), 4_000_000);
+    });
+
+    it("Rejects executors that are neither sender nor delegate", async () => {
+      const pda = await createDelegatedSplit(0);
+      const stranger = anchor.web3.Keypair.generate();
+
+      try {
+        await executeFromDeposit(pda, 1_000_000, stranger);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "UnauthorizedExecutor");
+      }
+    });
+
+    it("Refunds the remaining prefunded balance to the sender", async () => {
+      const pda = await createDelegatedSplit(0);
+
+      await executeFromDeposit(pda, 2_000_000, delegate);
+      await program.methods
+        .refundEscrow()
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.depositedAmount.toNumber(), 0);
+    });
+  });
+
+  describe("Circuit Breaker Tests", () => {
+    const setCeiling = (ceiling: anchor.BN) =>
+      program.methods
+        .setEpochCeiling(ceiling)
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+    const execute = (pda: anchor.web3.PublicKey, amount: number) =>
+      program.methods
+        .executeSplit(new anchor.BN(amount), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    before(ensureProgramConfig);
+
+    after(async () => {
+      await setCeiling(new anchor.BN(0));
+    });
+
+    it("Fails executions beyond the epoch ceiling until the admin raises it", async () => {
+      const config = await program.account.programConfig.fetch(programConfigPda);
+      await setCeiling(config.epochVolume.add(new anchor.BN(5_000_000)));
+
+      await execute(await createSplit(60, 40), 3_000_000);
+
+      const blocked = await createSplit(60, 40);
+      try {
+        await execute(blocked, 3_000_000);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "CircuitBreakerTripped");
+      }
+
+      await setCeiling(config.epochVolume.add(new anchor.BN(10_000_000)));
+      await execute(blocked, 3_000_000);
+    });
+
+    it("Keeps cancellation available while the breaker is tripped", async () => {
+      const config = await program.account.programConfig.fetch(programConfigPda);
+      await setCeiling(config.epochVolume.add(new anchor.BN(1_000)));
+
+      const pda = await createSplit(60, 40);
+      try {
+        await execute(pda, 2_000_000);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "CircuitBreakerTripped");
+      }
+
+      await program.methods
+        .cancelSplit({ userRequested: {} })
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+      assert.isNull(await program.account.splitConfig.fetchNullable(pda));
+    });
+
+    it("Rejects ceiling changes from anyone but the admin", async () => {
+      const stranger = anchor.web3.Keypair.generate();
+
+      try {
+        await program.methods
+          .setEpochCeiling(new anchor.BN(1))
+          .accounts({ programConfig: programConfigPda, admin: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "UnauthorizedAdmin");
+      }
+    });
+  });
+
+  describe("Bump Repair Tests", () => {
+    const repair = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .repairBump()
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc({ commitment: "confirmed" });
+
+    it("Leaves a canonical bump untouched", async () => {
+      const pda = await createSplit(60, 40);
+      const [, canonicalBump] = getSplitConfigPDA(
+        sender.publicKey,
+        (await program.account.splitConfig.fetch(pda)).nonce.toNumber()
+      );
+
+      const signature = await repair(pda);
+
+      const events = await getEvents(signature);
+      assert.isUndefined(events.find((e) => e.name === "bumpRepaired"));
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.bump, canonicalBump);
+    });
+
+    // Requires a build with `--features test-helpers`
+    it("Repairs a corrupted bump", async function () {
+      if (!program.idl.instructions.some((ix) => ix.name === "corruptBumpForTesting")) {
+        this.skip();
+      }
+
+      const pda = await createSplit(60, 40);
+      const splitNonce = (await program.account.splitConfig.fetch(pda)).nonce.toNumber();
+      const [, canonicalBump] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await (program.methods as any)
+        .corruptBumpForTesting((canonicalBump + 1) % 256)
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      // The stored bump no longer derives the config address
+      try {
+        await program.methods
+          .setTopic([1, 2, 3, 4, 5, 6, 7, 8])
+          .accounts({ splitConfig: pda, sender: sender.publicKey })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ConstraintSeeds");
+      }
+
+      const signature = await repair(pda);
+
+      const events = await getEvents(signature);
+      const repaired = events.find((e) => e.name === "bumpRepaired");
+      assert.equal(repaired.data.newBump, canonicalBump);
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.bump, canonicalBump);
+    });
+
+    it("Rejects repair by anyone but the sender", async () => {
+      const pda = await createSplit(60, 40);
+      const stranger = anchor.web3.Keypair.generate();
+
+      try {
+        await program.methods
+          .repairBump()
+          .accounts({ splitConfig: pda, sender: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ConstraintSeeds");
+      }
+    });
+  });
+
+  describe("Remainder Rotation Tests", () => {
+    const createPrefundedSplit = async (rotateRemainder: boolean) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(50, 50, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          rotateRemainder,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .depositToSplit(new anchor.BN(10_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const executeOdd = async (pda: anchor.web3.PublicKey) => {
+      const signature = await program.methods
+        .executeFromDeposit(new anchor.BN(1_001))
+        .accounts({
+          splitConfig: pda,
+          executor: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const executed = (await getEvents(signature)).find((e) => e.name === "splitExecuted");
+      return [executed.data.amount1.toNumber(), executed.data.amount2.toNumber()];
+    };
+
+    it("Alternates the rounding lamport across reusable executions", async () => {
+      const pda = await createPrefundedSplit(true);
+
+      const shares = [];
+      for (let i = 0; i < 4; i++) {
+        shares.push(await executeOdd(pda));
+      }
+
+      assert.deepEqual(shares, [
+        [500, 501],
+        [501, 500],
+        [500, 501],
+        [501, 500],
+      ]);
+    });
+
+    it("Keeps giving the remainder to recipient2 without rotation", async () => {
+      const pda = await createPrefundedSplit(false);
+
+      assert.deepEqual(await executeOdd(pda), [500, 501]);
+      assert.deepEqual(await executeOdd(pda), [500, 501]);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.remainderRotation, 0);
+    });
+  });
+
+  describe("Claim Donation Tests", () => {
+    const charity = anchor.web3.Keypair.generate();
+
+    const createClaimSplit = async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          claimMode: true,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .executeSplit(new anchor.BN(10_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const claim = (
+      pda: anchor.web3.PublicKey,
+      donateBps: number,
+      donationDestination: anchor.web3.PublicKey | null
+    ) =>
+      program.methods
+        .claimSplit(donateBps)
+        .accounts({
+          splitConfig: pda,
+          recipient: recipient1.publicKey,
+          programConfig: programConfigPda,
+          donationDestination,
+        })
+        .signers([recipient1])
+        .rpc({ commitment: "confirmed" });
+
+    const claimedEvent = async (signature: string) =>
+      (await getEvents(signature)).find((e) => e.name === "splitClaimed").data;
+
+    before(async () => {
+      await ensureProgramConfig();
+      await program.methods
+        .setDonationPolicy(charity.publicKey, false)
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+      // Fund the charity above the rent-exempt minimum so small donations land
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: sender.publicKey,
+            toPubkey: charity.publicKey,
+            lamports: 1_000_000,
+          })
+        )
+      );
+    });
+
+    it("Credits shares at execution instead of transferring them", async () => {
+      const pda = await createClaimSplit();
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.recipient1Claimable.toNumber(), 6_000_000);
+      assert.equal(splitConfig.recipient2Claimable.toNumber(), 4_000_000);
+    });
+
+    it("Pays the whole claim to the recipient with a 0% donation", async () => {
+      const pda = await createClaimSplit();
+      const before = await provider.connection.getBalance(recipient1.publicKey);
+
+      const event = await claimedEvent(await claim(pda, 0, null));
+
+      const after = await provider.connection.getBalance(recipient1.publicKey);
+      assert.equal(after - before, 6_000_000);
+      assert.equal(event.recipientAmount.toNumber(), 6_000_000);
+      assert.equal(event.donatedAmount.toNumber(), 0);
+      assert.isTrue(event.donationDestination.equals(anchor.web3.PublicKey.default));
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.recipient1Claimable.toNumber(), 0);
+    });
+
+    it("Splits a claim between recipient and charity", async () => {
+      const pda = await createClaimSplit();
+      const charityBefore = await provider.connection.getBalance(charity.publicKey);
+
+      const event = await claimedEvent(await claim(pda, 2_500, charity.publicKey));
+
+      const charityAfter = await provider.connection.getBalance(charity.publicKey);
+      assert.equal(charityAfter - charityBefore, 1_500_000);
+      assert.equal(event.recipientAmount.toNumber(), 4_500_000);
+      assert.equal(event.donatedAmount.toNumber(), 1_500_000);
+    });
+
+    it("Donates the entire claim at 100%", async () => {
+      const pda = await createClaimSplit();
+      const before = await provider.connection.getBalance(recipient1.publicKey);
+
+      const event = await claimedEvent(await claim(pda, 10_000, charity.publicKey));
+
+      // The recipient only pays the transaction fee
+      const after = await provider.connection.getBalance(recipient1.publicKey);
+      assert.isAtMost(after, before);
+      assert.equal(event.recipientAmount.toNumber(), 0);
+      assert.equal(event.donatedAmount.toNumber(), 6_000_000);
+    });
+
+    it("Rejects donations to anyone but the charity", async () => {
+      const pda = await createClaimSplit();
+
+      try {
+        await claim(pda, 5_000, anchor.web3.Keypair.generate().publicKey);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "DonationDestinationNotAllowed");
+      }
+    });
+
+    it("Rejects more than 10000 basis points", async () => {
+      const pda = await createClaimSplit();
+
+      try {
+        await claim(pda, 10_001, charity.publicKey);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidDonationBps");
+      }
+    });
+
+    it("Blocks closing until every share is claimed", async () => {
+      const pda = await createClaimSplit();
+
+      try {
+        await program.methods
+          .closeSplit()
+          .accounts({ splitConfig: pda, sender: sender.publicKey })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ClaimsOutstanding");
+      }
+    });
+  });
+
+  describe("Split History Tests", () => {
+    const getHistoryPDA = (pda: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("history"), pda.toBuffer()],
+        program.programId
+      )[0];
+
+    const execute = (pda: anchor.web3.PublicKey, history: anchor.web3.PublicKey | null) =>
+      program.methods
+        .executeSplit(new anchor.BN(2_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          history,
+        })
+        .rpc();
+
+    it("Creates the history lazily on the first execution", async () => {
+      const pda = await createSplit(60, 40);
+      const historyPda = getHistoryPDA(pda);
+      assert.isNull(await program.account.splitHistory.fetchNullable(historyPda));
+
+      await execute(pda, historyPda);
+
+      const history = await program.account.splitHistory.fetch(historyPda);
+      assert.isTrue(history.splitConfig.equals(pda));
+      assert.equal(history.executionCount.toNumber(), 1);
+      assert.equal(history.totalAmount.toNumber(), 2_000_000);
+      assert.isAbove(history.lastExecutedAt.toNumber(), 0);
+    });
+
+    it("Skips the history when it is not passed", async () => {
+      const pda = await createSplit(60, 40);
+
+      await execute(pda, null);
+
+      assert.isNull(await program.account.splitHistory.fetchNullable(getHistoryPDA(pda)));
+    });
+  });
+
+  describe("Agreement Attestation Tests", () => {
+    const agreementHash = Array.from(
+      createHash("sha256").update("split agreement v1").digest()
+    ) as number[];
+
+    const createAgreementSplit = async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      const signature = await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          agreementHash,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      return { pda, signature };
+    };
+
+    const attest = (pda: anchor.web3.PublicKey, recipient: anchor.web3.Keypair, hash: number[]) =>
+      program.methods
+        .attestAgreement(hash)
+        .accounts({ splitConfig: pda, recipient: recipient.publicKey })
+        .signers([recipient])
+        .rpc({ commitment: "confirmed" });
+
+    it("Commits to the agreement hash at initialization", async () => {
+      const { pda, signature } = await createAgreementSplit();
+
+      const [initialized] = await getEvents(signature);
+      assert.deepEqual(initialized.data.agreementHash, agreementHash);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.deepEqual(splitConfig.agreementHash, agreementHash);
+      assert.equal(splitConfig.recipient1Attested, false);
+    });
+
+    it("Records each recipient's attestation of the exact hash", async () => {
+      const { pda } = await createAgreementSplit();
+
+      const signature = await attest(pda, recipient1, agreementHash);
+
+      const [attested] = await getEvents(signature);
+      assert.equal(attested.name, "agreementAttested");
+      assert.isTrue(attested.data.recipient.equals(recipient1.publicKey));
+      assert.deepEqual(attested.data.hash, agreementHash);
+
+      await attest(pda, recipient2, agreementHash);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.recipient1Attested, true);
+      assert.equal(splitConfig.recipient2Attested, true);
+    });
+
+    it("Rejects attestation of a different hash", async () => {
+      const { pda } = await createAgreementSplit();
+
+      try {
+        await attest(pda, recipient1, new Array(32).fill(7));
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "AgreementHashMismatch");
+      }
+    });
+
+    it("Rejects attestation when no agreement was committed", async () => {
+      const pda = await createSplit(60, 40);
+
+      try {
+        await attest(pda, recipient1, agreementHash);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "NoAgreement");
+      }
+    });
+  });
+
+  describe("Soft Pause Tests", () => {
+    const setSoftPause = (softPaused: boolean, maxExecution: number) =>
+      program.methods
+        .setSoftPause(softPaused, new anchor.BN(maxExecution))
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+    const execute = async (amount: number) =>
+      program.methods
+        .executeSplit(new anchor.BN(amount), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: await createSplit(60, 40),
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    before(async () => {
+      await ensureProgramConfig();
+      await setSoftPause(true, 1_000_000);
+    });
+
+    after(async () => {
+      await setSoftPause(false, 0);
+    });
+
+    it("Allows small executions during the soft pause", async () => {
+      await execute(1_000_000);
+    });
+
+    it("Rejects large executions during the soft pause", async () => {
+      try {
+        await execute(1_000_001);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "SoftPauseLimitExceeded");
+      }
+    });
+
+    it("Allows large executions again once the pause is lifted", async () => {
+      await setSoftPause(false, 0);
+      await execute(5_000_000);
+    });
+  });
+
+  describe("Rent Split Tests", () => {
+    const createRentSplit = async (percentage: number) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          rentSplit: { destination: recipient2.publicKey, percentage },
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    it("Splits the reclaimed rent on cancel", async () => {
+      const pda = await createRentSplit(30);
+      const rent = await provider.connection.getBalance(pda);
+      const destinationBefore = await provider.connection.getBalance(recipient2.publicKey);
+
+      const signature = await program.methods
+        .cancelSplit({ userRequested: {} })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          rentSplitDestination: recipient2.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const destinationAfter = await provider.connection.getBalance(recipient2.publicKey);
+      const expected = Math.floor((rent * 30) / 100);
+      assert.equal(destinationAfter - destinationBefore, expected);
+
+      const distributed = (await getEvents(signature)).find((e) => e.name === "rentDistributed");
+      assert.isTrue(distributed.data.destination.equals(recipient2.publicKey));
+      assert.equal(distributed.data.destinationAmount.toNumber(), expected);
+      assert.equal(distributed.data.senderAmount.toNumber(), rent - expected);
+    });
+
+    it("Splits the reclaimed rent on close", async () => {
+      const pda = await createRentSplit(100);
+      await program.methods
+        .executeSplit(new anchor.BN(2_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      const rent = await provider.connection.getBalance(pda);
+      const destinationBefore = await provider.connection.getBalance(recipient2.publicKey);
+
+      await program.methods
+        .closeSplit()
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          rentSplitDestination: recipient2.publicKey,
+        })
+        .rpc();
+
+      const destinationAfter = await provider.connection.getBalance(recipient2.publicKey);
+      assert.equal(destinationAfter - destinationBefore, rent);
+    });
+
+    it("Requires the recorded destination when closing", async () => {
+      const pda = await createRentSplit(50);
+
+      try {
+        await program.methods
+          .cancelSplit({ userRequested: {} })
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            rentSplitDestination: recipient1.publicKey,
+          })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidRentSplit");
+      }
+    });
+
+    it("Rejects a percentage above 100", async () => {
+      try {
+        await createRentSplit(101);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidRentSplit");
+      }
+    });
+  });
+
+  describe("Stake Delivery Tests", () => {
+    it("Stakes recipient1's share while recipient2 receives a transfer", async () => {
+      const { current } = await provider.connection.getVoteAccounts();
+      const voteAccount = new anchor.web3.PublicKey(current[0].votePubkey);
+      const stakeAccount = anchor.web3.Keypair.generate();
+
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          recipient1Delivery: { stake: { voteAccount } },
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+
+      await program.methods
+        .executeSplit(new anchor.BN(5 * anchor.web3.LAMPORTS_PER_SOL), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: stakeAccount.publicKey, isSigner: true, isWritable: true },
+          { pubkey: voteAccount, isSigner: false, isWritable: false },
+          { pubkey: anchor.web3.StakeProgram.programId, isSigner: false, isWritable: false },
+          { pubkey: anchor.web3.SystemProgram.programId, isSigner: false, isWritable: false },
+          { pubkey: anchor.web3.SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+          { pubkey: anchor.web3.SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+          { pubkey: anchor.web3.SYSVAR_STAKE_HISTORY_PUBKEY, isSigner: false, isWritable: false },
+          {
+            pubkey: new anchor.web3.PublicKey("StakeConfig11111111111111111111111111111111"),
+            isSigner: false,
+            isWritable: false,
+          },
+        ])
+        .signers([stakeAccount])
+        .rpc();
+
+      const recipient1After = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2After = await provider.connection.getBalance(recipient2.publicKey);
+      assert.equal(recipient1After, recipient1Before);
+      assert.equal(recipient2After - recipient2Before, 2 * anchor.web3.LAMPORTS_PER_SOL);
+
+      const stakeInfo = await provider.connection.getParsedAccountInfo(stakeAccount.publicKey);
+      assert.equal(stakeInfo.value.lamports, 3 * anchor.web3.LAMPORTS_PER_SOL);
+      const parsed = (stakeInfo.value.data as anchor.web3.ParsedAccountData).parsed.info;
+      assert.equal(parsed.meta.authorized.staker, recipient1.publicKey.toBase58());
+      assert.equal(parsed.meta.authorized.withdrawer, recipient1.publicKey.toBase58());
+      assert.equal(parsed.stake.delegation.voter, voteAccount.toBase58());
+    });
+  });
+
+  describe("Slot Window Tests", () => {
+    const execute = (pda: anchor.web3.PublicKey, validUntilSlot: number) =>
+      program.methods
+        .executeSplit(new anchor.BN(2_000_000), new anchor.BN(validUntilSlot), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    it("Executes within the slot window and reports the bound", async () => {
+      const pda = await createSplit(60, 40);
+      const validUntilSlot = (await provider.connection.getSlot()) + 1_000;
+
+      const signature = await execute(pda, validUntilSlot);
+
+      const executed = (await getEvents(signature)).find((e) => e.name === "splitExecuted");
+      assert.equal(executed.data.validUntilSlot.toNumber(), validUntilSlot);
+      assert.isAtMost(executed.data.slot.toNumber(), validUntilSlot);
+    });
+
+    it("Rejects execution after the slot window", async () => {
+      const pda = await createSplit(60, 40);
+      const validUntilSlot = (await provider.connection.getSlot()) - 1;
+
+      try {
+        await execute(pda, validUntilSlot);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ExecutionWindowExpiredSlot");
+      }
+    });
+  });
+
+  describe("Recipient Data Length Tests", () => {
+    const createDataAccount = async (space: number) => {
+      const account = anchor.web3.Keypair.generate();
+      const lamports = await provider.connection.getMinimumBalanceForRentExemption(space);
+
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.createAccount({
+            fromPubkey: sender.publicKey,
+            newAccountPubkey: account.publicKey,
+            lamports,
+            space,
+            programId: anchor.web3.SystemProgram.programId,
+          })
+        ),
+        [account]
+      );
+
+      return account.publicKey;
+    };
+
+    const executeWithDataRecipients = async (space: number) => {
+      const dataRecipient1 = await createDataAccount(space);
+      const dataRecipient2 = await createDataAccount(space);
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          minRecipientDataLen: new anchor.BN(64),
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: dataRecipient1,
+          recipient2: dataRecipient2,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .executeSplit(new anchor.BN(2_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: dataRecipient1,
+          recipient2: dataRecipient2,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    };
+
+    it("Executes when recipients hold enough data", async () => {
+      await executeWithDataRecipients(64);
+    });
+
+    it("Rejects recipients below the minimum data length", async () => {
+      try {
+        await executeWithDataRecipients(32);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "RecipientTooSmall");
+      }
+    });
+  });
+
+  describe("Counter Backfill Tests", () => {
+    const setMigrationWindow = (windowEnd: number) =>
+      program.methods
+        .setMigrationWindow(new anchor.BN(windowEnd))
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+    const backfill = (splitConfig: anchor.web3.PublicKey, admin = sender) =>
+      program.methods
+        .backfillCounters(new anchor.BN(3), new anchor.BN(9_000_000), Array(32).fill(7))
+        .accounts({ programConfig: programConfigPda, splitConfig, admin: admin.publicKey })
+        .signers(admin === sender ? [] : [admin])
+        .rpc();
+
+    before(async () => {
+      await ensureProgramConfig();
+      await setMigrationWindow(Math.floor(Date.now() / 1000) + 3600);
+    });
+
+    it("Sets historical counters once", async () => {
+      const splitConfig = await createSplit(60, 40);
+      await backfill(splitConfig);
+
+      const config = await program.account.splitConfig.fetch(splitConfig);
+      assert.equal(config.executionCount.toNumber(), 3);
+      assert.equal(config.totalDistributed.toNumber(), 9_000_000);
+      assert.isTrue(config.backfilled);
+    });
+
+    it("Rejects a second backfill of the same config", async () => {
+      const splitConfig = await createSplit(60, 40);
+      await backfill(splitConfig);
+
+      try {
+        await backfill(splitConfig);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "AlreadyBackfilled");
+      }
+    });
+
+    it("Rejects backfills from anyone but the admin", async () => {
+      const stranger = anchor.web3.Keypair.generate();
+
+      try {
+        await backfill(await createSplit(60, 40), stranger);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "UnauthorizedAdmin");
+      }
+    });
+
+    it("Disables backfills for good once the window closes", async () => {
+      await setMigrationWindow(1);
+
+      try {
+        await backfill(await createSplit(60, 40));
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "MigrationWindowClosed");
+      }
+
+      try {
+        await setMigrationWindow(Math.floor(Date.now() / 1000) + 3600);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "MigrationWindowClosed");
+      }
+    });
+  });
+
+  describe("Treasury Distribution Tests", () => {
+    const stakeholder1 = anchor.web3.Keypair.generate();
+    const stakeholder2 = anchor.web3.Keypair.generate();
+    const [treasuryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury")],
+      program.programId
+    );
+
+    const distribute = (admin = sender) =>
+      program.methods
+        .distributeTreasury()
+        .accounts({
+          programConfig: programConfigPda,
+          treasury: treasuryPda,
+          stakeholder1: stakeholder1.publicKey,
+          stakeholder2: stakeholder2.publicKey,
+          admin: admin.publicKey,
+        })
+        .signers(admin === sender ? [] : [admin])
+        .rpc();
+
+    before(async () => {
+      await ensureProgramConfig();
+      await program.methods
+        .setTreasuryStakeholders(stakeholder1.publicKey, stakeholder2.publicKey, 70, 30)
+        .accounts({
+          programConfig: programConfigPda,
+          treasury: treasuryPda,
+          admin: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("Splits accumulated fees between the stakeholders", async () => {
+      // Simulate fees collected into the treasury
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: sender.publicKey,
+            toPubkey: treasuryPda,
+            lamports: 10_000_000,
+          })
+        )
+      );
+
+      await distribute();
+
+      assert.equal(await provider.connection.getBalance(stakeholder1.publicKey), 7_000_000);
+      assert.equal(await provider.connection.getBalance(stakeholder2.publicKey), 3_000_000);
+
+      const treasury = await program.account.treasury.fetch(treasuryPda);
+      assert.equal(treasury.totalDistributed.toNumber(), 10_000_000);
+    });
+
+    it("Rejects distributing an empty treasury", async () => {
+      try {
+        await distribute();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "TreasuryEmpty");
+      }
+    });
+
+    it("Rejects distributions from anyone but the admin", async () => {
+      const stranger = anchor.web3.Keypair.generate();
+
+      try {
+        await distribute(stranger);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "UnauthorizedAdmin");
+      }
+    });
+  });
+
+  describe("Token Denomination Tests", () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+
+    type TokenAccounts = {
+      mint: anchor.web3.PublicKey;
+      senderTokenAccount: anchor.web3.PublicKey;
+      recipient1TokenAccount: anchor.web3.PublicKey;
+      recipient2TokenAccount: anchor.web3.PublicKey;
+    };
+
+    const setupMint = async (decimals: number): Promise<TokenAccounts> => {
+      const mint = await createMint(provider.connection, payer, sender.publicKey, null, decimals);
+      const ata = async (owner: anchor.web3.PublicKey) =>
+        (await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, owner)).address;
+      const accounts = {
+        mint,
+        senderTokenAccount: await ata(sender.publicKey),
+        recipient1TokenAccount: await ata(recipient1.publicKey),
+        recipient2TokenAccount: await ata(recipient2.publicKey),
+      };
+      await mintTo(provider.connection, payer, mint, accounts.senderTokenAccount, payer, 100_000_000_000);
+      return accounts;
+    };
+
+    const createDenominatedSplit = async (denomination: number) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          denomination: new anchor.BN(denomination),
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const executeSpl = (pda: anchor.web3.PublicKey, accounts: TokenAccounts, amount: number) =>
+      program.methods
+        .executeSplitSpl(new anchor.BN(amount), 0)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          ...accounts,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    const tokenBalance = async (address: anchor.web3.PublicKey) =>
+      Number((await getAccount(provider.connection, address)).amount);
+
+    it("Keeps whole-unit shares of a 0-decimal mint", async () => {
+      const accounts = await setupMint(0);
+      const pda = await createDenominatedSplit(1);
+
+      // 60% of 7 units floors to 4, the remaining 3 go to recipient 2
+      await executeSpl(pda, accounts, 7);
+
+      assert.equal(await tokenBalance(accounts.recipient1TokenAccount), 4);
+      assert.equal(await tokenBalance(accounts.recipient2TokenAccount), 3);
+    });
+
+    it("Rounds shares to the denomination of a 9-decimal mint", async () => {
+      const accounts = await setupMint(9);
+      const pda = await createDenominatedSplit(1_000_000_000);
+
+      // 60% of 3 tokens is 1.8, rounded down to 1 token with the rest to recipient 2
+      await executeSpl(pda, accounts, 3_000_000_000);
+
+      assert.equal(await tokenBalance(accounts.recipient1TokenAccount), 1_000_000_000);
+      assert.equal(await tokenBalance(accounts.recipient2TokenAccount), 2_000_000_000);
+    });
+
+    it("Rejects amounts that are not a multiple of the denomination", async () => {
+      const accounts = await setupMint(9);
+      const pda = await createDenominatedSplit(1_000_000_000);
+
+      try {
+        await executeSpl(pda, accounts, 2_500_000_000);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "AmountNotDenominated");
+      }
+    });
+
+    it("Quotes the nearest valid amounts around an undenominated one", async () => {
+      const pda = await createDenominatedSplit(1_000_000_000);
+
+      const quote = await program.methods
+        .quoteTokenSplit(new anchor.BN(2_500_000_000), 0)
+        .accounts({ splitConfig: pda })
+        .view();
+
+      assert.isFalse(quote.denominated);
+      assert.equal(quote.lowerValidAmount.toNumber(), 2_000_000_000);
+      assert.equal(quote.upperValidAmount.toNumber(), 3_000_000_000);
+    });
+  });
+
+  describe("Delegated Token Split Tests", () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    let mint: anchor.web3.PublicKey;
+    let senderAta: anchor.web3.PublicKey;
+    let recipient1Ata: anchor.web3.PublicKey;
+    let recipient2Ata: anchor.web3.PublicKey;
+
+    const tokenBalance = async (address: anchor.web3.PublicKey) =>
+      Number((await getAccount(provider.connection, address)).amount);
+
+    const executeDelegated = (pda: anchor.web3.PublicKey, amount: number) =>
+      program.methods
+        .executeSplitDelegatedToken(new anchor.BN(amount))
+        .accounts({
+          splitConfig: pda,
+          executor: sender.publicKey,
+          mint,
+          senderTokenAccount: senderAta,
+          recipient1TokenAccount: recipient1Ata,
+          recipient2TokenAccount: recipient2Ata,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    before(async () => {
+      mint = await createMint(provider.connection, payer, sender.publicKey, null, 6);
+      senderAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, sender.publicKey)
+      ).address;
+      recipient1Ata = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, recipient1.publicKey)
+      ).address;
+      recipient2Ata = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, recipient2.publicKey)
+      ).address;
+      await mintTo(provider.connection, payer, mint, senderAta, payer, 10_000_000);
+    });
+
+    it("Splits tokens within the delegated allowance", async () => {
+      const pda = await createSplit(60, 40);
+      await approve(provider.connection, payer, senderAta, pda, payer, 2_000_000);
+
+      await executeDelegated(pda, 2_000_000);
+
+      assert.equal(await tokenBalance(recipient1Ata), 1_200_000);
+      assert.equal(await tokenBalance(recipient2Ata), 800_000);
+
+      const source = await getAccount(provider.connection, senderAta);
+      assert.equal(Number(source.delegatedAmount), 0);
+    });
+
+    it("Executes a config only once despite remaining allowance", async () => {
+      const pda = await createSplit(60, 40);
+      await approve(provider.connection, payer, senderAta, pda, payer, 2_000_000);
+
+      await executeDelegated(pda, 1_000_000);
+      assert.isTrue((await program.account.splitConfig.fetch(pda)).executed);
+
+      try {
+        await executeDelegated(pda, 1_000_000);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "AlreadyExecuted");
+      }
+    });
+
+    it("Rejects amounts above the delegated allowance", async () => {
+      const pda = await createSplit(60, 40);
+      await approve(provider.connection, payer, senderAta, pda, payer, 500_000);
+
+      try {
+        await executeDelegated(pda, 1_000_000);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "TokenDelegationInsufficient");
+      }
+    });
+
+    it("Rejects token accounts delegated to another config", async () => {
+      const approved = await createSplit(60, 40);
+      const other = await createSplit(60, 40);
+      await approve(provider.connection, payer, senderAta, approved, payer, 1_000_000);
+
+      try {
+        await executeDelegated(other, 1_000_000);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "TokenDelegationMissing");
+      }
+    });
+  });
+
+  describe("Effective Terms Tests", () => {
+    // Deterministic pseudo-random source so failures reproduce
+    let seed = 42;
+    const nextRandom = (max: number) => {
+      seed = (seed * 1103515245 + 12345) % 2147483648;
+      return seed % max;
+    };
+
+    it("Resolves exactly what execution realizes across random configs", async () => {
+      for (let i = 0; i < 8; i++) {
+        const p1 = 1 + nextRandom(99);
+        const amount = 1_000 + nextRandom(50_000_000);
+        const pda = await createSplit(p1, 100 - p1);
+
+        const terms = await program.methods
+          .resolveEffectiveTerms(new anchor.BN(amount))
+          .accounts({ splitConfig: pda })
+          .view();
+
+        const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+        const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+
+        await program.methods
+          .executeSplit(new anchor.BN(amount), new anchor.BN(0), null, null)
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+
+        const recipient1After = await provider.connection.getBalance(recipient1.publicKey);
+        const recipient2After = await provider.connection.getBalance(recipient2.publicKey);
+        assert.equal(recipient1After - recipient1Before, terms.amount1.toNumber());
+        assert.equal(recipient2After - recipient2Before, terms.amount2.toNumber());
+      }
+    });
+
+    it("Rejects amounts execution would reject", async () => {
+      const pda = await createSplit(60, 40);
+
+      try {
+        await program.methods
+          .resolveEffectiveTerms(new anchor.BN(999))
+          .accounts({ splitConfig: pda })
+          .view();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "AmountTooSmall");
+      }
+    });
+  });
+
+  describe("Rent Estimate Tests", () => {
+    it("Estimates the rent charged at initialization", async () => {
+      const estimate = await program.methods.estimateRent(2, 0).view();
+
+      const pda = await createSplit(60, 40);
+      const charged = await provider.connection.getBalance(pda);
+
+      assert.equal(estimate.toNumber(), charged);
+    });
+
+    it("Adds a recipient set for more than two recipients", async () => {
+      const pair = await program.methods.estimateRent(2, 0).view();
+      const multi = await program.methods.estimateRent(3, 0).view();
+
+      assert.isAbove(multi.toNumber(), pair.toNumber());
+    });
+
+    it("Rejects layouts configs cannot have", async () => {
+      // Too many recipients, then a memo configs don't store
+      for (const [numRecipients, memoLen] of [[33, 0], [2, 64]]) {
+        try {
+          await program.methods.estimateRent(numRecipients, memoLen).view();
+          assert.fail("Expected error not thrown");
+        } catch (err) {
+          assert.include(err.toString(), "UnsupportedConfigLayout");
+        }
+      }
+    });
+  });
+
+  describe("Close Residual Tests", () => {
+    const closeWithResidual = async (closeResidualToRecipients: boolean) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          closeResidualToRecipients,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .executeSplit(new anchor.BN(2_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      // Stray lamports sent to the config after execution
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: sender.publicKey,
+            toPubkey: pda,
+            lamports: 100_000,
+          })
+        )
+      );
+
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+
+      await program.methods
+        .closeSplit()
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .rpc();
+
+      return {
+        recipient1Gain: (await provider.connection.getBalance(recipient1.publicKey)) - recipient1Before,
+        recipient2Gain: (await provider.connection.getBalance(recipient2.publicKey)) - recipient2Before,
+      };
+    };
+
+    it("Splits the residual to recipients when configured", async () => {
+      const { recipient1Gain, recipient2Gain } = await closeWithResidual(true);
+      assert.equal(recipient1Gain, 60_000);
+      assert.equal(recipient2Gain, 40_000);
+    });
+
+    it("Returns the residual to the sender by default", async () => {
+      const { recipient1Gain, recipient2Gain } = await closeWithResidual(false);
+      assert.equal(recipient1Gain, 0);
+      assert.equal(recipient2Gain, 0);
+    });
+  });
+
+  describe("Vault Sweep Tests", () => {
+    // Terms hash of a 60/40 config with no flags, as initialize_split_committed derives it
+    const committedHash = () =>
+      createHash("sha256")
+        .update("solsplit_terms")
+        .update(recipient1.publicKey.toBuffer())
+        .update(recipient2.publicKey.toBuffer())
+        .update(Buffer.from([60, 40, 0, 0]))
+        .digest();
+
+    const createCommittedSplit = async () => {
+      const splitNonce = nonce++;
+      const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("split_config"),
+          sender.publicKey.toBuffer(),
+          guardedNonceBytes(splitNonce),
+          committedHash(),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .initializeSplitCommitted(60, 40, new anchor.BN(splitNonce), defaultOptions())
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const createClosedVault = async (strandedLamports: number, committed = false) => {
+      const splitNonce = nonce;
+      const pda = committed ? await createCommittedSplit() : await createSplit(60, 40);
+
+      await program.methods
+        .executeSplit(new anchor.BN(2_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .closeSplit()
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      // Lamports sent to the config's address after it closed
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: sender.publicKey,
+            toPubkey: pda,
+            lamports: strandedLamports,
+          })
+        )
+      );
+
+      return { pda, splitNonce };
+    };
+
+    const sweep = (
+      nonces: number[],
+      vaults: anchor.web3.PublicKey[],
+      termsHashes: (number[] | null)[] = []
+    ) =>
+      program.methods
+        .sweepVaults(
+          nonces.map((n) => new anchor.BN(n)),
+          termsHashes
+        )
+        .accounts({
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(
+          vaults.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+        )
+        .rpc({ commitment: "confirmed" });
+
+    it("Sweeps closed vaults and skips everything else", async () => {
+      const closed1 = await createClosedVault(1_000_000);
+      const closed2 = await createClosedVault(2_000_000);
+      const liveNonce = nonce;
+      const live = await createSplit(60, 40);
+      const stranger = anchor.web3.Keypair.generate().publicKey;
+
+      const signature = await sweep(
+        [closed1.splitNonce, closed2.splitNonce, liveNonce, closed1.splitNonce],
+        [closed1.pda, closed2.pda, live, stranger]
+      );
+
+      assert.equal(await provider.connection.getBalance(closed1.pda), 0);
+      assert.equal(await provider.connection.getBalance(closed2.pda), 0);
+
+      const swept = (await getEvents(signature)).filter((e) => e.name === "vaultSwept");
+      assert.equal(swept.length, 4);
+      assert.equal(swept[0].data.amount.toNumber(), 1_000_000);
+      assert.equal(swept[1].data.amount.toNumber(), 2_000_000);
+      assert.deepEqual(swept[2].data.skipReason, { liveConfig: {} });
+      assert.deepEqual(swept[3].data.skipReason, { seedMismatch: {} });
+    });
+
+    it("Sweeps closed vaults of configs committed to their terms", async () => {
+      const committed = await createClosedVault(1_000_000, true);
+
+      // Without its terms hash the vault doesn't derive from the nonce
+      let signature = await sweep([committed.splitNonce], [committed.pda]);
+      let swept = (await getEvents(signature)).filter((e) => e.name === "vaultSwept");
+      assert.deepEqual(swept[0].data.skipReason, { seedMismatch: {} });
+
+      signature = await sweep([committed.splitNonce], [committed.pda], [Array.from(committedHash())]);
+      swept = (await getEvents(signature)).filter((e) => e.name === "vaultSwept");
+      assert.equal(swept[0].data.amount.toNumber(), 1_000_000);
+      assert.equal(await provider.connection.getBalance(committed.pda), 0);
+    });
+
+    it("Rejects batches above the cap", async () => {
+      // The cap is checked before the accounts, which would not fit in one transaction
+      const nonces = Array.from({ length: 33 }, (_, i) => i);
+
+      try {
+        await sweep(nonces, []);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "SweepBatchTooLarge");
+      }
+    });
+  });
+
+  describe("Oracle Release Tests", () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    let oracleAccount: anchor.web3.PublicKey;
+
+    // A token account's amount, at offset 64, stands in for the oracle value
+    const ORACLE_VALUE_OFFSET = 64;
+
+    const createOracleEscrow = async (conditionValue: number) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          escrow: true,
+          oracleCondition: {
+            oracle: oracleAccount,
+            owner: TOKEN_PROGRAM_ID,
+            valueOffset: ORACLE_VALUE_OFFSET,
+            conditionValue: new anchor.BN(conditionValue),
+          },
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .depositToSplit(new anchor.BN(10_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const release = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .releaseOnCondition()
+        .accounts({
+          splitConfig: pda,
+          oracle: oracleAccount,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      const mint = await createMint(provider.connection, payer, sender.publicKey, null, 0);
+      oracleAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          payer,
+          mint,
+          anchor.web3.Keypair.generate().publicKey
+        )
+      ).address;
+      await mintTo(provider.connection, payer, mint, oracleAccount, payer, 1_500);
+    });
+
+    it("Releases once the oracle meets the condition", async () => {
+      const pda = await createOracleEscrow(1_000);
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+
+      await release(pda);
+
+      const recipient1After = await provider.connection.getBalance(recipient1.publicKey);
+      assert.equal(recipient1After - recipient1Before, 6_000_000);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.isTrue(splitConfig.executed);
+    });
+
+    it("Holds the escrow while the oracle is below the condition", async () => {
+      const pda = await createOracleEscrow(2_000);
+
+      try {
+        await release(pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "OracleConditionNotMet");
+      }
+    });
+
+    it("Rejects oracle conditions on non-escrow configs", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      try {
+        await program.methods
+          .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+            ...defaultOptions(),
+            oracleCondition: {
+              oracle: oracleAccount,
+              owner: TOKEN_PROGRAM_ID,
+              valueOffset: ORACLE_VALUE_OFFSET,
+              conditionValue: new anchor.BN(1),
+            },
+          })
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "IncompatibleOptions");
+      }
+    });
+  });
+
+  describe("Sender Settings Tests", () => {
+    const [settingsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("sender_settings"), sender.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const defaults = (senderMinBalance: number) => ({
+      senderMinBalance: new anchor.BN(senderMinBalance),
+      rotateRemainder: true,
+      minRecipientDataLen: new anchor.BN(0),
+      denomination: new anchor.BN(10),
+      closeResidualToRecipients: true,
+    });
+
+    const initializeWithDefaults = async (senderSettings: anchor.web3.PublicKey | null) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      const signature = await program.methods
+        .initializeSplitWithDefaults(60, 40, new anchor.BN(splitNonce), {
+          senderMinBalance: null,
+          rotateRemainder: false,
+          minRecipientDataLen: null,
+          denomination: null,
+          closeResidualToRecipients: null,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          senderSettings,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      return { pda, signature };
+    };
+
+    before(async () => {
+      await program.methods
+        .initializeSenderSettings(defaults(1_000))
+        .accounts({
+          senderSettings: settingsPda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    after(async () => {
+      await program.methods
+        .closeSenderSettings()
+        .accounts({ senderSettings: settingsPda, sender: sender.publicKey })
+        .rpc();
+    });
+
+    it("Fills unset options from the sender's defaults", async () => {
+      const { pda, signature } = await initializeWithDefaults(settingsPda);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.senderMinBalance.toNumber(), 1_000);
+      assert.isFalse(splitConfig.rotateRemainder);
+      assert.equal(splitConfig.denomination.toNumber(), 10);
+      assert.isTrue(splitConfig.closeResidualToRecipients);
+
+      // Every field but the explicit rotate_remainder came from the defaults
+      const initialized = (await getEvents(signature)).find((e) => e.name === "splitInitialized");
+      assert.equal(initialized.data.defaultsApplied, 0b11101);
+    });
+
+    it("Leaves existing configs alone when the defaults change", async () => {
+      const { pda } = await initializeWithDefaults(settingsPda);
+
+      await program.methods
+        .updateSenderSettings(defaults(5_000))
+        .accounts({ senderSettings: settingsPda, sender: sender.publicKey })
+        .rpc();
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.senderMinBalance.toNumber(), 1_000);
+
+      const { pda: newer } = await initializeWithDefaults(settingsPda);
+      const newerConfig = await program.account.splitConfig.fetch(newer);
+      assert.equal(newerConfig.senderMinBalance.toNumber(), 5_000);
+    });
+
+    it("Requires the settings account", async () => {
+      try {
+        await initializeWithDefaults(null);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "SenderSettingsMissing");
+      }
+    });
+  });
+
+  describe("Config Lifetime Tests", () => {
+    const setMaxLifetime = (seconds: number) =>
+      program.methods
+        .setMaxConfigLifetime(new anchor.BN(seconds))
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+    const execute = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .executeSplit(new anchor.BN(2_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    const cleanup = (pda: anchor.web3.PublicKey, cleaner: anchor.web3.Keypair) =>
+      program.methods
+        .cleanupExpiredConfig()
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          programConfig: programConfigPda,
+          cleaner: cleaner.publicKey,
+        })
+        .signers([cleaner])
+        .rpc();
+
+    before(async () => {
+      await ensureProgramConfig();
+    });
+
+    after(async () => {
+      await setMaxLifetime(0);
+    });
+
+    it("Executes configs within the lifetime", async () => {
+      await setMaxLifetime(3600);
+      await execute(await createSplit(60, 40));
+    });
+
+    it("Rejects executing configs past the lifetime", async () => {
+      await setMaxLifetime(2);
+      const pda = await createSplit(60, 40);
+      await sleep(3000);
+
+      try {
+        await execute(pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ConfigExpired");
+      }
+    });
+
+    it("Lets anyone clean up an expired config, refunding the sender", async () => {
+      await setMaxLifetime(2);
+      const pda = await createSplit(60, 40);
+      const rent = await provider.connection.getBalance(pda);
+      const cleaner = anchor.web3.Keypair.generate();
+
+      try {
+        await cleanup(pda, cleaner);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ConfigNotExpired");
+      }
+
+      await sleep(3000);
+      const senderBefore = await provider.connection.getBalance(sender.publicKey);
+      await cleanup(pda, cleaner);
+
+      assert.isNull(await provider.connection.getAccountInfo(pda));
+      // The sender's wallet also paid the transaction fee
+      const senderAfter = await provider.connection.getBalance(sender.publicKey);
+      assert.approximately(senderAfter - senderBefore, rent, 10_000);
+    });
+  });
+
+  describe("Cancel Reason Tests", () => {
+    const cancel = async (reason: object) => {
+      const pda = await createSplit(60, 40);
+      const signature = await program.methods
+        .cancelSplit(reason as any)
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc({ commitment: "confirmed" });
+
+      return (await getEvents(signature)).find((e) => e.name === "splitCancelled");
+    };
+
+    it("Reports the sender's reason", async () => {
+      const disputed = await cancel({ dispute: {} });
+      assert.deepEqual(disputed.data.reason, { dispute: {} });
+
+      const other = await cancel({ other: { 0: 7 } });
+      assert.equal(other.data.reason.other[0], 7);
+    });
+
+    it("Rejects reasons reserved for other paths", async () => {
+      for (const reason of [{ expired: {} }, { compliance: {} }]) {
+        try {
+          await cancel(reason);
+          assert.fail("Expected error not thrown");
+        } catch (err) {
+          assert.include(err.toString(), "InvalidCancelReason");
+        }
+      }
+    });
+
+    it("Always reports expiry cleanup as expired", async () => {
+      await ensureProgramConfig();
+      await program.methods
+        .setMaxConfigLifetime(new anchor.BN(2))
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+      try {
+        const pda = await createSplit(60, 40);
+        await sleep(3000);
+
+        const signature = await program.methods
+          .cleanupExpiredConfig()
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            programConfig: programConfigPda,
+            cleaner: sender.publicKey,
+          })
+          .rpc({ commitment: "confirmed" });
+
+        const cancelled = (await getEvents(signature)).find((e) => e.name === "splitCancelled");
+        assert.deepEqual(cancelled.data.reason, { expired: {} });
+      } finally {
+        await program.methods
+          .setMaxConfigLifetime(new anchor.BN(0))
+          .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+          .rpc();
+      }
+    });
+  });
+
+  describe("Reconfiguration Tests", () => {
+    const newRecipient1 = anchor.web3.Keypair.generate().publicKey;
+    const newRecipient2 = anchor.web3.Keypair.generate().publicKey;
+
+    const reconfigure = (
+      pda: anchor.web3.PublicKey,
+      r1: anchor.web3.PublicKey,
+      r2: anchor.web3.PublicKey,
+      p1: number,
+      p2: number
+    ) =>
+      program.methods
+        .reconfigureSplit(r1, r2, p1, p2)
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc({ commitment: "confirmed" });
+
+    it("Replaces recipients and percentages together", async () => {
+      const pda = await createSplit(60, 40);
+
+      const signature = await reconfigure(pda, newRecipient1, newRecipient2, 25, 75);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.isTrue(splitConfig.recipient1.equals(newRecipient1));
+      assert.isTrue(splitConfig.recipient2.equals(newRecipient2));
+      assert.equal(splitConfig.recipient1Percentage, 25);
+      assert.equal(splitConfig.recipient2Percentage, 75);
+
+      const events = (await getEvents(signature)).filter((e) => e.name === "splitReconfigured");
+      assert.equal(events.length, 1);
+      assert.isTrue(events[0].data.oldRecipient1.equals(recipient1.publicKey));
+      assert.equal(events[0].data.oldRecipient1Percentage, 60);
+    });
+
+    it("Rejects invalid combinations", async () => {
+      const pda = await createSplit(60, 40);
+      const cases: [anchor.web3.PublicKey, anchor.web3.PublicKey, number, number, string][] = [
+        [newRecipient1, newRecipient2, 50, 30, "InvalidPercentages"],
+        [newRecipient1, newRecipient2, 200, 100, "InvalidPercentages"],
+        [newRecipient1, newRecipient2, 0, 100, "ZeroPercentage"],
+        [newRecipient1, newRecipient1, 50, 50, "DuplicateRecipient"],
+        [newRecipient1, anchor.web3.SystemProgram.programId, 50, 50, "InvalidRecipient"],
+      ];
+
+      for (const [r1, r2, p1, p2, expected] of cases) {
+        try {
+          await reconfigure(pda, r1, r2, p1, p2);
+          assert.fail("Expected error not thrown");
+        } catch (err) {
+          assert.include(err.toString(), expected);
+        }
+      }
+
+      // Nothing was partially applied
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.isTrue(splitConfig.recipient1.equals(recipient1.publicKey));
+      assert.equal(splitConfig.recipient1Percentage, 60);
+    });
+
+    it("Rejects reconfiguring an executed config", async () => {
+      const pda = await createSplit(60, 40);
+      await program.methods
+        .executeSplit(new anchor.BN(2_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      try {
+        await reconfigure(pda, newRecipient1, newRecipient2, 50, 50);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "AlreadyExecuted");
+      }
+    });
+
+    it("Rejects reconfiguring once a recipient has been paid", async () => {
+      const pda = await createSplit(60, 40);
+      await program.methods
+        .depositToSplit(new anchor.BN(10_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .executeFromDeposit(new anchor.BN(1_000_000))
+        .accounts({
+          splitConfig: pda,
+          executor: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .rpc();
+
+      try {
+        await reconfigure(pda, newRecipient1, newRecipient2, 50, 50);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "RecipientHistory");
+      }
+    });
+
+    it("Rechecks the withholding against the new recipients", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          withholding: { destination: newRecipient2, bps: 1_000, appliesToSlot: 1 },
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      try {
+        await reconfigure(pda, newRecipient1, newRecipient2, 50, 50);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidWithholding");
+      }
+    });
+  });
+
+  describe("Executor Bond Tests", () => {
+    const executor = anchor.web3.Keypair.generate();
+    const [bondPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("executor_bond"), executor.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const now = () => Math.floor(Date.now() / 1000);
+
+    const createOpenSplit = async (quietWindow: { start: number; end: number } | null) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          anyoneCanExecute: true,
+          executorQuietWindow: quietWindow && {
+            start: new anchor.BN(quietWindow.start),
+            end: new anchor.BN(quietWindow.end),
+          },
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .depositToSplit(new anchor.BN(10_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const executeOpen = (pda: anchor.web3.PublicKey, executorBond: anchor.web3.PublicKey | null) =>
+      program.methods
+        .executeFromDeposit(new anchor.BN(1_000_000))
+        .accounts({
+          splitConfig: pda,
+          executor: executor.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          executorBond,
+        })
+        .signers([executor])
+        .rpc();
+
+    const postBond = (lamports: number) =>
+      program.methods
+        .postExecutorBond(new anchor.BN(lamports))
+        .accounts({
+          executorBond: bondPda,
+          executor: executor.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([executor])
+        .rpc();
+
+    const slash = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .slashExecutorBond()
+        .accounts({ splitConfig: pda, executorBond: bondPda, sender: sender.publicKey })
+        .rpc();
+
+    before(async () => {
+      await ensureProgramConfig();
+      await program.methods
+        .setMinExecutorBond(new anchor.BN(1_000_000))
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: sender.publicKey,
+            toPubkey: executor.publicKey,
+            lamports: 100_000_000,
+          })
+        )
+      );
+    });
+
+    after(async () => {
+      await program.methods
+        .setMinExecutorBond(new anchor.BN(0))
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+    });
+
+    it("Rejects open executors without a sufficient bond", async () => {
+      const pda = await createOpenSplit(null);
+
+      try {
+        await executeOpen(pda, null);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ExecutorBondRequired");
+      }
+
+      await postBond(500_000);
+      try {
+        await executeOpen(pda, bondPda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ExecutorBondRequired");
+      }
+    });
+
+    it("Lets bonded executors execute open configs", async () => {
+      await postBond(500_000);
+      await executeOpen(await createOpenSplit(null), bondPda);
+
+      const bond = await program.account.executorBond.fetch(bondPda);
+      assert.equal(bond.amount.toNumber(), 1_000_000);
+    });
+
+    it("Refuses to slash an execution outside the quiet window", async () => {
+      const pda = await createOpenSplit({ start: now() - 7200, end: now() - 3600 });
+      await executeOpen(pda, bondPda);
+
+      try {
+        await slash(pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "NoBondViolation");
+      }
+    });
+
+    it("Refuses to slash on behalf of a config the executor did not touch", async () => {
+      await executeOpen(await createOpenSplit(null), bondPda);
+      const untouched = await createOpenSplit({ start: now() - 60, end: now() + 3600 });
+
+      try {
+        await slash(untouched);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "NoBondViolation");
+      }
+    });
+
+    it("Slashes an execution inside the quiet window into the vault", async () => {
+      const pda = await createOpenSplit({ start: now() - 60, end: now() + 3600 });
+      await executeOpen(pda, bondPda);
+
+      await slash(pda);
+
+      const bond = await program.account.executorBond.fetch(bondPda);
+      assert.equal(bond.amount.toNumber(), 0);
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.depositedAmount.toNumber(), 9_000_000 + 1_000_000);
+
+      // A slashed bond no longer covers open execution
+      try {
+        await executeOpen(pda, bondPda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ExecutorBondRequired");
+      }
+
+      // A top-up isn't slashable for the violation already slashed
+      await postBond(1_000_000);
+      try {
+        await slash(pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "NoBondViolation");
+      }
+      const toppedUp = await program.account.executorBond.fetch(bondPda);
+      assert.equal(toppedUp.amount.toNumber(), 1_000_000);
+    });
+
+    it("Keeps the bond locked during the withdrawal cooldown", async () => {
+      try {
+        await program.methods
+          .withdrawExecutorBond()
+          .accounts({ executorBond: bondPda, executor: executor.publicKey })
+          .signers([executor])
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "BondCooldownActive");
+      }
+    });
+
+    it("Refuses to slash a bond that never executed, even under an epoch-wide window", async () => {
+      const idle = anchor.web3.Keypair.generate();
+      const [idleBond] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("executor_bond"), idle.publicKey.toBuffer()],
+        program.programId
+      );
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: sender.publicKey,
+            toPubkey: idle.publicKey,
+            lamports: 10_000_000,
+          })
+        )
+      );
+      await program.methods
+        .postExecutorBond(new anchor.BN(1_000_000))
+        .accounts({
+          executorBond: idleBond,
+          executor: idle.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([idle])
+        .rpc();
+
+      // The window contains zero, the timestamp a never-executed bond stores
+      const pda = await createOpenSplit({ start: 0, end: now() + 3600 });
+
+      try {
+        await program.methods
+          .slashExecutorBond()
+          .accounts({ splitConfig: pda, executorBond: idleBond, sender: sender.publicKey })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "NotYetExecuted");
+      }
+    });
+  });
+
+  describe("Recipient Cap Tests", () => {
+    const createCappedSplit = async (recipient1Cap: number, escrow = false) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          escrow,
+          recipient1Cap: new anchor.BN(recipient1Cap),
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const executeFromDeposit = (pda: anchor.web3.PublicKey, amount: number) =>
+      program.methods
+        .executeFromDeposit(new anchor.BN(amount))
+        .accounts({
+          splitConfig: pda,
+          executor: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .rpc();
+
+    it("Keeps the overflow above a cap with the sender", async () => {
+      const pda = await createCappedSplit(1_000_000);
+
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+
+      const terms = await program.methods
+        .resolveEffectiveTerms(new anchor.BN(5_000_000))
+        .accounts({ splitConfig: pda })
+        .view();
+      assert.equal(terms.overflow.toNumber(), 2_000_000);
+
+      await program.methods
+        .executeSplit(new anchor.BN(5_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const recipient1After = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2After = await provider.connection.getBalance(recipient2.publicKey);
+      assert.equal(recipient1After - recipient1Before, 1_000_000);
+      assert.equal(recipient2After - recipient2Before, 2_000_000);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.recipient1Received.toNumber(), 1_000_000);
+      assert.equal(splitConfig.totalDistributed.toNumber(), 3_000_000);
+    });
+
+    it("Stops paying a recipient once their cap is reached mid-lifecycle", async () => {
+      const pda = await createCappedSplit(2_000_000);
+
+      await program.methods
+        .depositToSplit(new anchor.BN(10_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      // 1.2M of 2M used, then the next run hits the cap with 0.4M overflow
+      await executeFromDeposit(pda, 2_000_000);
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      await executeFromDeposit(pda, 2_000_000);
+      const recipient1Mid = await provider.connection.getBalance(recipient1.publicKey);
+      assert.equal(recipient1Mid - recipient1Before, 800_000);
+
+      // Once capped, recipient 1's whole share stays in the deposit
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+      await executeFromDeposit(pda, 2_000_000);
+      assert.equal(await provider.connection.getBalance(recipient1.publicKey), recipient1Mid);
+      const recipient2After = await provider.connection.getBalance(recipient2.publicKey);
+      assert.equal(recipient2After - recipient2Before, 800_000);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.recipient1Received.toNumber(), 2_000_000);
+      assert.equal(splitConfig.depositedAmount.toNumber(), 10_000_000 - 2_000_000 - 2_400_000);
+    });
+
+    it("Rejects caps on escrow configs", async () => {
+      try {
+        await createCappedSplit(1_000_000, true);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "IncompatibleOptions");
+      }
+    });
+  });
+
+  describe("Blackout Window Tests", () => {
+    const DAY = 86_400;
+    const now = () => Math.floor(Date.now() / 1000);
+
+    type Window = { start: number; duration: number; period: number };
+
+    const createBlackoutSplit = async (windows: Window[]) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          blackoutWindows: windows.map((w) => ({
+            startOffsetSeconds: new anchor.BN(w.start),
+            durationSeconds: new anchor.BN(w.duration),
+            periodSeconds: new anchor.BN(w.period),
+          })),
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const blockedUntil = async (pda: anchor.web3.PublicKey) => {
+      const terms = await program.methods
+        .resolveEffectiveTerms(new anchor.BN(1_000_000))
+        .accounts({ splitConfig: pda })
+        .view();
+      return terms.blockedUntil.toNumber();
+    };
+
+    const execute = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    it("Rejects execution inside a one-off window and reports when it ends", async () => {
+      const start = now() - 600;
+      const pda = await createBlackoutSplit([{ start, duration: 3600, period: 0 }]);
+
+      assert.equal(await blockedUntil(pda), start + 3600);
+      try {
+        await execute(pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ExecutionInBlackout");
+      }
+    });
+
+    it("Finds the current occurrence of a recurring window", async () => {
+      // Anchored ten periods back, so the live occurrence is the eleventh
+      const start = now() - 10 * DAY - 600;
+      const pda = await createBlackoutSplit([{ start, duration: 3600, period: DAY }]);
+
+      assert.equal(await blockedUntil(pda), start + 10 * DAY + 3600);
+    });
+
+    it("Walks through back-to-back windows to the first open second", async () => {
+      const start = now() - 600;
+      const pda = await createBlackoutSplit([
+        { start, duration: 3600, period: 0 },
+        { start: start + 3600, duration: 3600, period: DAY },
+      ]);
+
+      assert.equal(await blockedUntil(pda), start + 7200);
+    });
+
+    it("Allows execution outside the windows", async () => {
+      // One window ended well before now, the other starts well after
+      const pda = await createBlackoutSplit([
+        { start: now() - 3 * 3600, duration: 3600, period: 0 },
+        { start: now() + 600, duration: 3600, period: DAY },
+      ]);
+
+      assert.equal(await blockedUntil(pda), 0);
+      await execute(pda);
+    });
+
+    it("Rejects malformed windows at initialization", async () => {
+      const start = now();
+      for (const windows of [
+        [{ start, duration: 0, period: DAY }],
+        [{ start, duration: DAY, period: DAY }],
+        [{ start: -1, duration: 60, period: DAY }],
+        // Two windows that together black out every day
+        [
+          { start, duration: DAY / 2, period: DAY },
+          { start: start + DAY / 2, duration: DAY / 2, period: DAY },
+        ],
+        new Array(5).fill({ start, duration: 60, period: DAY }),
+      ]) {
+        try {
+          await createBlackoutSplit(windows);
+          assert.fail("Expected error not thrown");
+        } catch (err) {
+          assert.include(err.toString(), "InvalidBlackoutWindow");
+        }
+      }
+    });
+  });
+
+  describe("Recipient Total Tests", () => {
+    const assertTotal = (pda: anchor.web3.PublicKey, recipient: anchor.web3.PublicKey, expected: number) =>
+      program.methods
+        .assertRecipientTotal(recipient, new anchor.BN(expected))
+        .accounts({ splitConfig: pda })
+        .rpc();
+
+    let pda: anchor.web3.PublicKey;
+
+    before(async () => {
+      pda = await createSplit(60, 40);
+
+      await program.methods
+        .depositToSplit(new anchor.BN(10_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      for (const amount of [1_000_000, 2_000_000, 1_001]) {
+        await program.methods
+          .executeFromDeposit(new anchor.BN(amount))
+          .accounts({
+            splitConfig: pda,
+            executor: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+          })
+          .rpc();
+      }
+    });
+
+    it("Passes for each recipient's cumulative total", async () => {
+      await assertTotal(pda, recipient1.publicKey, 600_000 + 1_200_000 + 600);
+      await assertTotal(pda, recipient2.publicKey, 400_000 + 800_000 + 401);
+    });
+
+    it("Fails for any other total", async () => {
+      for (const expected of [0, 1_800_599, 1_800_601]) {
+        try {
+          await assertTotal(pda, recipient1.publicKey, expected);
+          assert.fail("Expected error not thrown");
+        } catch (err) {
+          assert.include(err.toString(), "TotalMismatch");
+        }
+      }
+    });
+
+    it("Rejects accounts that are not recipients", async () => {
+      try {
+        await assertTotal(pda, sender.publicKey, 0);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidRecipient");
+      }
+    });
+  });
+
+  describe("Shortfall Policy Tests", () => {
+    const depositTo = (pda: anchor.web3.PublicKey, amount: number) =>
+      program.methods
+        .depositToSplit(new anchor.BN(amount))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    const createShortSplit = async (shortfallPolicy: any, deposit: number, allowPartial = true) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          shortfallPolicy,
+          allowPartial,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await depositTo(pda, deposit);
+      return pda;
+    };
+
+    // Executes and returns what each recipient received
+    const execute = async (pda: anchor.web3.PublicKey, amount: number) => {
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+
+      const tx = await program.methods
+        .executeFromDeposit(new anchor.BN(amount))
+        .accounts({
+          splitConfig: pda,
+          executor: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const recipient1After = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2After = await provider.connection.getBalance(recipient2.publicKey);
+      const partial = (await getEvents(tx)).find((e) => e.name === "partialExecution");
+      return {
+        paid: [recipient1After - recipient1Before, recipient2After - recipient2Before],
+        partial,
+      };
+    };
+
+    // 1M splits into 600k / 400k
+    const cases: [string, any, number, number[]][] = [
+      ["slot 2 first, covering slot 2", { prioritizeSlot: { 0: 2 } }, 500_000, [100_000, 400_000]],
+      ["slot 2 first, short of slot 2", { prioritizeSlot: { 0: 2 } }, 300_000, [0, 300_000]],
+      ["slot 1 first, covering slot 1", { prioritizeSlot: { 0: 1 } }, 700_000, [600_000, 100_000]],
+      ["slot 1 first, short of slot 1", { prioritizeSlot: { 0: 1 } }, 500_000, [500_000, 0]],
+      ["pro rata at half funding", { proRata: {} }, 500_000, [300_000, 200_000]],
+      ["pro rata at a tenth", { proRata: {} }, 100_000, [60_000, 40_000]],
+    ];
+
+    for (const [name, policy, deposit, expected] of cases) {
+      it(`Pays ${name}`, async () => {
+        const pda = await createShortSplit(policy, deposit);
+        const { paid, partial } = await execute(pda, 1_000_000);
+
+        assert.deepEqual(paid, expected);
+        assert.deepEqual(partial.data.policy, policy);
+        assert.equal(partial.data.unpaid1.toNumber(), 600_000 - expected[0]);
+        assert.equal(partial.data.unpaid2.toNumber(), 400_000 - expected[1]);
+
+        const splitConfig = await program.account.splitConfig.fetch(pda);
+        assert.isFalse(splitConfig.executed);
+        assert.equal(splitConfig.depositedAmount.toNumber(), 0);
+      });
+    }
+
+    it("Pays in full without a partial event when the deposit covers the amount", async () => {
+      const pda = await createShortSplit({ proRata: {} }, 2_000_000);
+      const { paid, partial } = await execute(pda, 1_000_000);
+
+      assert.deepEqual(paid, [600_000, 400_000]);
+      assert.isUndefined(partial);
+    });
+
+    it("Fails entirely under FailEntirely or without allow_partial", async () => {
+      for (const pda of [
+        await createShortSplit({ failEntirely: {} }, 500_000),
+        await createShortSplit({ proRata: {} }, 500_000, false),
+      ]) {
+        try {
+          await execute(pda, 1_000_000);
+          assert.fail("Expected error not thrown");
+        } catch (err) {
+          assert.include(err.toString(), "InsufficientBalance");
+        }
+      }
+    });
+
+    it("Tops up a recorded shortfall once the deposit is refilled", async () => {
+      const pda = await createShortSplit({ proRata: {} }, 500_000);
+      await execute(pda, 1_000_000);
+
+      await depositTo(pda, 1_000_000);
+      const { paid, partial } = await execute(pda, 0);
+
+      assert.deepEqual(paid, [300_000, 200_000]);
+      assert.isUndefined(partial);
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.recipient1Shortfall.toNumber(), 0);
+      assert.equal(splitConfig.recipient2Shortfall.toNumber(), 0);
+      assert.equal(splitConfig.depositedAmount.toNumber(), 500_000);
+    });
+
+    it("Rejects a zero amount with nothing to top up", async () => {
+      const pda = await createShortSplit({ proRata: {} }, 500_000);
+      try {
+        await execute(pda, 0);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "AmountTooSmall");
+      }
+    });
+
+    it("Rejects prioritizing a slot that does not exist", async () => {
+      try {
+        await createShortSplit({ prioritizeSlot: { 0: 3 } }, 500_000);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidShortfallPolicy");
+      }
+    });
+  });
+
+  describe("Claim Queue Tests", () => {
+    const createQueuedSplit = async (amounts: number[]) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          claimMode: true,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .depositToSplit(new anchor.BN(100_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      for (const amount of amounts) {
+        await program.methods
+          .executeFromDeposit(new anchor.BN(amount))
+          .accounts({
+            splitConfig: pda,
+            executor: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+          })
+          .rpc();
+      }
+
+      return pda;
+    };
+
+    // Claims for recipient 1 and returns the lamports received and paid entries
+    const claim = async (pda: anchor.web3.PublicKey, all: boolean) => {
+      const before = await provider.connection.getBalance(recipient1.publicKey);
+      const method = all ? program.methods.claimAll() : program.methods.claimNext();
+      const signature = await method
+        .accounts({ splitConfig: pda, recipient: recipient1.publicKey })
+        .signers([recipient1])
+        .rpc({ commitment: "confirmed" });
+
+      const after = await provider.connection.getBalance(recipient1.publicKey);
+      const paid = (await getEvents(signature))
+        .filter((e) => e.name === "claimEntryPaid")
+        .map((e) => e.data);
+      return { received: after - before, paid };
+    };
+
+    it("Queues a dated entry per execution", async () => {
+      const pda = await createQueuedSplit([1_000_000, 2_000_000, 3_000_000]);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.deepEqual(
+        splitConfig.recipient1ClaimQueue.map((e) => [e.amount.toNumber(), e.executionCount.toNumber()]),
+        [[600_000, 1], [1_200_000, 2], [1_800_000, 3]]
+      );
+      assert.equal(splitConfig.recipient1Claimable.toNumber(), 3_600_000);
+    });
+
+    it("Pays exactly the oldest entry with claim_next", async () => {
+      const pda = await createQueuedSplit([1_000_000, 2_000_000, 3_000_000]);
+
+      const first = await claim(pda, false);
+      assert.equal(first.received, 600_000);
+      assert.equal(first.paid.length, 1);
+      assert.equal(first.paid[0].executionCount.toNumber(), 1);
+
+      const second = await claim(pda, false);
+      assert.equal(second.received, 1_200_000);
+      assert.equal(second.paid[0].executionCount.toNumber(), 2);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.recipient1ClaimQueue.length, 1);
+      assert.equal(splitConfig.recipient1Claimable.toNumber(), 1_800_000);
+    });
+
+    it("Drains every entry in order with claim_all", async () => {
+      const pda = await createQueuedSplit([1_000_000, 2_000_000, 3_000_000]);
+
+      const { received, paid } = await claim(pda, true);
+      assert.equal(received, 3_600_000);
+      assert.deepEqual(
+        paid.map((e) => e.executionCount.toNumber()),
+        [1, 2, 3]
+      );
+
+      try {
+        await claim(pda, false);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "NothingToClaim");
+      }
+    });
+
+    it("Merges the two oldest entries when the queue overflows", async () => {
+      const pda = await createQueuedSplit(new Array(17).fill(1_000_000));
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      const queue = splitConfig.recipient1ClaimQueue;
+      assert.equal(queue.length, 16);
+      assert.equal(queue[0].amount.toNumber(), 1_200_000);
+      assert.equal(queue[0].executionCount.toNumber(), 1);
+      assert.isTrue(queue[0].merged);
+      assert.equal(queue[1].executionCount.toNumber(), 3);
+      assert.equal(queue[15].executionCount.toNumber(), 17);
+
+      const { received, paid } = await claim(pda, false);
+      assert.equal(received, 1_200_000);
+      assert.isTrue(paid[0].merged);
+    });
+
+    it("Clears the queue when the whole balance is claimed", async () => {
+      const pda = await createQueuedSplit([1_000_000, 2_000_000]);
+
+      await program.methods
+        .claimSplit(0)
+        .accounts({ splitConfig: pda, recipient: recipient1.publicKey })
+        .signers([recipient1])
+        .rpc();
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.recipient1ClaimQueue.length, 0);
+      assert.equal(splitConfig.recipient2ClaimQueue.length, 2);
+    });
+  });
+
+  describe("Close Config Tests", () => {
+    const execute = async (amount: number) =>
+      program.methods
+        .executeSplit(new anchor.BN(amount), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: await createSplit(60, 40),
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    const closeConfig = (admin: anchor.web3.Keypair | null) =>
+      program.methods
+        .closeConfig()
+        .accounts({
+          programConfig: programConfigPda,
+          admin: admin ? admin.publicKey : sender.publicKey,
+        })
+        .signers(admin ? [admin] : [])
+        .rpc();
+
+    before(async () => {
+      await ensureProgramConfig();
+      // A pause that would block the executions below while the config exists
+      await program.methods
+        .setSoftPause(true, new anchor.BN(1_000_000))
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+    });
+
+    // Later suites expect the global config to exist
+    after(async () => {
+      await ensureProgramConfig();
+    });
+
+    it("Rejects closes by anyone but the admin", async () => {
+      try {
+        await closeConfig(anchor.web3.Keypair.generate());
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "UnauthorizedAdmin");
+      }
+    });
+
+    it("Refunds the rent to the admin and lifts the global limits", async () => {
+      const rent = await provider.connection.getBalance(programConfigPda);
+      const adminBefore = await provider.connection.getBalance(sender.publicKey);
+
+      await closeConfig(null);
+
+      const adminAfter = await provider.connection.getBalance(sender.publicKey);
+      assert.approximately(adminAfter - adminBefore, rent, 10_000);
+      assert.isNull(await program.account.programConfig.fetchNullable(programConfigPda));
+
+      // Without the config, executions proceed unrestricted
+      await execute(5_000_000);
+    });
+  });
+
+  describe("Execution Memo Tests", () => {
+    const createMemoSplit = async (requireExecutionMemo: boolean) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          requireExecutionMemo,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const accounts = (pda: anchor.web3.PublicKey) => ({
+      splitConfig: pda,
+      sender: sender.publicKey,
+      recipient1: recipient1.publicKey,
+      recipient2: recipient2.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    });
+
+    const executeWithMemo = (pda: anchor.web3.PublicKey, memo: string) =>
+      program.methods
+        .executeSplitWithMemo(new anchor.BN(1_000_000), new anchor.BN(0), memo)
+        .accounts(accounts(pda))
+        .rpc({ commitment: "confirmed" });
+
+    it("Rejects executions without a memo when one is required", async () => {
+      const pda = await createMemoSplit(true);
+
+      try {
+        await program.methods
+          .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+          .accounts(accounts(pda))
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ExecutionMemoRequired");
+      }
+
+      try {
+        await executeWithMemo(pda, "");
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ExecutionMemoRequired");
+      }
+    });
+
+    it("Executes and records the memo when one is given", async () => {
+      const pda = await createMemoSplit(true);
+
+      const signature = await executeWithMemo(pda, "INV-2024-0042");
+
+      const memo = (await getEvents(signature)).find((e) => e.name === "executionMemo");
+      assert.equal(memo.data.memo, "INV-2024-0042");
+      assert.equal(memo.data.executionCount.toNumber(), 1);
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.isTrue(splitConfig.executed);
+    });
+
+    it("Executes with or without a memo when none is required", async () => {
+      await program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts(accounts(await createMemoSplit(false)))
+        .rpc();
+
+      await executeWithMemo(await createMemoSplit(false), "optional note");
+    });
+
+    it("Rejects memos over the length limit", async () => {
+      const pda = await createMemoSplit(true);
+
+      try {
+        await executeWithMemo(pda, "x".repeat(129));
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ExecutionMemoTooLong");
+      }
+    });
+  });
+
+  describe("Cascade Tests", () => {
+    const member1 = anchor.web3.Keypair.generate();
+    const member2 = anchor.web3.Keypair.generate();
+
+    const createLinkedSplit = async (
+      splitNonce: number,
+      first: anchor.web3.PublicKey,
+      second: anchor.web3.PublicKey,
+      cascades: boolean,
+      options = {}
+    ) => {
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          cascadeTarget1: cascades ? first : null,
+          ...options,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: first,
+          recipient2: second,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const execute = (
+      pda: anchor.web3.PublicKey,
+      first: anchor.web3.PublicKey,
+      remaining: anchor.web3.PublicKey[]
+    ) =>
+      program.methods
+        .executeSplit(new anchor.BN(10_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: first,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(
+          remaining.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+        )
+        .rpc({ commitment: "confirmed" });
+
+    it("Cascades recipient 1's share through a linked child split", async () => {
+      const child = await createLinkedSplit(nonce++, member1.publicKey, member2.publicKey, false);
+      const parent = await createLinkedSplit(nonce++, child, recipient2.publicKey, true);
+
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+      const childBefore = await provider.connection.getBalance(child);
+
+      const signature = await execute(parent, child, [member1.publicKey, member2.publicKey]);
+
+      // 6M cascades to the child, which splits it 60/40 between its members
+      assert.equal(await provider.connection.getBalance(member1.publicKey), 3_600_000);
+      assert.equal(await provider.connection.getBalance(member2.publicKey), 2_400_000);
+      const recipient2After = await provider.connection.getBalance(recipient2.publicKey);
+      assert.equal(recipient2After - recipient2Before, 4_000_000);
+      assert.equal(await provider.connection.getBalance(child), childBefore);
+
+      const childConfig = await program.account.splitConfig.fetch(child);
+      assert.isTrue(childConfig.executed);
+      assert.equal(childConfig.totalDistributed.toNumber(), 6_000_000);
+
+      const cascaded = (await getEvents(signature)).find((e) => e.name === "splitCascaded");
+      assert.isTrue(cascaded.data.child.equals(child));
+      assert.equal(cascaded.data.depth, 1);
+    });
+
+    it("Rejects cycles between linked configs", async () => {
+      const first = nonce++;
+      const second = nonce++;
+      const [firstPda] = getSplitConfigPDA(sender.publicKey, first);
+      const [secondPda] = getSplitConfigPDA(sender.publicKey, second);
+
+      await createLinkedSplit(first, secondPda, recipient2.publicKey, true);
+      await createLinkedSplit(second, firstPda, recipient2.publicKey, true);
+
+      try {
+        await execute(firstPda, secondPda, [firstPda]);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "CascadeCycle");
+      }
+    });
+
+    it("Rejects chains deeper than the limit", async () => {
+      // Four linked levels below the parent, the last one paying out
+      const nonces = [nonce++, nonce++, nonce++, nonce++, nonce++];
+      const pdas = nonces.map((n) => getSplitConfigPDA(sender.publicKey, n)[0]);
+
+      await createLinkedSplit(nonces[4], member1.publicKey, member2.publicKey, false);
+      for (let i = 3; i >= 0; i--) {
+        await createLinkedSplit(nonces[i], pdas[i + 1], recipient2.publicKey, true);
+      }
+
+      try {
+        await execute(pdas[0], pdas[1], [...pdas.slice(2), member1.publicKey, member2.publicKey]);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "CascadeTooDeep");
+      }
+    });
+
+    it("Rejects a child still in its review period", async () => {
+      const child = await createLinkedSplit(nonce++, member1.publicKey, member2.publicKey, false, {
+        reviewPeriodSeconds: 3600,
+      });
+      const parent = await createLinkedSplit(nonce++, child, recipient2.publicKey, true);
+
+      try {
+        await execute(parent, child, [member1.publicKey, member2.publicKey]);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ReviewPeriodActive");
+      }
+    });
+
+    it("Requires recipient 1 to be the cascade target", async () => {
+      const child = await createLinkedSplit(nonce++, member1.publicKey, member2.publicKey, false);
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      try {
+        await program.methods
+          .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+            ...defaultOptions(),
+            cascadeTarget1: child,
+          })
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidCascadeTarget");
+      }
+    });
+  });
+
+  describe("Execute After Swap Tests", () => {
+    const swapper = anchor.web3.Keypair.generate();
+
+    // The system program stands in for the swap program: its transfer to the
+    // sender plays the swap output
+    const setSwapPrograms = (programs: anchor.web3.PublicKey[]) =>
+      program.methods
+        .setSwapPrograms(programs)
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+    const swapAndSplit = async (swapOutput: number, minAmount: number) => {
+      const pda = await createSplit(60, 40);
+      const baseline = await provider.connection.getBalance(sender.publicKey);
+
+      const split = await program.methods
+        .executeSplitAfterSwap(new anchor.BN(baseline), new anchor.BN(minAmount))
+        .accounts({
+          execute: {
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          },
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+
+      const signature = await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: swapper.publicKey,
+            toPubkey: sender.publicKey,
+            lamports: swapOutput,
+          }),
+          split
+        ),
+        [swapper],
+        { commitment: "confirmed" }
+      );
+
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const executed = (await getEvents(signature)).find((e) => e.name === "splitExecuted");
+      return { executed: executed.data, fee: tx.meta.fee };
+    };
+
+    before(async () => {
+      await ensureProgramConfig();
+      await setSwapPrograms([anchor.web3.SystemProgram.programId]);
+
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: sender.publicKey,
+            toPubkey: swapper.publicKey,
+            lamports: 100_000_000,
+          })
+        )
+      );
+    });
+
+    after(async () => {
+      await setSwapPrograms([]);
+    });
+
+    it("Splits exactly the measured swap output", async () => {
+      const { executed, fee } = await swapAndSplit(10_000_000, 1_000_000);
+
+      // The sender also paid the transaction fee out of the measured balance
+      const delta = 10_000_000 - fee;
+      assert.equal(executed.totalAmount.toNumber(), delta);
+      assert.equal(executed.amount1.toNumber(), Math.floor((delta * 60) / 100));
+    });
+
+    it("Rejects swap output below the minimum", async () => {
+      try {
+        await swapAndSplit(2_000_000, 5_000_000);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "SwapOutputTooSmall");
+      }
+    });
+
+    it("Rejects executions not preceded by a whitelisted swap", async () => {
+      await setSwapPrograms([]);
+      try {
+        await swapAndSplit(10_000_000, 1_000_000);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "SwapNotFound");
+      }
+    });
+  });
+
+  describe("Reclaim Queue Tests", () => {
+    const [reclaimQueuePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("reclaim_queue"), sender.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const createDeferredSplit = async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          deferReclaim: true,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const execute = (pda: anchor.web3.PublicKey, reclaimQueue: anchor.web3.PublicKey | null) =>
+      program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          reclaimQueue,
+        })
+        .rpc();
+
+    const sweep = (configs: anchor.web3.PublicKey[]) =>
+      program.methods
+        .sweepReclaim()
+        .accounts({ reclaimQueue: reclaimQueuePda, sender: sender.publicKey })
+        .remainingAccounts(
+          configs.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+        )
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .initReclaimQueue()
+        .accounts({
+          reclaimQueue: reclaimQueuePda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("Requires the reclaim queue to execute a deferred config", async () => {
+      try {
+        await execute(await createDeferredSplit(), null);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ReclaimQueueRequired");
+      }
+    });
+
+    it("Queues executed configs and sweeps them together", async () => {
+      const pdas = [];
+      for (let i = 0; i < 3; i++) {
+        const pda = await createDeferredSplit();
+        await execute(pda, reclaimQueuePda);
+        pdas.push(pda);
+      }
+
+      const queue = await program.account.reclaimQueue.fetch(reclaimQueuePda);
+      assert.deepEqual(
+        queue.configs.map((key) => key.toBase58()),
+        pdas.map((key) => key.toBase58())
+      );
+
+      let rent = 0;
+      for (const pda of pdas) {
+        rent += await provider.connection.getBalance(pda);
+      }
+      const senderBefore = await provider.connection.getBalance(sender.publicKey);
+
+      await sweep(pdas);
+
+      const senderAfter = await provider.connection.getBalance(sender.publicKey);
+      assert.approximately(senderAfter - senderBefore, rent, 10_000);
+      for (const pda of pdas) {
+        assert.isNull(await provider.connection.getAccountInfo(pda));
+      }
+      const swept = await program.account.reclaimQueue.fetch(reclaimQueuePda);
+      assert.equal(swept.configs.length, 0);
+    });
+
+    it("Rejects sweeps whose accounts differ from the queue", async () => {
+      const pda = await createDeferredSplit();
+      await execute(pda, reclaimQueuePda);
+
+      try {
+        await sweep([await createSplit(60, 40)]);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ReclaimAccountsMismatch");
+      }
+
+      await sweep([pda]);
+    });
+
+    it("Keeps queued configs open for the sweep", async () => {
+      const pda = await createDeferredSplit();
+      await execute(pda, reclaimQueuePda);
+
+      try {
+        await program.methods
+          .closeSplit()
+          .accounts({ splitConfig: pda, sender: sender.publicKey })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ReclaimDeferred");
+      }
+
+      await sweep([pda]);
+      assert.isNull(await provider.connection.getAccountInfo(pda));
+    });
+  });
+
+  describe("Review Period Tests", () => {
+    const createReviewedSplit = async (reviewPeriodSeconds: number) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      const signature = await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          reviewPeriodSeconds,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const initialized = (await getEvents(signature)).find((e) => e.name === "splitInitialized");
+      return { pda, initialized: initialized.data };
+    };
+
+    const execute = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    it("Reports the review period at initialization", async () => {
+      const { initialized } = await createReviewedSplit(3);
+      assert.equal(initialized.reviewPeriodSeconds, 3);
+      assert.equal(initialized.schemaVersion, EVENT_SCHEMA_VERSION);
+    });
+
+    it("Rejects execution until the review period has passed", async () => {
+      const { pda } = await createReviewedSplit(3);
+
+      try {
+        await execute(pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ReviewPeriodActive");
+      }
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      const terms = await program.methods
+        .resolveEffectiveTerms(new anchor.BN(1_000_000))
+        .accounts({ splitConfig: pda })
+        .view();
+      assert.equal(terms.blockedUntil.toNumber(), splitConfig.createdAt.toNumber() + 3);
+
+      // Cross the boundary on the cluster clock
+      await sleep(5000);
+      await execute(pda);
+    });
+
+    it("Allows cancelling during the review period", async () => {
+      const { pda } = await createReviewedSplit(3600);
+
+      await program.methods
+        .cancelSplit({ userRequested: {} })
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      assert.isNull(await provider.connection.getAccountInfo(pda));
+    });
+
+    it("Executes immediately with a zero review period", async () => {
+      const { pda } = await createReviewedSplit(0);
+      await execute(pda);
+    });
+  });
+
+  describe("Token Split SOL Fee Tests", () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const [treasuryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury")],
+      program.programId
+    );
+
+    let mint: anchor.web3.PublicKey;
+    let senderTokenAccount: anchor.web3.PublicKey;
+    let recipient1TokenAccount: anchor.web3.PublicKey;
+    let recipient2TokenAccount: anchor.web3.PublicKey;
+
+    const setFee = (fee: number) =>
+      program.methods
+        .setTokenSplitFee(new anchor.BN(fee))
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+    const executeWithFee = async (amount: number, maxFee: number) =>
+      program.methods
+        .executeSplitSplSolFee(new anchor.BN(amount), 0, new anchor.BN(maxFee))
+        .accounts({
+          execute: {
+            splitConfig: await createSplit(60, 40),
+            sender: sender.publicKey,
+            mint,
+            senderTokenAccount,
+            recipient1TokenAccount,
+            recipient2TokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          },
+          programConfig: programConfigPda,
+          treasury: treasuryPda,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    const tokenBalance = async (address: anchor.web3.PublicKey) =>
+      Number((await getAccount(provider.connection, address)).amount);
+
+    before(async () => {
+      await ensureProgramConfig();
+      // Creates the treasury if no earlier suite did
+      await program.methods
+        .setTreasuryStakeholders(
+          anchor.web3.Keypair.generate().publicKey,
+          anchor.web3.Keypair.generate().publicKey,
+          50,
+          50
+        )
+        .accounts({
+          programConfig: programConfigPda,
+          treasury: treasuryPda,
+          admin: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      await setFee(50_000);
+
+      mint = await createMint(provider.connection, payer, sender.publicKey, null, 6);
+      const ata = async (owner: anchor.web3.PublicKey) =>
+        (await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, owner)).address;
+      senderTokenAccount = await ata(sender.publicKey);
+      recipient1TokenAccount = await ata(recipient1.publicKey);
+      recipient2TokenAccount = await ata(recipient2.publicKey);
+      await mintTo(provider.connection, payer, mint, senderTokenAccount, payer, 1_000_000_000);
+    });
+
+    after(async () => {
+      await setFee(0);
+    });
+
+    it("Pays recipients in tokens and the treasury in SOL", async () => {
+      const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+
+      const signature = await executeWithFee(1_000_000, 50_000);
+
+      assert.equal(await tokenBalance(recipient1TokenAccount), 600_000);
+      assert.equal(await tokenBalance(recipient2TokenAccount), 400_000);
+      const treasuryAfter = await provider.connection.getBalance(treasuryPda);
+      assert.equal(treasuryAfter - treasuryBefore, 50_000);
+
+      const collected = (await getEvents(signature)).find((e) => e.name === "solFeeCollected");
+      assert.equal(collected.data.fee.toNumber(), 50_000);
+      assert.isTrue(collected.data.mint.equals(mint));
+    });
+
+    it("Rejects a fee above the signer's maximum", async () => {
+      try {
+        await executeWithFee(1_000_000, 49_999);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "FeeAboveMaximum");
+      }
+    });
+
+    it("Skips the SOL leg when the fee is zero", async () => {
+      await setFee(0);
+      const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+
+      await executeWithFee(1_000_000, 0);
+
+      assert.equal(await provider.connection.getBalance(treasuryPda), treasuryBefore);
+      await setFee(50_000);
+    });
+  });
+
+  describe("Auto Swap Tests", () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const autoSwapBuilt = () =>
+      program.idl.instructions.some((ix) => ix.name === "executeSplitSplAutoSwap");
+
+    let mint: anchor.web3.PublicKey;
+    let senderTokenAccount: anchor.web3.PublicKey;
+    let recipient1TokenAccount: anchor.web3.PublicKey;
+    let recipient2TokenAccount: anchor.web3.PublicKey;
+
+    const swapSpec = (minOutBps: number) => ({
+      targetMint: anchor.web3.Keypair.generate().publicKey,
+      minOutBps,
+      venueProgram: anchor.web3.SystemProgram.programId,
+    });
+
+    const createSwappingSplit = async (minOutBps: number, fallback: boolean) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          recipient2AutoSwap: swapSpec(minOutBps),
+          swapFallbackToOriginal: fallback,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const executeSpl = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .executeSplitSpl(new anchor.BN(1_000_000), 0)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          mint,
+          senderTokenAccount,
+          recipient1TokenAccount,
+          recipient2TokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    const tokenBalance = async (address: anchor.web3.PublicKey) =>
+      Number((await getAccount(provider.connection, address)).amount);
+
+    before(async () => {
+      mint = await createMint(provider.connection, payer, sender.publicKey, null, 6);
+      const ata = async (owner: anchor.web3.PublicKey) =>
+        (await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, owner)).address;
+      senderTokenAccount = await ata(sender.publicKey);
+      recipient1TokenAccount = await ata(recipient1.publicKey);
+      recipient2TokenAccount = await ata(recipient2.publicKey);
+      await mintTo(provider.connection, payer, mint, senderTokenAccount, payer, 1_000_000_000);
+    });
+
+    it("Rejects auto-swap configs in builds without the feature", async function () {
+      if (autoSwapBuilt()) {
+        this.skip();
+      }
+
+      try {
+        await createSwappingSplit(9_900, true);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "AutoSwapDisabled");
+      }
+    });
+
+    // Requires a build with `--features auto-swap`
+    it("Rejects a zero slippage floor", async function () {
+      if (!autoSwapBuilt()) {
+        this.skip();
+      }
+
+      try {
+        await createSwappingSplit(0, true);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidSwapSpec");
+      }
+    });
+
+    it("Pays the original mint when no route is given and fallback is allowed", async function () {
+      if (!autoSwapBuilt()) {
+        this.skip();
+      }
+
+      const r1Before = await tokenBalance(recipient1TokenAccount);
+      const r2Before = await tokenBalance(recipient2TokenAccount);
+
+      const signature = await executeSpl(await createSwappingSplit(9_900, true));
+
+      assert.equal((await tokenBalance(recipient1TokenAccount)) - r1Before, 600_000);
+      assert.equal((await tokenBalance(recipient2TokenAccount)) - r2Before, 400_000);
+      const fallback = (await getEvents(signature)).find((e) => e.name === "autoSwapFallback");
+      assert.equal(fallback.data.slot, 2);
+      assert.equal(fallback.data.amount.toNumber(), 400_000);
+    });
+
+    it("Fails without a route when fallback is not allowed", async function () {
+      if (!autoSwapBuilt()) {
+        this.skip();
+      }
+
+      try {
+        await executeSpl(await createSwappingSplit(9_900, false));
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "AutoSwapUnavailable");
+      }
+    });
+  });
+
+  describe("Large Amount Tests", () => {
+    const U64_MAX = new anchor.BN("18446744073709551615");
+
+    it("Resolves shares of u64::MAX without truncation", async () => {
+      for (const p1 of [1, 60, 99]) {
+        const pda = await createSplit(p1, 100 - p1);
+
+        const terms = await program.methods
+          .resolveEffectiveTerms(U64_MAX)
+          .accounts({ splitConfig: pda })
+          .view();
+
+        const expected1 = U64_MAX.muln(p1).divn(100);
+        assert.isTrue(terms.amount1.eq(expected1));
+        assert.isTrue(terms.amount2.eq(U64_MAX.sub(expected1)));
+      }
+    });
+
+    it("Rejects a u64::MAX execution with a clean error", async () => {
+      const pda = await createSplit(60, 40);
+
+      try {
+        await program.methods
+          .executeSplit(U64_MAX, new anchor.BN(0), null, null)
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InsufficientBalance");
+      }
+    });
+  });
+
+  describe("Preview Distribution Tests", () => {
+    const createWithOptions = async (options: object) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          ...options,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const preview = (pda: anchor.web3.PublicKey, amount: number) =>
+      program.methods
+        .previewDistribution(new anchor.BN(amount))
+        .accounts({ splitConfig: pda })
+        .view();
+
+    const total = (entries: any[]) =>
+      entries.reduce((sum, entry) => sum + entry.amount.toNumber(), 0);
+
+    it("Itemizes a capped execution and matches what it realizes", async () => {
+      const pda = await createWithOptions({ recipient1Cap: new anchor.BN(1_000_000) });
+
+      const result = await preview(pda, 5_000_001);
+      assert.equal(result.entries.length, 3);
+      assert.equal(total(result.entries), 5_000_001);
+
+      const [first, second, overflow] = result.entries;
+      assert.deepEqual(first.kind, { recipient1: {} });
+      assert.isTrue(first.destination.equals(recipient1.publicKey));
+      assert.deepEqual(overflow.kind, { capOverflow: {} });
+      assert.isTrue(overflow.destination.equals(sender.publicKey));
+
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+
+      await program.methods
+        .executeSplit(new anchor.BN(5_000_001), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const recipient1After = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2After = await provider.connection.getBalance(recipient2.publicKey);
+      assert.equal(recipient1After - recipient1Before, first.amount.toNumber());
+      assert.equal(recipient2After - recipient2Before, second.amount.toNumber());
+    });
+
+    it("Itemizes withholding out of the recipient's share", async () => {
+      const taxAuthority = anchor.web3.Keypair.generate().publicKey;
+      const pda = await createWithOptions({
+        withholding: { destination: taxAuthority, bps: 1_000, appliesToSlot: 1 },
+      });
+
+      const result = await preview(pda, 10_000_000);
+      assert.equal(result.entries.length, 3);
+      assert.equal(total(result.entries), 10_000_000);
+
+      // Recipient 1's 6M share nets 5.4M after 10% is withheld
+      const [first, second, withheld] = result.entries;
+      assert.equal(first.amount.toNumber(), 5_400_000);
+      assert.equal(second.amount.toNumber(), 4_000_000);
+      assert.deepEqual(withheld.kind, { withholding: {} });
+      assert.isTrue(withheld.destination.equals(taxAuthority));
+      assert.equal(withheld.amount.toNumber(), 600_000);
+    });
+
+    it("Points claim mode shares at the config", async () => {
+      const pda = await createWithOptions({ claimMode: true });
+
+      const result = await preview(pda, 1_000_003);
+      assert.equal(result.entries.length, 2);
+      assert.equal(total(result.entries), 1_000_003);
+      for (const entry of result.entries) {
+        assert.isTrue(entry.destination.equals(pda));
+      }
+    });
+  });
+
+  describe("Recipient Ledger Tests", () => {
+    // Fresh recipients so ledgers only reflect this suite
+    const ledgerRecipient1 = anchor.web3.Keypair.generate();
+    const ledgerRecipient2 = anchor.web3.Keypair.generate();
+
+    const ledgerPda = (recipient: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("recipient_ledger"), recipient.toBuffer()],
+        program.programId
+      )[0];
+
+    const createLedgerSplit = async (claimMode = false) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          claimMode,
+          updateRecipientLedger: true,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: ledgerRecipient1.publicKey,
+          recipient2: ledgerRecipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const execute = (pda: anchor.web3.PublicKey, amount: number, withLedgers = true) =>
+      program.methods
+        .executeSplit(new anchor.BN(amount), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: ledgerRecipient1.publicKey,
+          recipient2: ledgerRecipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          recipient1Ledger: withLedgers ? ledgerPda(ledgerRecipient1.publicKey) : null,
+          recipient2Ledger: withLedgers ? ledgerPda(ledgerRecipient2.publicKey) : null,
+        })
+        .rpc();
+
+    const fetchLedger = (recipient: anchor.web3.PublicKey) =>
+      program.account.recipientLedger.fetch(ledgerPda(recipient));
+
+    it("Creates ledgers lazily and records paid shares", async () => {
+      await execute(await createLedgerSplit(), 10_000_000);
+
+      const ledger1 = await fetchLedger(ledgerRecipient1.publicKey);
+      assert.isTrue(ledger1.recipient.equals(ledgerRecipient1.publicKey));
+      assert.equal(ledger1.earned.toNumber(), 6_000_000);
+      assert.equal(ledger1.paid.toNumber(), 6_000_000);
+      assert.equal(ledger1.pending.toNumber(), 0);
+      assert.equal((await fetchLedger(ledgerRecipient2.publicKey)).earned.toNumber(), 4_000_000);
+    });
+
+    it("Requires the ledgers on opted-in configs", async () => {
+      try {
+        await execute(await createLedgerSplit(), 10_000_000, false);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "RecipientLedgerRequired");
+      }
+    });
+
+    it("Serializes concurrent executions naming the same recipient", async () => {
+      const before = (await fetchLedger(ledgerRecipient1.publicKey)).earned.toNumber();
+      const [first, second] = [await createLedgerSplit(), await createLedgerSplit()];
+
+      // Both write-lock the same ledgers, so the runtime runs them one after
+      // the other and neither update is lost
+      await Promise.all([execute(first, 10_000_000), execute(second, 20_000_000)]);
+
+      const after = (await fetchLedger(ledgerRecipient1.publicKey)).earned.toNumber();
+      assert.equal(after - before, 18_000_000);
+    });
+
+    it("Tracks claims from pending to paid and closes once settled", async () => {
+      const pda = await createLedgerSplit(true);
+      const paidBefore = (await fetchLedger(ledgerRecipient1.publicKey)).paid.toNumber();
+      await execute(pda, 10_000_000);
+
+      let ledger = await fetchLedger(ledgerRecipient1.publicKey);
+      assert.equal(ledger.pending.toNumber(), 6_000_000);
+
+      const closeLedger = () =>
+        program.methods
+          .closeRecipientLedger()
+          .accounts({
+            recipientLedger: ledgerPda(ledgerRecipient1.publicKey),
+            recipient: ledgerRecipient1.publicKey,
+          })
+          .signers([ledgerRecipient1])
+          .rpc();
+
+      try {
+        await closeLedger();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "LedgerPending");
+      }
+
+      await program.methods
+        .claimSplit(0)
+        .accounts({
+          splitConfig: pda,
+          recipient: ledgerRecipient1.publicKey,
+          recipientLedger: ledgerPda(ledgerRecipient1.publicKey),
+        })
+        .signers([ledgerRecipient1])
+        .rpc();
+
+      ledger = await fetchLedger(ledgerRecipient1.publicKey);
+      assert.equal(ledger.pending.toNumber(), 0);
+      assert.equal(ledger.paid.toNumber() - paidBefore, 6_000_000);
+
+      await closeLedger();
+      assert.isNull(
+        await provider.connection.getAccountInfo(ledgerPda(ledgerRecipient1.publicKey))
+      );
+    });
+  });
+
+  describe("Cancel Cooldown Tests", () => {
+    const createCooledSplit = async (cancelCooldownSeconds: number) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          cancelCooldownSeconds,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const cancel = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .cancelSplit({ userRequested: {} })
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+    it("Rejects cancelling before the cooldown and allows it after", async () => {
+      const pda = await createCooledSplit(3);
+
+      try {
+        await cancel(pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "CancelCooldownActive");
+      }
+
+      // Cross the boundary on the cluster clock
+      await sleep(5000);
+      await cancel(pda);
+      assert.isNull(await provider.connection.getAccountInfo(pda));
+    });
+
+    it("Still allows execution during the cooldown", async () => {
+      const pda = await createCooledSplit(3600);
+
+      await program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+  });
+
+  describe("Creation Fee Tests", () => {
+    const [treasuryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury")],
+      program.programId
+    );
+    const [feeExemptionPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_exemption"), sender.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const setFee = (fee: number) =>
+      program.methods
+        .setCreationFee(new anchor.BN(fee))
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+    const create = async (exempt = false) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      const signature = await program.methods
+        .initializeSplit(60, 40, new anchor.BN(splitNonce), null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          programConfig: programConfigPda,
+          treasury: treasuryPda,
+          feeExemption: exempt ? feeExemptionPda : null,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      return { pda, signature };
+    };
+
+    const cancel = (pda: anchor.web3.PublicKey, withTreasury: boolean) =>
+      program.methods
+        .cancelSplit({ userRequested: {} })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          treasury: withTreasury ? treasuryPda : null,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    const stakeholder1 = anchor.web3.Keypair.generate().publicKey;
+    const stakeholder2 = anchor.web3.Keypair.generate().publicKey;
+
+    before(async () => {
+      await ensureProgramConfig();
+      // Creates the treasury if no earlier suite did
+      await program.methods
+        .setTreasuryStakeholders(stakeholder1, stakeholder2, 50, 50)
+        .accounts({
+          programConfig: programConfigPda,
+          treasury: treasuryPda,
+          admin: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      await setFee(100_000);
+    });
+
+    after(async () => {
+      await setFee(0);
+    });
+
+    it("Collects the fee into the treasury and records it", async () => {
+      const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+
+      const { pda, signature } = await create();
+
+      assert.equal((await provider.connection.getBalance(treasuryPda)) - treasuryBefore, 100_000);
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.creationFeePaid.toNumber(), 100_000);
+      const collected = (await getEvents(signature)).find((e) => e.name === "creationFeeCollected");
+      assert.equal(collected.data.fee.toNumber(), 100_000);
+    });
+
+    it("Refunds the fee when cancelled within a day", async () => {
+      const { pda } = await create();
+
+      try {
+        await cancel(pda, false);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "TreasuryRequired");
+      }
+
+      const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+      const signature = await cancel(pda, true);
+
+      assert.equal(treasuryBefore - (await provider.connection.getBalance(treasuryPda)), 100_000);
+      const refunded = (await getEvents(signature)).find((e) => e.name === "creationFeeRefunded");
+      assert.equal(refunded.data.fee.toNumber(), 100_000);
+    });
+
+    it("Holds refundable fees back from treasury distributions", async () => {
+      const { pda } = await create();
+
+      // Other lamports in the treasury are distributable as usual
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: sender.publicKey,
+            toPubkey: treasuryPda,
+            lamports: 1_000_000,
+          })
+        )
+      );
+      await program.methods
+        .distributeTreasury()
+        .accounts({
+          programConfig: programConfigPda,
+          treasury: treasuryPda,
+          stakeholder1,
+          stakeholder2,
+          admin: sender.publicKey,
+        })
+        .rpc();
+
+      const treasury = await program.account.treasury.fetch(treasuryPda);
+      const reserved = treasury.refundReserve.reduce((sum, fee) => sum + fee.toNumber(), 0);
+      assert.isAtLeast(reserved, 100_000);
+
+      const signature = await cancel(pda, true);
+      const refunded = (await getEvents(signature)).find((e) => e.name === "creationFeeRefunded");
+      assert.equal(refunded.data.fee.toNumber(), 100_000);
+    });
+
+    it("Skips the fee for exempt senders", async () => {
+      await program.methods
+        .grantFeeExemption(sender.publicKey)
+        .accounts({
+          programConfig: programConfigPda,
+          feeExemption: feeExemptionPda,
+          admin: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+      const { pda } = await create(true);
+
+      assert.equal(await provider.connection.getBalance(treasuryPda), treasuryBefore);
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.creationFeePaid.toNumber(), 0);
+
+      await program.methods
+        .revokeFeeExemption()
+        .accounts({
+          programConfig: programConfigPda,
+          feeExemption: feeExemptionPda,
+          admin: sender.publicKey,
+        })
+        .rpc();
+    });
+  });
+
+  describe("Delegate Handoff Tests", () => {
+    const incoming = anchor.web3.Keypair.generate();
+
+    const createProposedSplit = async () => {
+      const pda = await createSplit(60, 40);
+
+      await program.methods
+        .depositToSplit(new anchor.BN(10_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .proposeDelegate(incoming.publicKey, new anchor.BN(0))
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      return pda;
+    };
+
+    const executeAs = (pda: anchor.web3.PublicKey, executor: anchor.web3.Keypair) =>
+      program.methods
+        .executeFromDeposit(new anchor.BN(1_000_000))
+        .accounts({
+          splitConfig: pda,
+          executor: executor.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .signers([executor])
+        .rpc();
+
+    const accept = (pda: anchor.web3.PublicKey, signer: anchor.web3.Keypair) =>
+      program.methods
+        .acceptDelegate()
+        .accounts({ splitConfig: pda, delegate: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    it("Rejects execution by a delegate that has not accepted", async () => {
+      const pda = await createProposedSplit();
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.isTrue(splitConfig.pendingDelegate.equals(incoming.publicKey));
+      assert.isTrue(splitConfig.delegate.equals(anchor.web3.PublicKey.default));
+
+      try {
+        await executeAs(pda, incoming);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "UnauthorizedExecutor");
+      }
+    });
+
+    it("Activates the delegate once it accepts", async () => {
+      const pda = await createProposedSplit();
+
+      try {
+        await accept(pda, anchor.web3.Keypair.generate());
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "UnauthorizedDelegate");
+      }
+
+      await accept(pda, incoming);
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.isTrue(splitConfig.delegate.equals(incoming.publicKey));
+      assert.isTrue(splitConfig.pendingDelegate.equals(anchor.web3.PublicKey.default));
+
+      await executeAs(pda, incoming);
+    });
+  });
+
+  describe("Ratio Tier Tests", () => {
+    const tier = (minAmount: number, recipient1Percentage: number) => ({
+      minAmount: new anchor.BN(minAmount),
+      recipient1Percentage,
+    });
+
+    const createTieredSplit = async (ratioTiers: object[], options: object = {}) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          ratioTiers,
+          ...options,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const table = [tier(5_000_000, 70), tier(20_000_000, 90)];
+
+    const executeAndMeasure = async (amount: number) => {
+      const pda = await createTieredSplit(table);
+      const before = await provider.connection.getBalance(recipient1.publicKey);
+
+      await program.methods
+        .executeSplit(new anchor.BN(amount), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return (await provider.connection.getBalance(recipient1.publicKey)) - before;
+    };
+
+    it("Uses the config's percentage below the lowest bracket", async () => {
+      assert.equal(await executeAndMeasure(1_000_000), 600_000);
+    });
+
+    it("Uses a middle bracket's ratio", async () => {
+      assert.equal(await executeAndMeasure(10_000_000), 7_000_000);
+    });
+
+    it("Uses the highest bracket's ratio from its threshold up", async () => {
+      assert.equal(await executeAndMeasure(20_000_000), 18_000_000);
+    });
+
+    it("Quotes the gross for nets split at a bracket's ratio", async () => {
+      const pda = await createTieredSplit(table);
+
+      const gross = await program.methods
+        .computeGrossForNet(new anchor.BN(7_000_000), new anchor.BN(3_000_000))
+        .accounts({ splitConfig: pda })
+        .view();
+      assert.equal(gross.toNumber(), 10_000_000);
+    });
+
+    it("Releases an escrow at its deposit's bracket", async () => {
+      const pda = await createTieredSplit(table, {
+        escrow: true,
+        acceptanceDeadline: new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+      });
+      await program.methods
+        .depositToSplit(new anchor.BN(10_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const before = await provider.connection.getBalance(recipient1.publicKey);
+      for (const recipient of [recipient1, recipient2]) {
+        await program.methods
+          .confirmAndRelease()
+          .accounts({
+            splitConfig: pda,
+            recipient: recipient.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+          })
+          .signers([recipient])
+          .rpc();
+      }
+
+      assert.equal((await provider.connection.getBalance(recipient1.publicKey)) - before, 7_000_000);
+    });
+
+    it("Refuses to split tokens by lamport thresholds", async () => {
+      const pda = await createTieredSplit(table);
+
+      try {
+        await program.methods
+          .quoteTokenSplit(new anchor.BN(10_000_000), 0)
+          .accounts({ splitConfig: pda })
+          .view();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "RatioTiersUnsupported");
+      }
+    });
+
+    it("Rejects thresholds that don't strictly increase", async () => {
+      try {
+        await createTieredSplit([tier(5_000_000, 70), tier(5_000_000, 90)]);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidRatioTiers");
+      }
+    });
+
+    it("Rejects a bracket that leaves a recipient nothing", async () => {
+      try {
+        await createTieredSplit([tier(5_000_000, 100)]);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidRatioTiers");
+      }
+    });
+  });
+
+  describe("Execution Countdown Tests", () => {
+    const createWithOptions = async (options: object) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          ...options,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const countdown = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .timeUntilExecutable()
+        .accounts({ splitConfig: pda, programConfig: programConfigPda })
+        .view();
+
+    it("Counts down to the end of the review period", async () => {
+      const pda = await createWithOptions({ reviewPeriodSeconds: 3 });
+
+      const before = await countdown(pda);
+      assert.isAbove(before.secondsUntilExecutable.toNumber(), 0);
+      assert.isAtMost(before.secondsUntilExecutable.toNumber(), 3);
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(before.executableAt.toNumber(), splitConfig.createdAt.toNumber() + 3);
+
+      // Cross the boundary on the cluster clock
+      await sleep(5000);
+      const after = await countdown(pda);
+      assert.equal(after.secondsUntilExecutable.toNumber(), 0);
+    });
+
+    it("Reports no expiry for an unlocked config without deadlines", async () => {
+      const result = await countdown(await createWithOptions({}));
+
+      assert.equal(result.secondsUntilExecutable.toNumber(), 0);
+      assert.isNull(result.expiresAt);
+      assert.isNull(result.secondsUntilExpiry);
+    });
+
+    it("Counts down to an escrow's acceptance deadline", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const pda = await createWithOptions({
+        escrow: true,
+        acceptanceDeadline: new anchor.BN(now + 3600),
+      });
+
+      const result = await countdown(pda);
+      assert.equal(result.expiresAt.toNumber(), now + 3600);
+      assert.isAbove(result.secondsUntilExpiry.toNumber(), 3500);
+      assert.isAtMost(result.secondsUntilExpiry.toNumber(), 3600);
+    });
+  });
+
+  describe("Teardown Sender Tests", () => {
+    let owner: anchor.web3.Keypair;
+    let ownerOutbox: anchor.web3.PublicKey;
+    let ownerNonce = 0;
+
+    const createSplit = async (options: object = {}) => {
+      const splitNonce = ownerNonce++;
+      const [pda] = getSplitConfigPDA(owner.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(50, 50, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          ...options,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: owner.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+
+      return pda;
+    };
+
+    const teardown = (accounts: anchor.web3.PublicKey[], dryRun: boolean) =>
+      program.methods
+        .teardownSender(dryRun)
+        .accounts({ sender: owner.publicKey })
+        .remainingAccounts(
+          accounts.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+
+    before(async () => {
+      owner = anchor.web3.Keypair.generate();
+      const airdropSig = await provider.connection.requestAirdrop(
+        owner.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+
+      [ownerOutbox] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("outbox"), owner.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initOutbox()
+        .accounts({
+          outbox: ownerOutbox,
+          sender: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+    });
+
+    it("Reports without closing anything on a dry run", async () => {
+      const pda = await createSplit();
+
+      const signature = await teardown([pda, ownerOutbox], true);
+      const event = (await getEvents(signature)).find((e) => e.name === "senderTornDown");
+      assert.isTrue(event.data.dryRun);
+      assert.equal(event.data.accounts, 2);
+      assert.isAbove(event.data.lamports.toNumber(), 0);
+
+      assert.isNotNull(await provider.connection.getAccountInfo(pda));
+      assert.isNotNull(await provider.connection.getAccountInfo(ownerOutbox));
+    });
+
+    it("Refuses while a config holds escrowed funds", async () => {
+      const funded = await createSplit({ escrow: true });
+      await program.methods
+        .depositToSplit(new anchor.BN(5_000))
+        .accounts({
+          splitConfig: funded,
+          sender: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          outbox: ownerOutbox,
+        })
+        .signers([owner])
+        .rpc();
+
+      try {
+        await teardown([funded, ownerOutbox], false);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "TeardownBlocked");
+      }
+
+      // A dry run still reports the offender
+      const signature = await teardown([funded], true);
+      const event = (await getEvents(signature)).find((e) => e.name === "senderTornDown");
+      assert.equal(event.data.blocked, 1);
+    });
+
+    it("Refuses while a forwarding config holds uncranked receipts", async () => {
+      const forwarding = await createSplit({ forwardReceipts: true });
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: sender.publicKey,
+            toPubkey: forwarding,
+            lamports: 1_000_000,
+          })
+        )
+      );
+
+      const signature = await teardown([forwarding], true);
+      const event = (await getEvents(signature)).find((e) => e.name === "senderTornDown");
+      assert.equal(event.data.blocked, 1);
+    });
+
+    it("Requires the reclaim queue alongside a queued config", async () => {
+      const [ownerQueue] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("reclaim_queue"), owner.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initReclaimQueue()
+        .accounts({
+          reclaimQueue: ownerQueue,
+          sender: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+
+      const deferred = await createSplit({ deferReclaim: true });
+      await program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: deferred,
+          sender: owner.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          reclaimQueue: ownerQueue,
+        })
+        .signers([owner])
+        .rpc();
+
+      try {
+        await teardown([deferred], false);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "TeardownBlocked");
+      }
+
+      await teardown([deferred, ownerQueue], false);
+      assert.isNull(await provider.connection.getAccountInfo(deferred));
+      assert.isNull(await provider.connection.getAccountInfo(ownerQueue));
+    });
+
+    it("Rejects another sender's config", async () => {
+      const nonceValue = nonce++;
+      const [foreign] = getSplitConfigPDA(sender.publicKey, nonceValue);
+      await program.methods
+        .initializeSplit(50, 50, new anchor.BN(nonceValue), null)
+        .accounts({
+          splitConfig: foreign,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      try {
+        await teardown([foreign], true);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidTeardownAccount");
+      }
+    });
+
+    it("Closes configs and the outbox with rent to the sender", async () => {
+      const first = await createSplit();
+      const second = await createSplit();
+      const before = await provider.connection.getBalance(owner.publicKey);
+
+      const signature = await teardown([ownerOutbox, first, second], false);
+      const event = (await getEvents(signature)).find((e) => e.name === "senderTornDown");
+      assert.isFalse(event.data.dryRun);
+      assert.equal(event.data.accounts, 3);
+
+      for (const closed of [first, second, ownerOutbox]) {
+        assert.isNull(await provider.connection.getAccountInfo(closed));
+      }
+      const after = await provider.connection.getBalance(owner.publicKey);
+      assert.isAbove(after, before);
+    });
+  });
+
+  describe("Multi-Recipient Tests", () => {
+    const extraRecipient = anchor.web3.Keypair.generate();
+
+    const createSplit = async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplit(60, 40, new anchor.BN(splitNonce), null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const getRecipientSetPDA = (pda: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("recipient_set"), pda.toBuffer()],
+        program.programId
+      )[0];
+
+    const share = (recipient: anchor.web3.PublicKey, bps: number) => ({ recipient, bps });
+
+    const initRecipientSet = (pda: anchor.web3.PublicKey, shares: object[]) =>
+      program.methods
+        .initRecipientSet(shares)
+        .accounts({
+          splitConfig: pda,
+          recipientSet: getRecipientSetPDA(pda),
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    const executeMulti = (
+      pda: anchor.web3.PublicKey,
+      amount: number,
+      recipients: anchor.web3.PublicKey[],
+      recipientSet: anchor.web3.PublicKey | null
+    ) =>
+      program.methods
+        .executeSplitMulti(new anchor.BN(amount))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          recipientSet,
+        })
+        .remainingAccounts(
+          recipients.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .rpc({ commitment: "confirmed" });
+
+    const executeFast = (pda: anchor.web3.PublicKey, amount: number) =>
+      program.methods
+        .executeSplit(new anchor.BN(amount), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    // Balance changes of both recipients and the compute the execution used
+    const measure = async (execute: () => Promise<string>) => {
+      const before1 = await provider.connection.getBalance(recipient1.publicKey, "confirmed");
+      const before2 = await provider.connection.getBalance(recipient2.publicKey, "confirmed");
+      const signature = await execute();
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return {
+        delta1: (await provider.connection.getBalance(recipient1.publicKey, "confirmed")) - before1,
+        delta2: (await provider.connection.getBalance(recipient2.publicKey, "confirmed")) - before2,
+        computeUnits: tx.meta.computeUnitsConsumed,
+      };
+    };
+
+    const amount = 1_000_001;
+    const pair = [recipient1.publicKey, recipient2.publicKey];
+
+    it("Pays a two-recipient config identically on both paths", async () => {
+      const fastPda = await createSplit();
+      const multiPda = await createSplit();
+
+      const fast = await measure(() => executeFast(fastPda, amount));
+      const multi = await measure(() => executeMulti(multiPda, amount, pair, null));
+
+      assert.equal(fast.delta1, 600_000);
+      assert.equal(fast.delta2, 400_001);
+      assert.equal(multi.delta1, fast.delta1);
+      assert.equal(multi.delta2, fast.delta2);
+    });
+
+    it("Pays a two-entry recipient set like the fast path, for more compute", async () => {
+      const fastPda = await createSplit();
+      const setPda = await createSplit();
+      const recipientSet = getRecipientSetPDA(setPda);
+      await initRecipientSet(setPda, [share(recipient1.publicKey, 6000), share(recipient2.publicKey, 4000)]);
+
+      const fast = await measure(() => executeFast(fastPda, amount));
+      const multi = await measure(() => executeMulti(setPda, amount, pair, recipientSet));
+
+      assert.equal(multi.delta1, fast.delta1);
+      assert.equal(multi.delta2, fast.delta2);
+      assert.isBelow(fast.computeUnits, multi.computeUnits);
+    });
+
+    it("Splits across three recipients with the remainder to the last", async () => {
+      const pda = await createSplit();
+      const recipientSet = getRecipientSetPDA(pda);
+      const recipients = [...pair, extraRecipient.publicKey];
+      await initRecipientSet(pda, [
+        share(recipient1.publicKey, 5000),
+        share(recipient2.publicKey, 3000),
+        share(extraRecipient.publicKey, 2000),
+      ]);
+
+      const signature = await executeMulti(pda, 1_000_000_001, recipients, recipientSet);
+      const executed = (await getEvents(signature)).find((e) => e.name === "multiSplitExecuted");
+      assert.deepEqual(
+        executed.data.amounts.map((a: anchor.BN) => a.toNumber()),
+        [500_000_000, 300_000_000, 200_000_001]
+      );
+      assert.equal(
+        await provider.connection.getBalance(extraRecipient.publicKey, "confirmed"),
+        200_000_001
+      );
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.equal(splitConfig.layout, 1);
+      assert.isTrue(splitConfig.executed);
+    });
+
+    it("Keeps multi-recipient configs off the two-recipient path", async () => {
+      const pda = await createSplit();
+      await initRecipientSet(pda, [
+        share(recipient1.publicKey, 5000),
+        share(recipient2.publicKey, 3000),
+        share(extraRecipient.publicKey, 2000),
+      ]);
+
+      try {
+        await executeFast(pda, amount);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "MultiRecipientLayout");
+      }
+    });
+
+    it("Rejects shares that don't total 10000 bps", async () => {
+      const pda = await createSplit();
+
+      try {
+        await initRecipientSet(pda, [share(recipient1.publicKey, 5000), share(recipient2.publicKey, 4000)]);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidRecipientShares");
+      }
+    });
+  });
+
+  describe("Token Account Validation Tests", () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    let mint: anchor.web3.PublicKey;
+    let splitPda: anchor.web3.PublicKey;
+
+    const validate = (recipient1TokenAccount: anchor.web3.PublicKey) =>
+      program.methods
+        .validateTokenAccounts()
+        .accounts({
+          splitConfig: splitPda,
+          mint,
+          senderTokenAccount: getAssociatedTokenAddressSync(mint, sender.publicKey),
+          recipient1TokenAccount,
+          recipient2TokenAccount: getAssociatedTokenAddressSync(mint, recipient2.publicKey),
+        })
+        .view();
+
+    before(async () => {
+      mint = await createMint(provider.connection, payer, sender.publicKey, null, 6);
+
+      const splitNonce = nonce++;
+      [splitPda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+      await program.methods
+        .initializeSplit(50, 50, new anchor.BN(splitNonce), null)
+        .accounts({
+          splitConfig: splitPda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("Accepts the canonical associated token accounts", async () => {
+      const mismatched = await validate(getAssociatedTokenAddressSync(mint, recipient1.publicKey));
+      assert.deepEqual(mismatched, []);
+    });
+
+    it("Reports a recipient token account that isn't their associated account", async () => {
+      // recipient2's ATA in recipient1's slot
+      const mismatched = await validate(getAssociatedTokenAddressSync(mint, recipient2.publicKey));
+      assert.deepEqual(mismatched, [{ recipient1: {} }]);
+    });
+  });
+
+  describe("History Digest Tests", () => {
+    // Raw event bytes as logged, in order
+    const loggedEvents = async (signature: string) => {
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return tx.meta.logMessages
+        .filter((line) => line.startsWith("Program data: "))
+        .map((line) => Buffer.from(line.slice("Program data: ".length), "base64"));
+    };
+
+    const replay = (events: Buffer[]) =>
+      events.reduce(
+        (digest, data) => createHash("sha256").update(digest).update(data).digest(),
+        Buffer.alloc(32)
+      );
+
+    const readDigest = (pda: anchor.web3.PublicKey) =>
+      program.methods.readHistoryDigest().accounts({ splitConfig: pda }).view();
+
+    const createSplit = async (trackHistoryDigest: boolean) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      const signature = await program.methods
+        .initializeSplitWithOptions(50, 50, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          trackHistoryDigest,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      return { pda, signature };
+    };
+
+    it("Matches a replay of the config's logged events", async () => {
+      const { pda, signature: initSignature } = await createSplit(true);
+
+      const topicSignature = await program.methods
+        .setTopic([1, 2, 3, 4, 5, 6, 7, 8])
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc({ commitment: "confirmed" });
+
+      const executeSignature = await program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const events: Buffer[] = [];
+      for (const signature of [initSignature, topicSignature, executeSignature]) {
+        events.push(...(await loggedEvents(signature)));
+      }
+      assert.isAtLeast(events.length, 3);
+
+      const digest = Buffer.from(await readDigest(pda));
+      assert.isTrue(digest.equals(replay(events)));
+
+      // Dropping any event breaks the match
+      assert.isFalse(digest.equals(replay(events.slice(1))));
+    });
+
+    it("Refuses to read the digest of a config that doesn't track one", async () => {
+      const { pda } = await createSplit(false);
+
+      try {
+        await readDigest(pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "HistoryDigestDisabled");
+      }
+    });
+  });
+
+  describe("Forwarding Tests", () => {
+    const createSplit = async (forwardReceipts: boolean) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(70, 30, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          forwardReceipts,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    // A plain transfer to the config's address, as any wallet would send
+    const sendTo = (pda: anchor.web3.PublicKey, lamports: number) =>
+      provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.transfer({
+            fromPubkey: provider.wallet.publicKey,
+            toPubkey: pda,
+            lamports,
+          })
+        )
+      );
+
+    const crank = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .forwardAndSplit()
+        .accounts({
+          splitConfig: pda,
+          cranker: provider.wallet.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .rpc();
+
+    const recipientBalances = async () => [
+      await provider.connection.getBalance(recipient1.publicKey),
+      await provider.connection.getBalance(recipient2.publicKey),
+    ];
+
+    it("Splits SOL sent to the config across deposit and crank cycles", async () => {
+      const pda = await createSplit(true);
+      const rentBalance = await provider.connection.getBalance(pda);
+
+      for (const lamports of [1_000_000, 2_500_000]) {
+        await sendTo(pda, lamports);
+        const [before1, before2] = await recipientBalances();
+        await crank(pda);
+        const [after1, after2] = await recipientBalances();
+
+        assert.equal(after1 - before1, (lamports * 70) / 100);
+        assert.equal(after2 - before2, (lamports * 30) / 100);
+        assert.equal(await provider.connection.getBalance(pda), rentBalance);
+      }
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.isFalse(splitConfig.executed);
+      assert.equal(splitConfig.executionCount.toNumber(), 2);
+      assert.equal(splitConfig.totalDistributed.toNumber(), 3_500_000);
+    });
+
+    it("Rejects a crank with nothing new to forward", async () => {
+      const pda = await createSplit(true);
+
+      try {
+        await crank(pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "AmountTooSmall");
+      }
+    });
+
+    it("Rejects configs that don't forward receipts", async () => {
+      const pda = await createSplit(false);
+      await sendTo(pda, 1_000_000);
+
+      try {
+        await crank(pda);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ForwardingDisabled");
+      }
+    });
+  });
+
+  describe("Recipient Preference Tests", () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    let mint: anchor.web3.PublicKey;
+    let senderAta: anchor.web3.PublicKey;
+    let recipient1Ata: anchor.web3.PublicKey;
+    let recipient1Preferred: anchor.web3.PublicKey;
+    let recipient2Ata: anchor.web3.PublicKey;
+    let splitPda: anchor.web3.PublicKey;
+    let preferencePda: anchor.web3.PublicKey;
+
+    const executeSpl = (recipient1TokenAccount: anchor.web3.PublicKey, withPreference: boolean) =>
+      program.methods
+        .executeSplitSpl(new anchor.BN(1_000), 0)
+        .accounts({
+          splitConfig: splitPda,
+          sender: sender.publicKey,
+          mint,
+          senderTokenAccount: senderAta,
+          recipient1TokenAccount,
+          recipient2TokenAccount: recipient2Ata,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          recipient1Preference: withPreference ? preferencePda : null,
+          recipient2Preference: null,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    before(async () => {
+      const airdropSig = await provider.connection.requestAirdrop(
+        recipient1.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+
+      mint = await createMint(provider.connection, payer, sender.publicKey, null, 6);
+      senderAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, sender.publicKey)
+      ).address;
+      recipient1Ata = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, recipient1.publicKey)
+      ).address;
+      // A second, non-associated account the recipient would rather be paid to
+      recipient1Preferred = await createAccount(
+        provider.connection,
+        payer,
+        mint,
+        recipient1.publicKey,
+        anchor.web3.Keypair.generate()
+      );
+      recipient2Ata = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, recipient2.publicKey)
+      ).address;
+      await mintTo(provider.connection, payer, mint, senderAta, payer, 10_000_000);
+
+      const splitNonce = nonce++;
+      [splitPda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+      await program.methods
+        .initializeSplit(50, 50, new anchor.BN(splitNonce), null)
+        .accounts({
+          splitConfig: splitPda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      [preferencePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("pref"), recipient1.publicKey.toBuffer(), mint.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .setRecipientPreference()
+        .accounts({
+          preference: preferencePda,
+          recipient: recipient1.publicKey,
+          mint,
+          tokenAccount: recipient1Preferred,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([recipient1])
+        .rpc();
+    });
+
+    it("Rejects a destination other than the preferred account", async () => {
+      try {
+        await executeSpl(recipient1Ata, true);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "PreferenceViolation");
+      }
+    });
+
+    it("Pays the preferred account and records that it was honored", async () => {
+      const signature = await executeSpl(recipient1Preferred, true);
+
+      const executed = (await getEvents(signature)).find((e) => e.name === "tokenSplitExecuted");
+      assert.equal(executed.data.schemaVersion, EVENT_SCHEMA_VERSION);
+      assert.deepEqual(executed.data.preferencesHonored, [true, false]);
+      assert.equal(Number((await getAccount(provider.connection, recipient1Preferred)).amount), 500);
+    });
+
+    it("Falls back to any of the recipient's accounts once closed", async () => {
+      await program.methods
+        .closeRecipientPreference()
+        .accounts({ preference: preferencePda, recipient: recipient1.publicKey })
+        .signers([recipient1])
+        .rpc();
+      assert.isNull(await provider.connection.getAccountInfo(preferencePda));
+
+      const signature = await executeSpl(recipient1Ata, false);
+      const executed = (await getEvents(signature)).find((e) => e.name === "tokenSplitExecuted");
+      assert.deepEqual(executed.data.preferencesHonored, [false, false]);
+    });
+  });
+
+  describe("Batch Execution Tests", () => {
+    const createSplit = async (options: object = {}) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(50, 50, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          ...options,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const entryAccounts = (pda: anchor.web3.PublicKey) =>
+      [pda, recipient1.publicKey, recipient2.publicKey].map((pubkey) => ({
+        pubkey,
+        isWritable: true,
+        isSigner: false,
+      }));
+
+    const executeBatch = (configs: anchor.web3.PublicKey[], amounts: number[], allowPartial: boolean) =>
+      program.methods
+        .executeSplitsBatch(
+          amounts.map((amount) => new anchor.BN(amount)),
+          allowPartial
+        )
+        .accounts({
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(configs.flatMap(entryAccounts))
+        .rpc({ commitment: "confirmed" });
+
+    const readReport = async (signature: string) => {
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const [data] = tx.meta.returnData.data;
+      return program.coder.types.decode("batchReport", Buffer.from(data, "base64"));
+    };
+
+    const errorCode = (name: string) =>
+      program.idl.errors.find((e) => e.name.toLowerCase() === name.toLowerCase()).code;
+
+    it("Reports every entry status in partial mode", async () => {
+      const executed = await createSplit();
+      const alreadyExecuted = await createSplit();
+      const inReview = await createSplit({ reviewPeriodSeconds: 3600 });
+      const unaffordable = await createSplit();
+
+      await program.methods
+        .executeSplit(new anchor.BN(10_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: alreadyExecuted,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const signature = await executeBatch(
+        [executed, alreadyExecuted, inReview, unaffordable],
+        [10_000, 10_000, 10_000, 1_000_000_000_000_000],
+        true
+      );
+
+      const report = await readReport(signature);
+      const statuses = report.entries.map((entry) => entry.status);
+      assert.deepEqual(statuses[0], { executed: {} });
+      assert.deepEqual(statuses[1], { skippedAlreadyExecuted: {} });
+      assert.equal(Object.values(statuses[2].failedValidation)[0], errorCode("reviewPeriodActive"));
+      assert.equal(Object.values(statuses[3].failedTransfer)[0], errorCode("insufficientBalance"));
+      assert.equal(report.entries[0].amount1.toNumber(), 5_000);
+      assert.equal(report.entries[3].amount1.toNumber(), 0);
+
+      const summary = (await getEvents(signature)).find((e) => e.name === "batchExecuted");
+      assert.equal(summary.data.executed, 1);
+      assert.equal(summary.data.skipped, 1);
+      assert.equal(summary.data.failed, 2);
+      assert.equal(summary.data.totalDistributed.toNumber(), 10_000);
+
+      assert.isTrue((await program.account.splitConfig.fetch(executed)).executed);
+      assert.isFalse((await program.account.splitConfig.fetch(inReview)).executed);
+    });
+
+    it("Fails the whole batch on the first failing entry otherwise", async () => {
+      const first = await createSplit();
+      const inReview = await createSplit({ reviewPeriodSeconds: 3600 });
+
+      try {
+        await executeBatch([first, inReview], [10_000, 10_000], false);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ReviewPeriodActive");
+      }
+      assert.isFalse((await program.account.splitConfig.fetch(first)).executed);
+    });
+  });
+
+  describe("Bps Upgrade Tests", () => {
+    const createSplit = async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplit(70, 30, new anchor.BN(splitNonce), null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const upgrade = async (configs: anchor.web3.PublicKey[]) => {
+      const signature = await program.methods
+        .batchUpgradeToBps()
+        .accounts({ sender: sender.publicKey })
+        .remainingAccounts(
+          configs.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .rpc({ commitment: "confirmed" });
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const [data] = tx.meta.returnData.data;
+      return Buffer.from(data, "base64").readUInt32LE(0);
+    };
+
+    const executedShares = async (pda: anchor.web3.PublicKey, amount: number) => {
+      const signature = await program.methods
+        .executeSplit(new anchor.BN(amount), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+      const executed = (await getEvents(signature)).find((e) => e.name === "splitExecuted");
+      return [executed.data.amount1.toNumber(), executed.data.amount2.toNumber()];
+    };
+
+    it("Migrates legacy configs and skips already migrated ones", async () => {
+      const migrated = await createSplit();
+      const legacyA = await createSplit();
+      const legacyB = await createSplit();
+
+      assert.equal(await upgrade([migrated]), 1);
+      assert.equal(await upgrade([migrated, legacyA, legacyB]), 2);
+
+      for (const pda of [migrated, legacyA, legacyB]) {
+        const config = await program.account.splitConfig.fetch(pda);
+        assert.equal(config.recipient1Bps, 7_000);
+        assert.equal(config.recipient1Percentage, 70);
+      }
+      assert.equal(await upgrade([migrated, legacyA]), 0);
+    });
+
+    it("Executes migrated configs with the same shares as legacy ones", async () => {
+      const legacy = await createSplit();
+      const migrated = await createSplit();
+      await upgrade([migrated]);
+
+      const legacyShares = await executedShares(legacy, 10_007);
+      const migratedShares = await executedShares(migrated, 10_007);
+      assert.deepEqual(migratedShares, legacyShares);
+      assert.deepEqual(migratedShares, [7_004, 3_003]);
+    });
+
+    it("Rejects configs of another sender", async () => {
+      const stranger = anchor.web3.Keypair.generate();
+      const pda = await createSplit();
+
+      try {
+        await program.methods
+          .batchUpgradeToBps()
+          .accounts({ sender: stranger.publicKey })
+          .remainingAccounts([{ pubkey: pda, isWritable: true, isSigner: false }])
+          .signers([stranger])
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "UnauthorizedSender");
+      }
+    });
+  });
+
+  describe("Staged Split Tests", () => {
+    const LAMPORTS = anchor.web3.LAMPORTS_PER_SOL;
+
+    const createSplit = async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplit(50, 50, new anchor.BN(splitNonce), null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const recipientSetPda = (pda: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("recipient_set"), pda.toBuffer()],
+        program.programId
+      )[0];
+
+    const stagedSplitPda = (pda: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("staged_split"), pda.toBuffer()],
+        program.programId
+      )[0];
+
+    const stage = (pda: anchor.web3.PublicKey, amount: number, shares: object[], withSet = false) =>
+      program.methods
+        .stageSplit(new anchor.BN(amount), shares)
+        .accounts({
+          splitConfig: pda,
+          stagedSplit: stagedSplitPda(pda),
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          recipientSet: withSet ? recipientSetPda(pda) : null,
+        })
+        .rpc();
+
+    const commitPage = (pda: anchor.web3.PublicKey, recipients: anchor.web3.PublicKey[], staged = true) =>
+      program.methods
+        .commitSplitPage()
+        .accounts({
+          splitConfig: pda,
+          stagedSplit: staged ? stagedSplitPda(pda) : null,
+          sender: sender.publicKey,
+          cranker: sender.publicKey,
+        })
+        .remainingAccounts(
+          recipients.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .rpc();
+
+    it("Stages a 25-recipient split and commits it across pages", async () => {
+      const pda = await createSplit();
+      const recipients = Array.from({ length: 25 }, () => anchor.web3.Keypair.generate().publicKey);
+      // 24 recipients at 3.99% and the last taking the remaining 4.24%
+      const shares = recipients.map((recipient, i) => ({ recipient, bps: i < 24 ? 399 : 424 }));
+      const amount = 10 * LAMPORTS + 7;
+
+      await program.methods
+        .initRecipientSet(shares)
+        .accounts({
+          splitConfig: pda,
+          recipientSet: recipientSetPda(pda),
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      // Too many recipients to pay in one transaction
+      try {
+        await program.methods
+          .executeSplitMulti(new anchor.BN(amount))
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            recipientSet: recipientSetPda(pda),
+          })
+          .remainingAccounts(
+            recipients.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+          )
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "RecipientSetTooLarge");
+      }
+
+      await stage(pda, amount, shares, true);
+
+      const staged = await program.account.stagedSplit.fetch(stagedSplitPda(pda));
+      const expected = staged.payouts.map((payout) => payout.amount.toNumber());
+      assert.equal(expected[0], Math.floor((amount * 399) / 10_000));
+      assert.equal(expected.reduce((total, share) => total + share, 0), amount);
+
+      // Executing a staged config through the two-recipient path is refused
+      try {
+        await program.methods
+          .executeSplit(new anchor.BN(10_000), new anchor.BN(0), null, null)
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "MultiRecipientLayout");
+      }
+
+      // Pages must continue the staged order
+      try {
+        await commitPage(pda, recipients.slice(1, 10));
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "StagedPageMismatch");
+      }
+
+      await commitPage(pda, recipients.slice(0, 10));
+      await commitPage(pda, recipients.slice(10, 20));
+      assert.isFalse((await program.account.splitConfig.fetch(pda)).executed);
+
+      await commitPage(pda, recipients.slice(20));
+
+      for (const [i, recipient] of recipients.entries()) {
+        assert.equal(await provider.connection.getBalance(recipient), expected[i]);
+      }
+      const config = await program.account.splitConfig.fetch(pda);
+      assert.isTrue(config.executed);
+      assert.equal(config.layout, 1);
+      assert.equal(config.totalDistributed.toNumber(), amount);
+      assert.isNull(await provider.connection.getAccountInfo(stagedSplitPda(pda)));
+    });
+
+    it("Stages the config's recipient pair at its ratio", async () => {
+      const pda = await createSplit();
+
+      await stage(pda, 1_000_001, [
+        { recipient: recipient1.publicKey, bps: 5000 },
+        { recipient: recipient2.publicKey, bps: 5000 },
+      ]);
+
+      const staged = await program.account.stagedSplit.fetch(stagedSplitPda(pda));
+      assert.deepEqual(
+        staged.payouts.map((payout) => payout.recipient.toBase58()),
+        [recipient1.publicKey.toBase58(), recipient2.publicKey.toBase58()]
+      );
+      assert.deepEqual(
+        staged.payouts.map((payout) => payout.amount.toNumber()),
+        [500_000, 500_001]
+      );
+    });
+
+    it("Rejects shares that differ from the config's recipients", async () => {
+      const pda = await createSplit();
+      const stranger = anchor.web3.Keypair.generate().publicKey;
+
+      for (const shares of [
+        [
+          { recipient: recipient1.publicKey, bps: 9000 },
+          { recipient: recipient2.publicKey, bps: 1000 },
+        ],
+        [
+          { recipient: recipient1.publicKey, bps: 5000 },
+          { recipient: stranger, bps: 5000 },
+        ],
+        [
+          { recipient: recipient1.publicKey, bps: 5000 },
+          { recipient: recipient2.publicKey, bps: 4000 },
+          { recipient: stranger, bps: 1000 },
+        ],
+      ]) {
+        try {
+          await stage(pda, 1_000_000, shares);
+          assert.fail("Expected error not thrown");
+        } catch (err) {
+          assert.include(err.toString(), "StagedSharesMismatch");
+        }
+      }
+      assert.isFalse((await program.account.splitConfig.fetch(pda)).executed);
+    });
+
+    it("Rejects committing an unstaged config", async () => {
+      const pda = await createSplit();
+
+      try {
+        await commitPage(pda, [recipient1.publicKey], false);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "SplitNotStaged");
+      }
+    });
+  });
+
+  describe("Withholding Tests", () => {
+    const taxAuthority = anchor.web3.Keypair.generate();
+    const withholding = (bps: number, appliesToSlot = 2) => ({
+      destination: taxAuthority.publicKey,
+      bps,
+      appliesToSlot,
+    });
+
+    const createSplit = async (options: object) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          ...options,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const execute = (pda: anchor.web3.PublicKey, amount: number, withDestination = true) =>
+      program.methods
+        .executeSplit(new anchor.BN(amount), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(
+          withDestination
+            ? [{ pubkey: taxAuthority.publicKey, isWritable: true, isSigner: false }]
+            : []
+        )
+        .rpc({ commitment: "confirmed" });
+
+    it("Withholds part of the configured slot's share", async () => {
+      const pda = await createSplit({ withholding: withholding(2_000) });
+
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+      const authorityBefore = await provider.connection.getBalance(taxAuthority.publicKey);
+
+      const signature = await execute(pda, 100_000_000);
+
+      assert.equal(
+        (await provider.connection.getBalance(recipient1.publicKey)) - recipient1Before,
+        60_000_000
+      );
+      assert.equal(
+        (await provider.connection.getBalance(recipient2.publicKey)) - recipient2Before,
+        32_000_000
+      );
+      assert.equal(
+        (await provider.connection.getBalance(taxAuthority.publicKey)) - authorityBefore,
+        8_000_000
+      );
+
+      const withheld = (await getEvents(signature)).find((e) => e.name === "shareWithheld");
+      assert.equal(withheld.data.slot, 2);
+      assert.equal(withheld.data.gross.toNumber(), 40_000_000);
+      assert.equal(withheld.data.withheld.toNumber(), 8_000_000);
+      assert.equal(withheld.data.net.toNumber(), 32_000_000);
+    });
+
+    it("Requires the destination to be passed", async () => {
+      const pda = await createSplit({ withholding: withholding(2_000, 1) });
+
+      try {
+        await execute(pda, 100_000_000, false);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "WithholdingDestinationMissing");
+      }
+    });
+
+    it("Rejects withholding more than half a share", async () => {
+      try {
+        await createSplit({ withholding: withholding(5_001) });
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidWithholding");
+      }
+
+      try {
+        await createSplit({
+          withholding: { destination: recipient2.publicKey, bps: 1_000, appliesToSlot: 2 },
+        });
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidWithholding");
+      }
+    });
+
+    it("Resets acceptances when the withholding changes", async () => {
+      const pda = await createSplit({
+        escrow: true,
+        acceptanceDeadline: new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+      });
+
+      await program.methods
+        .depositToSplit(new anchor.BN(100_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .confirmAndRelease()
+        .accounts({
+          splitConfig: pda,
+          recipient: recipient1.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .signers([recipient1])
+        .rpc();
+      assert.isTrue((await program.account.splitConfig.fetch(pda)).recipient1Accepted);
+
+      await program.methods
+        .setWithholding(withholding(1_000))
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      const config = await program.account.splitConfig.fetch(pda);
+      assert.isFalse(config.recipient1Accepted);
+      assert.equal(config.withholding.bps, 1_000);
+    });
+  });
+
+  describe("Compact Event Tests", () => {
+    const createSplit = async (compactEvents: boolean) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(70, 30, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          compactEvents,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const execute = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .executeSplit(new anchor.BN(1_000_003), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    // Decode the byte layout documented on SplitExecuted::compact
+    const decodeCompact = (data: Buffer) => ({
+      schemaVersion: data.readUInt8(3),
+      sender: new anchor.web3.PublicKey(data.subarray(4, 36)),
+      nonce: new anchor.BN(data.subarray(36, 44), "le"),
+      amount1: new anchor.BN(data.subarray(44, 52), "le"),
+      amount2: new anchor.BN(data.subarray(52, 60), "le"),
+      totalAmount: new anchor.BN(data.subarray(60, 68), "le"),
+      topic: Array.from(data.subarray(68, 76)),
+      slot: new anchor.BN(data.subarray(76, 84), "le"),
+      timestamp: new anchor.BN(data.subarray(84, 92), "le"),
+    });
+
+    it("Logs a byte-packed record matching the verbose event", async () => {
+      const verbosePda = await createSplit(false);
+      const compactPda = await createSplit(true);
+
+      const verboseSignature = await execute(verbosePda);
+      const verbose = (await getEvents(verboseSignature)).find((e) => e.name === "splitExecuted");
+
+      const compactSignature = await execute(compactPda);
+      assert.isUndefined((await getEvents(compactSignature)).find((e) => e.name === "splitExecuted"));
+
+      const tx = await provider.connection.getTransaction(compactSignature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const records = tx.meta.logMessages
+        .filter((log) => log.startsWith("Program data: "))
+        .map((log) => Buffer.from(log.slice("Program data: ".length), "base64"))
+        .filter((data) => data.subarray(0, 3).toString() === "ssx");
+      assert.lengthOf(records, 1);
+      assert.equal(records[0].length, 92);
+
+      const compact = decodeCompact(records[0]);
+      const config = await program.account.splitConfig.fetch(compactPda);
+      assert.equal(compact.schemaVersion, EVENT_SCHEMA_VERSION);
+      assert.isTrue(compact.sender.equals(verbose.data.sender));
+      assert.isTrue(compact.nonce.eq(config.nonce));
+      assert.isTrue(compact.amount1.eq(verbose.data.amount1));
+      assert.isTrue(compact.amount2.eq(verbose.data.amount2));
+      assert.isTrue(compact.totalAmount.eq(verbose.data.totalAmount));
+      assert.deepEqual(compact.topic, verbose.data.topic);
+      assert.equal(compact.slot.toNumber(), tx.slot);
+      assert.closeTo(compact.timestamp.toNumber(), tx.blockTime, 5);
+    });
+  });
+
+  describe("Execute And Close Tests", () => {
+    const createSplit = async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplit(60, 40, new anchor.BN(splitNonce), null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const executeIx = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction();
+
+    const closeIx = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .closeSplit()
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .instruction();
+
+    const setAllowComposedClose = (allowed: boolean) =>
+      program.methods
+        .setAllowComposedClose(allowed)
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+    before(async () => {
+      await ensureProgramConfig();
+    });
+
+    it("Rejects closing after an execution in the same transaction", async () => {
+      const pda = await createSplit();
+      const tx = new anchor.web3.Transaction().add(await executeIx(pda), await closeIx(pda));
+
+      try {
+        await provider.sendAndConfirm(tx);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        // Raw transactions carry the error name in their logs
+        assert.include(err.toString() + (err.logs ?? []).join("\n"), "UseExecuteAndClose");
+      }
+      assert.isFalse((await program.account.splitConfig.fetch(pda)).executed);
+    });
+
+    it("Rejects closing after a batch execution in the same transaction", async () => {
+      const pda = await createSplit();
+      const batchIx = await program.methods
+        .executeSplitsBatch([new anchor.BN(1_000_000)], false)
+        .accounts({
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: pda, isWritable: true, isSigner: false },
+          { pubkey: recipient1.publicKey, isWritable: true, isSigner: false },
+          { pubkey: recipient2.publicKey, isWritable: true, isSigner: false },
+        ])
+        .instruction();
+      const tx = new anchor.web3.Transaction().add(batchIx, await closeIx(pda));
+
+      try {
+        await provider.sendAndConfirm(tx);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString() + (err.logs ?? []).join("\n"), "UseExecuteAndClose");
+      }
+      assert.isFalse((await program.account.splitConfig.fetch(pda)).executed);
+    });
+
+    it("Rejects closing after the last staged page in the same transaction", async () => {
+      const pda = await createSplit();
+      const [stagedSplit] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("staged_split"), pda.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .stageSplit(new anchor.BN(1_000_000), [
+          { recipient: recipient1.publicKey, bps: 6_000 },
+          { recipient: recipient2.publicKey, bps: 4_000 },
+        ])
+        .accounts({
+          splitConfig: pda,
+          stagedSplit,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const commitIx = await program.methods
+        .commitSplitPage()
+        .accounts({ splitConfig: pda, stagedSplit, sender: sender.publicKey, cranker: sender.publicKey })
+        .remainingAccounts(
+          [recipient1.publicKey, recipient2.publicKey].map((pubkey) => ({
+            pubkey,
+            isWritable: true,
+            isSigner: false,
+          }))
+        )
+        .instruction();
+      const tx = new anchor.web3.Transaction().add(commitIx, await closeIx(pda));
+
+      try {
+        await provider.sendAndConfirm(tx);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString() + (err.logs ?? []).join("\n"), "UseExecuteAndClose");
+      }
+      assert.isFalse((await program.account.splitConfig.fetch(pda)).executed);
+    });
+
+    it("Allows the composed close once the admin opts out", async () => {
+      const pda = await createSplit();
+      await setAllowComposedClose(true);
+
+      try {
+        const tx = new anchor.web3.Transaction().add(await executeIx(pda), await closeIx(pda));
+        await provider.sendAndConfirm(tx);
+        assert.isNull(await provider.connection.getAccountInfo(pda));
+      } finally {
+        await setAllowComposedClose(false);
+      }
+    });
+
+    it("Executes and closes in one instruction", async () => {
+      const pda = await createSplit();
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+
+      await program.methods
+        .executeAndCloseSplit(new anchor.BN(1_000_000), new anchor.BN(0))
+        .accounts({
+          execute: {
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          },
+        })
+        .rpc();
+
+      assert.equal(
+        (await provider.connection.getBalance(recipient1.publicKey)) - recipient1Before,
+        600_000
+      );
+      assert.equal(
+        (await provider.connection.getBalance(recipient2.publicKey)) - recipient2Before,
+        400_000
+      );
+      assert.isNull(await provider.connection.getAccountInfo(pda));
+    });
+  });
+
+  describe("Initialize And Deposit Tests", () => {
+    it("Creates an escrow config holding its initial deposit", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+      const amount = 2_000_000;
+
+      await program.methods
+        .initializeAndDeposit(60, 40, new anchor.BN(splitNonce), new anchor.BN(amount))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const config = await program.account.splitConfig.fetch(pda);
+      assert.isTrue(config.escrow);
+      assert.equal(config.depositedAmount.toNumber(), amount);
+
+      const info = await provider.connection.getAccountInfo(pda);
+      const rent = await provider.connection.getMinimumBalanceForRentExemption(
+        info.data.length
+      );
+      assert.equal(info.lamports, rent + amount);
+    });
+
+    it("Rejects a zero initial deposit", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      try {
+        await program.methods
+          .initializeAndDeposit(60, 40, new anchor.BN(splitNonce), new anchor.BN(0))
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "AmountTooSmall");
+      }
+    });
+  });
+
+  describe("Committed Terms Tests", () => {
+    // Flag bits in the program's canonical order
+    const termsHash = (percentage1: number, percentage2: number, flags: number) =>
+      createHash("sha256")
+        .update("solsplit_terms")
+        .update(recipient1.publicKey.toBuffer())
+        .update(recipient2.publicKey.toBuffer())
+        .update(Buffer.from([percentage1, percentage2]))
+        .update(Buffer.from([flags & 0xff, flags >> 8]))
+        .digest();
+
+    const committedPDA = (splitNonce: number, hash: Buffer) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("split_config"),
+          sender.publicKey.toBuffer(),
+          guardedNonceBytes(splitNonce),
+          hash,
+        ],
+        program.programId
+      )[0];
+
+    it("Derives an address a funder can check against the claimed terms", async () => {
+      const splitNonce = nonce++;
+      // anyone_can_execute is bit 2
+      const pda = committedPDA(splitNonce, termsHash(70, 30, 1 << 2));
+
+      await program.methods
+        .initializeSplitCommitted(70, 30, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          anyoneCanExecute: true,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const config = await program.account.splitConfig.fetch(pda);
+      assert.deepEqual(config.termsHash, [...termsHash(70, 30, 1 << 2)]);
+
+      // Claimed terms that differ in ratio or flags derive other addresses
+      assert.notEqual(
+        committedPDA(splitNonce, termsHash(30, 70, 1 << 2)).toBase58(),
+        pda.toBase58()
+      );
+      assert.notEqual(
+        committedPDA(splitNonce, termsHash(70, 30, 0)).toBase58(),
+        pda.toBase58()
+      );
+    });
+
+    it("Executes and closes through the committed address", async () => {
+      const splitNonce = nonce++;
+      const pda = committedPDA(splitNonce, termsHash(60, 40, 0));
+
+      await program.methods
+        .initializeSplitCommitted(60, 40, new anchor.BN(splitNonce), defaultOptions())
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      await program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.equal(
+        (await provider.connection.getBalance(recipient1.publicKey)) - recipient1Before,
+        600_000
+      );
+
+      await program.methods
+        .closeSplit()
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+      assert.isNull(await provider.connection.getAccountInfo(pda));
+    });
+
+    it("Rejects reconfiguring a committed config", async () => {
+      const splitNonce = nonce++;
+      const pda = committedPDA(splitNonce, termsHash(60, 40, 0));
+
+      await program.methods
+        .initializeSplitCommitted(60, 40, new anchor.BN(splitNonce), defaultOptions())
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .reconfigureSplit(recipient1.publicKey, recipient2.publicKey, 50, 50)
+          .accounts({ splitConfig: pda, sender: sender.publicKey })
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "TermsCommitted");
+      }
+    });
+  });
+
+  describe("Ratio Ramp Tests", () => {
+    const now = () => Math.floor(Date.now() / 1000);
+    // A long ramp keeps clock drift well below one basis point
+    const span = 2_000_000;
+
+    const createRampSplit = async (rampStart: number) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(80, 20, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          ratioRamp: {
+            startRecipient1Percentage: 80,
+            endRecipient1Percentage: 50,
+            rampStart: new anchor.BN(rampStart),
+            rampEnd: new anchor.BN(rampStart + span),
+          },
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const executeRamp = async (rampStart: number) => {
+      const pda = await createRampSplit(rampStart);
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      await program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return (await provider.connection.getBalance(recipient1.publicKey)) - recipient1Before;
+    };
+
+    it("Uses the starting ratio at the ramp start", async () => {
+      assert.equal(await executeRamp(now() + 60), 800_000);
+    });
+
+    it("Interpolates the ratio at the ramp midpoint", async () => {
+      assert.closeTo(await executeRamp(now() - span / 2), 650_000, 100);
+    });
+
+    it("Holds the ending ratio after the ramp end", async () => {
+      assert.equal(await executeRamp(now() - span - 60), 500_000);
+    });
+
+    it("Quotes the gross for nets at the ramp's ratio", async () => {
+      const pda = await createRampSplit(now() - span - 60);
+
+      const gross = await program.methods
+        .computeGrossForNet(new anchor.BN(500_000), new anchor.BN(500_000))
+        .accounts({ splitConfig: pda })
+        .view();
+      assert.equal(gross.toNumber(), 1_000_000);
+    });
+
+    it("Splits tokens at the ramp's ratio", async () => {
+      const payer = (provider.wallet as anchor.Wallet).payer;
+      const mint = await createMint(provider.connection, payer, sender.publicKey, null, 6);
+      const ata = async (owner: anchor.web3.PublicKey) =>
+        (await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, owner)).address;
+      const senderAta = await ata(sender.publicKey);
+      const recipient1Ata = await ata(recipient1.publicKey);
+      const recipient2Ata = await ata(recipient2.publicKey);
+      await mintTo(provider.connection, payer, mint, senderAta, payer, 1_000_000);
+
+      const pda = await createRampSplit(now() - span - 60);
+      const quote = await program.methods
+        .quoteTokenSplit(new anchor.BN(1_000_000), 0)
+        .accounts({ splitConfig: pda })
+        .view();
+      assert.equal(quote.amount1.toNumber(), 500_000);
+
+      await program.methods
+        .executeSplitSpl(new anchor.BN(1_000_000), 0)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          mint,
+          senderTokenAccount: senderAta,
+          recipient1TokenAccount: recipient1Ata,
+          recipient2TokenAccount: recipient2Ata,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const received = await getAccount(provider.connection, recipient1Ata);
+      assert.equal(Number(received.amount), 500_000);
+    });
+
+    it("Rejects a ramp that doesn't move forward in time", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      try {
+        await program.methods
+          .initializeSplitWithOptions(80, 20, new anchor.BN(splitNonce), {
+            ...defaultOptions(),
+            ratioRamp: {
+              startRecipient1Percentage: 80,
+              endRecipient1Percentage: 50,
+              rampStart: new anchor.BN(now()),
+              rampEnd: new anchor.BN(now()),
+            },
+          })
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidRatioRamp");
+      }
+    });
+  });
+
+  describe("Claims Freeze Tests", () => {
+    const createClaimSplit = async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          claimMode: true,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .executeSplit(new anchor.BN(10_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const claim = (pda: anchor.web3.PublicKey, recipient: anchor.web3.Keypair) =>
+      program.methods
+        .claimSplit(0)
+        .accounts({ splitConfig: pda, recipient: recipient.publicKey })
+        .signers([recipient])
+        .rpc();
+
+    it("Blocks claims after the recipient freezes them", async () => {
+      const pda = await createClaimSplit();
+
+      await program.methods
+        .freezeMyClaims()
+        .accounts({ splitConfig: pda, recipient: recipient1.publicKey })
+        .signers([recipient1])
+        .rpc();
+
+      try {
+        await claim(pda, recipient1);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "ClaimsFrozen");
+      }
+
+      // The other slot is unaffected
+      await claim(pda, recipient2);
+    });
+
+    it("Redirects sender-frozen claims to the registered recovery key", async () => {
+      const pda = await createClaimSplit();
+      const recovery = anchor.web3.Keypair.generate();
+
+      // Registered with the old key while it's still usable
+      await program.methods
+        .registerClaimsRecovery(recovery.publicKey)
+        .accounts({ splitConfig: pda, recipient: recipient1.publicKey })
+        .signers([recipient1])
+        .rpc();
+
+      await program.methods
+        .freezeRecipientClaims(1)
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      try {
+        await claim(pda, recipient1);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "ClaimsFrozen");
+      }
+
+      await program.methods
+        .redirectFrozenClaims(recovery.publicKey)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          newRecipient: recovery.publicKey,
+        })
+        .signers([recovery])
+        .rpc();
+
+      const config = await program.account.splitConfig.fetch(pda);
+      assert.equal(config.recipient1.toBase58(), recovery.publicKey.toBase58());
+      assert.deepEqual(config.recipient1ClaimStatus, { open: {} });
+
+      await claim(pda, recovery);
+      assert.equal(
+        await provider.connection.getBalance(recovery.publicKey),
+        6_000_000
+      );
+    });
+
+    it("Rejects redirecting to a key that wasn't registered", async () => {
+      const pda = await createClaimSplit();
+      const impostor = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .freezeRecipientClaims(2)
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      try {
+        await program.methods
+          .redirectFrozenClaims(impostor.publicKey)
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            newRecipient: impostor.publicKey,
+          })
+          .signers([impostor])
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "RecoveryKeyMismatch");
+      }
+    });
+  });
+
+  describe("External Reference Tests", () => {
+    const externalRef = anchor.web3.Keypair.generate().publicKey;
+
+    it("Surfaces the external reference set at init in events", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      const initTx = await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          externalRef,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const initialized = (await getEvents(initTx)).find(
+        (e) => e.name === "splitInitialized"
+      );
+      assert.equal(initialized.data.externalRef.toBase58(), externalRef.toBase58());
+
+      const executeTx = await program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const executed = (await getEvents(executeTx)).find(
+        (e) => e.name === "splitExecuted"
+      );
+      assert.equal(executed.data.externalRef.toBase58(), externalRef.toBase58());
+    });
+
+    it("Updates and clears the reference on an unexecuted config", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplit(60, 40, new anchor.BN(splitNonce), null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const updateTx = await program.methods
+        .updateExternalRef(externalRef)
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc({ commitment: "confirmed" });
+
+      const [updated] = await getEvents(updateTx);
+      assert.equal(updated.name, "externalRefUpdated");
+      assert.equal(updated.data.schemaVersion, EVENT_SCHEMA_VERSION);
+      assert.equal(updated.data.externalRef.toBase58(), externalRef.toBase58());
+      assert.equal(
+        (await program.account.splitConfig.fetch(pda)).externalRef.toBase58(),
+        externalRef.toBase58()
+      );
+
+      await program.methods
+        .updateExternalRef(null)
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+      assert.isNull((await program.account.splitConfig.fetch(pda)).externalRef);
+    });
+  });
+
+  describe("Private Recipient Tests", () => {
+    const privateKey = (recipient: anchor.web3.PublicKey, config: anchor.web3.PublicKey) =>
+      new anchor.web3.PublicKey(
+        createHash("sha256")
+          .update(recipient.toBuffer())
+          .update(config.toBuffer())
+          .digest()
+      );
+
+    const initializeAndExecute = async (privateRecipients: boolean) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      const initTx = await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          privateRecipients,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const executeTx = await program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const initialized = (await getEvents(initTx)).find(
+        (e) => e.name === "splitInitialized"
+      ).data;
+      const executed = (await getEvents(executeTx)).find(
+        (e) => e.name === "splitExecuted"
+      ).data;
+      return { pda, initialized, executed };
+    };
+
+    it("Emits plain recipient keys by default", async () => {
+      const { initialized, executed } = await initializeAndExecute(false);
+
+      for (const event of [initialized, executed]) {
+        assert.equal(event.recipient1.toBase58(), recipient1.publicKey.toBase58());
+        assert.equal(event.recipient2.toBase58(), recipient2.publicKey.toBase58());
+      }
+    });
+
+    it("Emits salted recipient hashes while keeping amounts and account data", async () => {
+      const { pda, initialized, executed } = await initializeAndExecute(true);
+
+      for (const event of [initialized, executed]) {
+        assert.equal(
+          event.recipient1.toBase58(),
+          privateKey(recipient1.publicKey, pda).toBase58()
+        );
+        assert.equal(
+          event.recipient2.toBase58(),
+          privateKey(recipient2.publicKey, pda).toBase58()
+        );
+      }
+      assert.equal(executed.amount1.toNumber(), 600_000);
+      assert.equal(executed.amount2.toNumber(), 400_000);
+
+      const config = await program.account.splitConfig.fetch(pda);
+      assert.equal(config.recipient1.toBase58(), recipient1.publicKey.toBase58());
+      assert.equal(config.recipient2.toBase58(), recipient2.publicKey.toBase58());
+    });
+  });
+
+  describe("Verified Recipient Tests", () => {
+    // The IDL lists recipients as plain accounts, so their signer flags are
+    // set on the built instruction
+    const initializeVerified = async (signers: anchor.web3.Keypair[]) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      const ix = await program.methods
+        .initializeSplitVerified(60, 40, new anchor.BN(splitNonce), defaultOptions())
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .instruction();
+      for (const key of ix.keys) {
+        if (signers.some((signer) => signer.publicKey.equals(key.pubkey))) {
+          key.isSigner = true;
+        }
+      }
+
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(ix), signers);
+      return pda;
+    };
+
+    it("Marks a config verified when both recipients sign", async () => {
+      const pda = await initializeVerified([recipient1, recipient2]);
+
+      const config = await program.account.splitConfig.fetch(pda);
+      assert.isTrue(config.verifiedRecipients);
+    });
+
+    it("Rejects initialization when a recipient doesn't sign", async () => {
+      try {
+        await initializeVerified([recipient1]);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(
+          err.toString() + (err.logs ?? []).join("\n"),
+          "RecipientSignatureRequired"
+        );
+      }
+    });
+  });
+
+  describe("Deterministic Only Tests", () => {
+    const initialize = (splitNonce: number, options: object) =>
+      program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          deterministicOnly: true,
+          ...options,
+        })
+        .accounts({
+          splitConfig: getSplitConfigPDA(sender.publicKey, splitNonce)[0],
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    it("Pays identical shares for executions landing at different times", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+      await initialize(splitNonce, {});
+
+      await program.methods
+        .depositToSplit(new anchor.BN(4_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const execute = async () => {
+        const before = await provider.connection.getBalance(recipient1.publicKey);
+        await program.methods
+          .executeFromDeposit(new anchor.BN(1_000_001))
+          .accounts({
+            splitConfig: pda,
+            executor: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+          })
+          .rpc();
+        return (await provider.connection.getBalance(recipient1.publicKey)) - before;
+      };
+
+      const first = await execute();
+      await sleep(2_000);
+      assert.equal(await execute(), first);
+    });
+
+    it("Rejects remainder rotation and ratio ramps", async () => {
+      for (const options of [
+        { rotateRemainder: true },
+        {
+          ratioRamp: {
+            startRecipient1Percentage: 80,
+            endRecipient1Percentage: 50,
+            rampStart: new anchor.BN(0),
+            rampEnd: new anchor.BN(1_000),
+          },
+        },
+      ]) {
+        try {
+          await initialize(nonce++, options);
+          assert.fail("Should have failed");
+        } catch (err) {
+          assert.include(err.toString(), "NonDeterministicOptionForbidden");
+        }
+      }
+    });
+  });
+
+  describe("Execution Approval Tests", () => {
+    const approvers = [0, 1, 2].map(() => anchor.web3.Keypair.generate());
+
+    const setup = async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          approvers: approvers.map((approver) => approver.publicKey),
+          approvalsRequired: 2,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .depositToSplit(new anchor.BN(2_000_000))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      return pda;
+    };
+
+    const approve = (pda: anchor.web3.PublicKey, approver: anchor.web3.Keypair) =>
+      program.methods
+        .approveExecution()
+        .accounts({ splitConfig: pda, approver: approver.publicKey })
+        .signers([approver])
+        .rpc();
+
+    const execute = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .executeFromDeposit(new anchor.BN(1_000_000))
+        .accounts({
+          splitConfig: pda,
+          executor: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .rpc();
+
+    it("Executes once the threshold is reached, then requires it again", async () => {
+      const pda = await setup();
+      await approve(pda, approvers[0]);
+      await approve(pda, approvers[2]);
+
+      const before = await provider.connection.getBalance(recipient1.publicKey);
+      await execute(pda);
+      assert.equal(
+        (await provider.connection.getBalance(recipient1.publicKey)) - before,
+        600_000
+      );
+
+      const config = await program.account.splitConfig.fetch(pda);
+      assert.equal(config.approvals, 0);
+
+      try {
+        await execute(pda);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "ApprovalsPending");
+      }
+    });
+
+    it("Blocks execution short of the threshold", async () => {
+      const pda = await setup();
+      await approve(pda, approvers[1]);
+      // A repeated approval doesn't count twice
+      await approve(pda, approvers[1]);
+
+      try {
+        await execute(pda);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "ApprovalsPending");
+      }
+    });
+
+    it("Rejects approvals from keys that aren't approvers", async () => {
+      const pda = await setup();
+      try {
+        await approve(pda, anchor.web3.Keypair.generate());
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "NotAnApprover");
+      }
+    });
+  });
+
+  describe("Client Attribution Tests", () => {
+    const clientId = Array.from(anchor.web3.Keypair.generate().publicKey.toBytes().slice(0, 8));
+    const [clientRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("client_registry"), Buffer.from(clientId)],
+      program.programId
+    );
+
+    before(async () => {
+      await ensureProgramConfig();
+      await program.methods
+        .registerClient(clientId, Array(32).fill(7))
+        .accounts({
+          programConfig: programConfigPda,
+          clientRegistry: clientRegistryPda,
+          admin: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("Records a registered initiating client as verified", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+      const signature = await program.methods
+        .initializeSplit(60, 40, new anchor.BN(splitNonce), clientId)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          clientRegistry: clientRegistryPda,
+        })
+        .rpc();
+
+      const splitConfig = await program.account.splitConfig.fetch(pda);
+      assert.deepEqual(splitConfig.client.clientId, clientId);
+      assert.isTrue(splitConfig.client.verified);
+
+      const initialized = (await getEvents(signature)).find((e) => e.name === "splitInitialized");
+      assert.isTrue(initialized.data.client.verified);
+    });
+
+    it("Flags an unregistered executing client as unverified", async () => {
+      const pda = await createSplit(60, 40);
+      const unknownId = [1, 2, 3, 4, 5, 6, 7, 8];
+
+      const signature = await program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), unknownId, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const executed = (await getEvents(signature)).find((e) => e.name === "splitExecuted");
+      assert.deepEqual(executed.data.client.clientId, unknownId);
+      assert.isFalse(executed.data.client.verified);
+    });
+
+    it("Rejects client registration by anyone but the admin", async () => {
+      const stranger = anchor.web3.Keypair.generate();
+      const otherId = [8, 7, 6, 5, 4, 3, 2, 1];
+      // Funded, so only the admin check can fail
+      const airdropSig = await provider.connection.requestAirdrop(
+        stranger.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+
+      try {
+        await program.methods
+          .registerClient(otherId, Array(32).fill(0))
+          .accounts({
+            programConfig: programConfigPda,
+            clientRegistry: anchor.web3.PublicKey.findProgramAddressSync(
+              [Buffer.from("client_registry"), Buffer.from(otherId)],
+              program.programId
+            )[0],
+            admin: stranger.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([stranger])
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "UnauthorizedAdmin");
+      }
+    });
+  });
+
+  describe("Execution Precondition Tests", () => {
+    const termsHash = (percentage1: number, percentage2: number) =>
+      Array.from(
+        createHash("sha256")
+          .update("solsplit_terms")
+          .update(recipient1.publicKey.toBuffer())
+          .update(recipient2.publicKey.toBuffer())
+          .update(Buffer.from([percentage1, percentage2]))
+          .update(Buffer.from([0, 0]))
+          .digest()
+      );
+
+    const preconditions = (overrides: object) => ({
+      expectedExecutionCount: null,
+      expectedTermsHash: null,
+      maxUnixTime: null,
+      ...overrides,
+    });
+
+    const execute = (pda: anchor.web3.PublicKey, pinned: object) =>
+      program.methods
+        .executeSplit(new anchor.BN(1_000_000), new anchor.BN(0), null, preconditions(pinned))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    const expectFailure = async (pda: anchor.web3.PublicKey, pinned: object, error: string) => {
+      try {
+        await execute(pda, pinned);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), error);
+      }
+    };
+
+    it("Executes when every pinned precondition holds", async () => {
+      const pda = await createSplit(60, 40);
+      const before = await provider.connection.getBalance(recipient1.publicKey);
+
+      await execute(pda, {
+        expectedExecutionCount: new anchor.BN(0),
+        expectedTermsHash: termsHash(60, 40),
+        maxUnixTime: new anchor.BN(Math.floor(Date.now() / 1000) + 3_600),
+      });
+
+      assert.equal((await provider.connection.getBalance(recipient1.publicKey)) - before, 600_000);
+    });
+
+    it("Rejects an execution count that moved on", async () => {
+      const pda = await createSplit(60, 40);
+      await expectFailure(pda, { expectedExecutionCount: new anchor.BN(3) }, "StaleExecutionCount");
+    });
+
+    it("Rejects terms changed since signing", async () => {
+      const pda = await createSplit(60, 40);
+      await program.methods
+        .reconfigureSplit(recipient1.publicKey, recipient2.publicKey, 50, 50)
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc();
+
+      await expectFailure(
+        pda,
+        { expectedTermsHash: termsHash(60, 40) },
+        "TermsChangedSinceSigning"
+      );
+    });
+
+    it("Rejects executions landing after the latest allowed time", async () => {
+      const pda = await createSplit(60, 40);
+      await expectFailure(
+        pda,
+        { maxUnixTime: new anchor.BN(Math.floor(Date.now() / 1000) - 3_600) },
+        "ExecutionTooLate"
+      );
+    });
+  });
+
+  describe("Cancel Penalty Tests", () => {
+    const deposit = 1_000_000_000;
+
+    const createPenalizedSplit = async (cancelPenaltyBps: number) => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      await program.methods
+        .initializeSplitWithOptions(60, 40, new anchor.BN(splitNonce), {
+          ...defaultOptions(),
+          escrow: true,
+          cancelPenaltyBps,
+        })
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .depositToSplit(new anchor.BN(deposit))
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      return pda;
+    };
+
+    const confirm = (pda: anchor.web3.PublicKey) =>
+      program.methods
+        .confirmAndRelease()
+        .accounts({
+          splitConfig: pda,
+          recipient: recipient1.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+        })
+        .signers([recipient1])
+        .rpc();
+
+    const refund = (pda: anchor.web3.PublicKey, withRecipients = true) =>
+      program.methods
+        .refundEscrow()
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: withRecipients ? recipient1.publicKey : null,
+          recipient2: withRecipients ? recipient2.publicKey : null,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    it("Pays the penalty to the recipients when an accepted deposit is refunded", async () => {
+      const pda = await createPenalizedSplit(1_000);
+      await confirm(pda);
+
+      const senderBefore = await provider.connection.getBalance(sender.publicKey);
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+
+      const signature = await refund(pda);
+
+      assert.equal(
+        (await provider.connection.getBalance(sender.publicKey)) - senderBefore,
+        900_000_000 - 5000
+      );
+      assert.equal(
+        (await provider.connection.getBalance(recipient1.publicKey)) - recipient1Before,
+        60_000_000
+      );
+      assert.equal(
+        (await provider.connection.getBalance(recipient2.publicKey)) - recipient2Before,
+        40_000_000
+      );
+      const refunded = (await getEvents(signature)).find((e) => e.name === "escrowRefunded");
+      assert.equal(refunded.data.amount.toNumber(), 900_000_000);
+      assert.equal(refunded.data.penalty1.toNumber(), 60_000_000);
+
+      const cancelSignature = await program.methods
+        .cancelSplit({ userRequested: {} })
+        .accounts({ splitConfig: pda, sender: sender.publicKey })
+        .rpc({ commitment: "confirmed" });
+      const cancelled = (await getEvents(cancelSignature)).find((e) => e.name === "splitCancelled");
+      assert.equal(cancelled.data.penalty1.toNumber(), 60_000_000);
+      assert.equal(cancelled.data.penalty2.toNumber(), 40_000_000);
+    });
+
+    it("Refunds in full when no recipient accepted", async () => {
+      const pda = await createPenalizedSplit(1_000);
+      const senderBefore = await provider.connection.getBalance(sender.publicKey);
+
+      await refund(pda, false);
+
+      assert.equal(
+        (await provider.connection.getBalance(sender.publicKey)) - senderBefore,
+        deposit - 5000
+      );
+    });
+
+    it("Requires the recipients to pay out a penalty", async () => {
+      const pda = await createPenalizedSplit(500);
+      await confirm(pda);
+
+      try {
+        await refund(pda, false);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "PenaltyRecipientsRequired");
+      }
+    });
+
+    it("Returns an expired accepted escrow's whole deposit to the sender", async () => {
+      await ensureProgramConfig();
+      const pda = await createPenalizedSplit(1_000);
+      await confirm(pda);
+
+      const setMaxLifetime = (seconds: number) =>
+        program.methods
+          .setMaxConfigLifetime(new anchor.BN(seconds))
+          .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+          .rpc();
+      await setMaxLifetime(1);
+      await sleep(2000);
+
+      const cleaner = anchor.web3.Keypair.generate();
+      const recipient1Before = await provider.connection.getBalance(recipient1.publicKey);
+      const recipient2Before = await provider.connection.getBalance(recipient2.publicKey);
+      const held = await provider.connection.getBalance(pda);
+      const senderBefore = await provider.connection.getBalance(sender.publicKey);
+
+      try {
+        const signature = await program.methods
+          .cleanupExpiredConfig()
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            programConfig: programConfigPda,
+            cleaner: cleaner.publicKey,
+          })
+          .signers([cleaner])
+          .rpc({ commitment: "confirmed" });
+
+        // Permissionless expiry cleanup doesn't trigger the penalty
+        assert.equal(await provider.connection.getBalance(recipient1.publicKey), recipient1Before);
+        assert.equal(await provider.connection.getBalance(recipient2.publicKey), recipient2Before);
+        const senderAfter = await provider.connection.getBalance(sender.publicKey);
+        assert.approximately(senderAfter - senderBefore, held, 10_000);
+        assert.isAbove(senderAfter - senderBefore, deposit);
+
+        const cancelled = (await getEvents(signature)).find((e) => e.name === "splitCancelled");
+        assert.equal(cancelled.data.penalty1.toNumber(), 0);
+        assert.equal(cancelled.data.penalty2.toNumber(), 0);
+      } finally {
+        await setMaxLifetime(0);
+      }
+    });
+
+    it("Rejects penalties above 2500 bps", async () => {
+      try {
+        await createPenalizedSplit(2_501);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidCancelPenalty");
+      }
+    });
+  });
+
+  // Runs last: the guard can't be cleared once set
+  describe("Genesis Guard Tests", () => {
+    const genesisGuard = [9, 8, 7, 6, 5, 4, 3, 2];
+
+    const setGuard = (guard: number[]) =>
+      program.methods
+        .setGenesisGuard(guard)
+        .accounts({ programConfig: programConfigPda, admin: sender.publicKey })
+        .rpc();
+
+    const initialize = (pda: anchor.web3.PublicKey, splitNonce: number) =>
+      program.methods
+        .initializeSplit(60, 40, new anchor.BN(splitNonce), null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    before(async () => {
+      await ensureProgramConfig();
+      await setGuard(genesisGuard);
+    });
+
+    it("Rejects a config derived without the guard", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce);
+
+      try {
+        await initialize(pda, splitNonce);
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ClusterGuardMismatch");
+      }
+      assert.isNull(await provider.connection.getAccountInfo(pda));
+    });
+
+    it("Rejects a committed config derived without the guard", async () => {
+      const splitNonce = nonce++;
+      const hash = createHash("sha256")
+        .update("solsplit_terms")
+        .update(recipient1.publicKey.toBuffer())
+        .update(recipient2.publicKey.toBuffer())
+        .update(Buffer.from([60, 40, 0, 0]))
+        .digest();
+      const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("split_config"), sender.publicKey.toBuffer(), guardedNonceBytes(splitNonce), hash],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .initializeSplitCommitted(60, 40, new anchor.BN(splitNonce), defaultOptions())
+          .accounts({
+            splitConfig: pda,
+            sender: sender.publicKey,
+            recipient1: recipient1.publicKey,
+            recipient2: recipient2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "ClusterGuardMismatch");
+      }
+      assert.isNull(await provider.connection.getAccountInfo(pda));
+    });
+
+    it("Creates configs at guard-mixed addresses", async () => {
+      const splitNonce = nonce++;
+      const [pda] = getSplitConfigPDA(sender.publicKey, splitNonce, genesisGuard);
+
+      await initialize(pda, splitNonce);
+
+      const config = await program.account.splitConfig.fetch(pda);
+      assert.isTrue(
+        config.nonce.toArrayLike(Buffer, "le", 8).equals(guardedNonceBytes(splitNonce, genesisGuard))
+      );
+
+      await program.methods
+        .executeSplit(new anchor.BN(10_000), new anchor.BN(0), null, null)
+        .accounts({
+          splitConfig: pda,
+          sender: sender.publicKey,
+          recipient1: recipient1.publicKey,
+          recipient2: recipient2.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      assert.isTrue((await program.account.splitConfig.fetch(pda)).executed);
+    });
+
+    it("Rejects changing or clearing the guard once set", async () => {
+      for (const guard of [[1, 2, 3, 4, 5, 6, 7, 8], new Array(8).fill(0)]) {
+        try {
+          await setGuard(guard);
+          assert.fail("Expected error not thrown");
+        } catch (err) {
+          assert.include(err.toString(), "GenesisGuardAlreadySet");
+        }
+      }
+      assert.deepEqual(
+        (await program.account.programConfig.fetch(programConfigPda)).genesisGuard,
+        genesisGuard
+      );
+    });
+
+    it("Only lets the admin set the guard", async () => {
+      const stranger = anchor.web3.Keypair.generate();
+
+      try {
+        await program.methods
+          .setGenesisGuard(genesisGuard)
+          .accounts({ programConfig: programConfigPda, admin: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+        assert.fail("Expected error not thrown");
+      } catch (err) {
+        assert.include(err.toString(), "UnauthorizedAdmin");
+      }
+    });
+  });
+});